@@ -4,7 +4,7 @@ use super::{TileDesc, LevelDesc};
 use std::sync::OnceLock;
 
 fn build_level1_tiles() -> &'static [TileDesc] {
-    let arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None }; 81];
+    let arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }; 81];
     // No special tiles for level1 in original definition
     Box::leak(arr.into_boxed_slice())
 }
@@ -18,6 +18,9 @@ pub fn level1() -> &'static LevelDesc {
         width: 3,
         height: 9,
         bpm: 120.0,
+        subdivision: 1,
+        wrap: false,
+        chain_clear: false,
         tiles,
         spawn_points: &[(0, 0), (1, 0), (2, 0)],
         goal_region: &[(1, 8)],
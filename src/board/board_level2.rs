@@ -5,19 +5,19 @@ use std::sync::OnceLock;
 
 fn build_level2_tiles() -> &'static [TileDesc] {
     use ObstacleKind::*;
-    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None }; 81];
-    arr[9 * 3 + 1] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 2] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 3] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 4] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 5] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 6] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[9 * 3 + 7] = TileDesc { obstacle: Some(Block), modifier: None };
-    arr[2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None };
-    arr[9 + 2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None };
-    arr[9 * 2 + 2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None };
-    arr[9 * 5 + 5] = TileDesc { obstacle: Some(TempoShift { mult: 1.35, beats: 4 }), modifier: None };
-    arr[9 * 6 + 6] = TileDesc { obstacle: Some(Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[ ("你", "好") ] }) };
+    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }; 81];
+    arr[9 * 3 + 1] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 2] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 3] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 4] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 5] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 6] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[9 * 3 + 7] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+    arr[2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None, fixed_hanzi: None };
+    arr[9 + 2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None, fixed_hanzi: None };
+    arr[9 * 2 + 2] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None, fixed_hanzi: None };
+    arr[9 * 5 + 5] = TileDesc { obstacle: Some(TempoShift { mult: 1.35, beats: 4 }), modifier: None, fixed_hanzi: None };
+    arr[9 * 6 + 6] = TileDesc { obstacle: Some(Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[ ("你", "好") ] }), fixed_hanzi: None };
     Box::leak(arr.into_boxed_slice())
 }
 
@@ -30,6 +30,9 @@ pub fn level2() -> &'static LevelDesc {
         width: 9,
         height: 9,
         bpm: 126.0,
+        subdivision: 1,
+        wrap: false,
+        chain_clear: false,
         tiles,
         spawn_points: &[
             (0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0),
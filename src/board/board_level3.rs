@@ -4,23 +4,23 @@ use super::{TileDesc, LevelDesc, ObstacleKind, ModifierKind};
 
 pub const LEVEL3_TILES: [TileDesc; 81] = [
     // y = 0
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Teleport { to: (0, 8) }), modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Teleport { to: (0, 8) }), modifier: None, fixed_hanzi: None },
     // y = 1
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Conveyor { dx: 1, dy: 0 }), modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Conveyor { dx: 1, dy: 0 }), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 2
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 3
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 4
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::TempoShift { mult: 1.5, beats: 3 }), modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::TempoShift { mult: 1.5, beats: 3 }), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 5
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Conveyor { dx: -1, dy: 0 }), modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Conveyor { dx: -1, dy: 0 }), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 6
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Block), modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 7
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: Some(ObstacleKind::Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[("水", "火"), ("山", "田")] }) }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: Some(ObstacleKind::Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[("水", "火"), ("山", "田")] }), fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
     // y = 8
-    TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None }, TileDesc { obstacle: None, modifier: None },
+    TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }, TileDesc { obstacle: None, modifier: None, fixed_hanzi: None },
 ];
 
 pub static LEVEL3_HANZI: [(&str, &str); 10] = [
@@ -41,6 +41,9 @@ pub static LEVEL3: LevelDesc = LevelDesc {
     width: 9,
     height: 9,
     bpm: 132.0,
+    subdivision: 1,
+    wrap: false,
+    chain_clear: false,
     tiles: &LEVEL3_TILES,
     spawn_points: &[
         (0, 0),
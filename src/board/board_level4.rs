@@ -14,7 +14,7 @@ pub static LEVEL4_HANZI: [(&str, &str); 6] = [
 
 fn build_level4_tiles() -> &'static [TileDesc] {
     use ObstacleKind::*;
-    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None }; 63];
+    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }; 63];
     for y in 0..9 {
         for x in 0..7 {
             let path_x = if y % 2 == 0 { x == (y % 7) } else { x == 6 - (y % 7) };
@@ -22,6 +22,7 @@ fn build_level4_tiles() -> &'static [TileDesc] {
                 arr[7 * y + x] = TileDesc {
                     obstacle: Some(Block),
                     modifier: None,
+                    fixed_hanzi: None,
                 };
             }
         }
@@ -36,14 +37,17 @@ fn build_level4_tiles() -> &'static [TileDesc] {
     arr[7 * 4 + 3] = TileDesc {
         obstacle: Some(TempoShift { mult: 1.5, beats: 3 }),
         modifier: None,
+        fixed_hanzi: None,
     };
     arr[7 * 5 + 1] = TileDesc {
         obstacle: None,
         modifier: Some(ModifierKind::ScoreMult { factor: 2.0, beats: 4 }),
+        fixed_hanzi: None,
     };
     arr[7 * 7 + 6] = TileDesc {
         obstacle: Some(Teleport { to: (0, 1) }),
         modifier: None,
+        fixed_hanzi: None,
     };
     Box::leak(arr.into_boxed_slice())
 }
@@ -57,6 +61,10 @@ pub fn level4() -> &'static LevelDesc {
         width: 7,
         height: 9,
         bpm: 128.0,
+        subdivision: 1,
+        wrap: false,
+        // Only six hanzi, so same-character runs are common: clear them as chains
+        chain_clear: true,
         tiles,
         spawn_points: &[(0, 0), (0, 1), (0, 2), (0, 3), (0, 4), (0, 5), (0, 6), (0, 7), (0, 8)],
         goal_region: &[(6, 8)],
@@ -17,30 +17,30 @@ pub static LEVEL5_HANZI: [(&str, &str); 9] = [
 
 fn build_level5_tiles() -> &'static [TileDesc] {
     use ObstacleKind::*;
-    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None }; 81];
+    let mut arr: Vec<TileDesc> = vec![TileDesc { obstacle: None, modifier: None, fixed_hanzi: None }; 81];
     for i in 0..9 {
-        arr[i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * 8 + i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * i + 8] = TileDesc { obstacle: Some(Block), modifier: None };
+        arr[i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * 8 + i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * i + 8] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
     }
     for i in 2..7 {
-        arr[9 * 2 + i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * 6 + i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * i + 2] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * i + 6] = TileDesc { obstacle: Some(Block), modifier: None };
+        arr[9 * 2 + i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * 6 + i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * i + 2] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * i + 6] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
     }
     for i in 4..5 {
-        arr[9 * 4 + i] = TileDesc { obstacle: Some(Block), modifier: None };
-        arr[9 * i + 4] = TileDesc { obstacle: Some(Block), modifier: None };
+        arr[9 * 4 + i] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
+        arr[9 * i + 4] = TileDesc { obstacle: Some(Block), modifier: None, fixed_hanzi: None };
     }
-    arr[16] = TileDesc { obstacle: Some(Teleport { to: (7, 1) }), modifier: None };
-    arr[64] = TileDesc { obstacle: Some(Teleport { to: (1, 7) }), modifier: None };
-    arr[32] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None };
-    arr[48] = TileDesc { obstacle: Some(Conveyor { dx: 1, dy: 0 }), modifier: None };
-    arr[10] = TileDesc { obstacle: None, modifier: Some(ModifierKind::ScoreMult { factor: 2.0, beats: 4 }) };
-    arr[70] = TileDesc { obstacle: None, modifier: Some(ModifierKind::SlowHop { factor: 1.5, beats: 3 }) };
-    arr[40] = TileDesc { obstacle: Some(Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[ ("梦", "星"), ("光", "影") ] }) };
+    arr[16] = TileDesc { obstacle: Some(Teleport { to: (7, 1) }), modifier: None, fixed_hanzi: None };
+    arr[64] = TileDesc { obstacle: Some(Teleport { to: (1, 7) }), modifier: None, fixed_hanzi: None };
+    arr[32] = TileDesc { obstacle: Some(Conveyor { dx: 0, dy: 1 }), modifier: None, fixed_hanzi: None };
+    arr[48] = TileDesc { obstacle: Some(Conveyor { dx: 1, dy: 0 }), modifier: None, fixed_hanzi: None };
+    arr[10] = TileDesc { obstacle: None, modifier: Some(ModifierKind::ScoreMult { factor: 2.0, beats: 4 }), fixed_hanzi: None };
+    arr[70] = TileDesc { obstacle: None, modifier: Some(ModifierKind::SlowHop { factor: 1.5, beats: 3 }), fixed_hanzi: None };
+    arr[40] = TileDesc { obstacle: Some(Transform), modifier: Some(ModifierKind::TransformMap { pairs: &[ ("梦", "星"), ("光", "影") ] }), fixed_hanzi: None };
     Box::leak(arr.into_boxed_slice())
 }
 
@@ -53,6 +53,9 @@ pub fn level5() -> &'static LevelDesc {
         width: 9,
         height: 9,
         bpm: 135.0,
+        subdivision: 1,
+        wrap: false,
+        chain_clear: false,
         tiles,
         spawn_points: &[ (1, 1), (1, 7), (7, 1), (7, 7) ],
         goal_region: &[ (4, 4) ],
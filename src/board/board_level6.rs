@@ -1,4 +1,4 @@
-use super::{LevelDesc, TileDesc};
+use super::{LevelDesc, ObstacleKind, TileDesc};
 
 // Small hanzi set for level 6 (exported by parent module)
 pub static LEVEL6_HANZI: &[(&str, &str)] = &[
@@ -18,8 +18,22 @@ pub fn level6() -> &'static LevelDesc {
     let width: u8 = 8;
     let height: u8 = 8;
     let bpm = 138.0;
-    // uniform empty tiles
-    let tiles_vec = vec![TileDesc::default(); (width as usize) * (height as usize)];
+    // mostly empty tiles
+    let mut tiles_vec = vec![TileDesc::default(); (width as usize) * (height as usize)];
+    // spike hazards flanking the central lane
+    for &(x, y) in &[(2usize, 2usize), (5, 2), (2, 5), (5, 5)] {
+        tiles_vec[y * width as usize + x].obstacle = Some(ObstacleKind::Spike);
+    }
+    // pinned phrase leading north from the cat's start: 中国学校
+    let phrase = [("中", "zhong1"), ("国", "guo2"), ("学", "xue2"), ("校", "xiao4")];
+    for (i, &hz) in phrase.iter().enumerate() {
+        tiles_vec[(3 - i) * width as usize + 4].fixed_hanzi = Some(hz);
+    }
+    // a block patrolling the lower corridor, one tile every 2 beats
+    tiles_vec[6 * width as usize + 1].obstacle = Some(ObstacleKind::MovingBlock {
+        path: &[(1, 6), (2, 6), (3, 6), (4, 6), (5, 6), (6, 6), (5, 6), (4, 6), (3, 6), (2, 6)],
+        period_beats: 2,
+    });
     let tiles: &'static [TileDesc] = Box::leak(tiles_vec.into_boxed_slice());
     let spawn_points: &'static [(u8, u8)] = Box::leak(vec![(3u8, 0u8), (4u8, 0u8), (0u8, 3u8)].into_boxed_slice());
     let goal_region: &'static [(u8, u8)] = Box::leak(vec![(3u8, 7u8), (4u8, 7u8)].into_boxed_slice());
@@ -29,6 +43,9 @@ pub fn level6() -> &'static LevelDesc {
         width,
         height,
         bpm,
+        subdivision: 1,
+        wrap: false,
+        chain_clear: false,
         tiles,
         spawn_points,
         goal_region,
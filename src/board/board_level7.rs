@@ -35,6 +35,9 @@ pub fn level7() -> &'static LevelDesc {
         let idx = *y as usize * width as usize + *x as usize;
         tiles_vec[idx].obstacle = Some(ObstacleKind::Block);
     }
+    // Door extending the left wall; its key sits past the right wall
+    tiles_vec[5 * width as usize + 2].obstacle = Some(ObstacleKind::Door { id: 1 });
+    tiles_vec[3 * width as usize + 8].obstacle = Some(ObstacleKind::Key { id: 1 });
     // Add a conveyor on row 1 pushing right
     tiles_vec[width as usize + 3].obstacle = Some(ObstacleKind::Conveyor { dx: 1, dy: 0 });
     tiles_vec[width as usize + 4].obstacle = Some(ObstacleKind::Conveyor { dx: 1, dy: 0 });
@@ -55,6 +58,10 @@ pub fn level7() -> &'static LevelDesc {
         width,
         height,
         bpm,
+        // Boss level refills on eighth notes for extra pressure
+        subdivision: 2,
+        wrap: false,
+        chain_clear: false,
         tiles,
         spawn_points,
         goal_region,
@@ -0,0 +1,140 @@
+// One-call setup for embedders: `start_game_config({...})` starts the board and
+// applies the given options, each of which is optional (a missing field keeps
+// the default). Unknown fields are ignored. Fields are read with `Reflect`
+// rather than serde: the `serde` features are optional and off by default,
+// while this entry point must work in every build, and deserializing a
+// `JsValue` would need serde-wasm-bindgen (or a JSON round trip) on top.
+//   lives        - starting lives, 1..=MAX_LIVES
+//   difficulty   - 0..1; turns adaptive tempo on, starting at this level
+//   ignore_tones - see `set_ignore_tones`
+//   show_hints   - tone marks above the hanzi (`set_tone_marks`)
+//   seed         - deterministic tile refills for this run only (see
+//                  `seed_board_rng`); later runs keep the previous setting
+//   volume       - master volume 0..1 for this board (like `set_volume`, but
+//                  the player's saved volume is left alone)
+//   language     - UI locale (`set_locale`)
+// The falling-note `lanes` option has no board equivalent and is ignored.
+use js_sys::Reflect;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::{
+    BOARD_STATE, MAX_LIVES, RNG_SEED, apply_tempo, metronome, normalize_lang, reseed_rng,
+    start_board_mode,
+};
+
+/// Parsed options; `None` keeps the default.
+#[derive(Default)]
+struct GameConfig {
+    lives: Option<i32>,
+    difficulty: Option<f64>,
+    ignore_tones: Option<bool>,
+    show_hints: Option<bool>,
+    seed: Option<u64>,
+    volume: Option<f64>,
+    language: Option<String>,
+}
+
+fn field(config: &JsValue, name: &str) -> Option<JsValue> {
+    let v = Reflect::get(config, &name.into()).ok()?;
+    (!v.is_undefined() && !v.is_null()).then_some(v)
+}
+
+fn number(config: &JsValue, name: &str) -> Result<Option<f64>, JsValue> {
+    field(config, name)
+        .map(|v| {
+            v.as_f64()
+                .filter(|n| n.is_finite())
+                .ok_or_else(|| JsValue::from_str(&format!("{name} must be a number")))
+        })
+        .transpose()
+}
+
+fn flag(config: &JsValue, name: &str) -> Result<Option<bool>, JsValue> {
+    field(config, name)
+        .map(|v| {
+            v.as_bool()
+                .ok_or_else(|| JsValue::from_str(&format!("{name} must be a boolean")))
+        })
+        .transpose()
+}
+
+impl GameConfig {
+    /// Read the options from a JS object (`undefined` / `null` = all defaults).
+    fn from_js(config: &JsValue) -> Result<Self, JsValue> {
+        if config.is_undefined() || config.is_null() {
+            return Ok(Self::default());
+        }
+        if !config.is_object() {
+            return Err(JsValue::from_str("config must be an object"));
+        }
+        let language = field(config, "language")
+            .map(|v| {
+                v.as_string()
+                    .ok_or_else(|| JsValue::from_str("language must be a string"))
+            })
+            .transpose()?;
+        Ok(GameConfig {
+            lives: number(config, "lives")?.map(|n| clamp_lives(n as i32)),
+            difficulty: number(config, "difficulty")?.map(|d| d.clamp(0.0, 1.0)),
+            ignore_tones: flag(config, "ignore_tones")?,
+            show_hints: flag(config, "show_hints")?,
+            seed: number(config, "seed")?.map(|s| s.max(0.0) as u64),
+            volume: number(config, "volume")?,
+            language,
+        })
+    }
+}
+
+pub(super) fn clamp_lives(lives: i32) -> i32 {
+    lives.clamp(1, MAX_LIVES)
+}
+
+/// Start the board with `config` applied; see the module comment for fields.
+/// Invalid field types are rejected before anything starts.
+#[wasm_bindgen]
+pub fn start_game_config(config: JsValue) -> Result<(), JsValue> {
+    let cfg = GameConfig::from_js(&config)?;
+    // Seed this board's stream, then put the previous seed back for later runs.
+    let previous = cfg.seed.map(|seed| {
+        let previous = RNG_SEED.with(|s| s.replace(Some(seed)));
+        reseed_rng();
+        previous
+    });
+    let started = start_board_mode();
+    if let Some(previous) = previous {
+        RNG_SEED.with(|s| s.set(previous));
+    }
+    started?;
+    // Starting loads the saved volume, so the configured one goes on top;
+    // it is not saved over the player's own setting.
+    if let Some(v) = cfg.volume {
+        metronome::apply_volume(v);
+    }
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            if let Some(lives) = cfg.lives {
+                state.lives = lives;
+            }
+            if let Some(d) = cfg.difficulty {
+                state.adaptive = true;
+                state.difficulty_progress = d;
+                apply_tempo(state, now);
+            }
+            if let Some(on) = cfg.ignore_tones {
+                state.ignore_tones = on;
+            }
+            if let Some(on) = cfg.show_hints {
+                state.show_tone_marks = on;
+            }
+            if let Some(lang) = &cfg.language {
+                state.locale = normalize_lang(lang);
+            }
+        }
+    });
+    Ok(())
+}
@@ -0,0 +1,155 @@
+// Runtime vocabulary supplied by the embedding page. Once set, it replaces the
+// built-in pools (SINGLE_HANZI and the per-level tables) for every level, so
+// tiles refilled from then on come from it. Each entry is a hanzi string and
+// its numbered pinyin, one syllable per character ("ni3hao3" for 你好);
+// `multi` marks multi-character words and must match the hanzi length.
+// `add_hanzi` adds single entries instead: they are kept apart and added to
+// every level's own pool (built-in or runtime); a new `set_dataset` drops
+// them. Entries are leaked to `'static` like the built-in levels, since the
+// grid borrows its strings.
+use js_sys::{Array, JSON, Reflect};
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+
+use super::{BOARD_STATE, level_hanzi_pool};
+
+pub(super) type Pool = &'static [(&'static str, &'static str)];
+type Entry = (&'static str, &'static str);
+
+thread_local! {
+    static DATASET: Cell<Option<Pool>> = const { Cell::new(None) };
+    static EXTRA: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// The runtime pool, if one was set.
+pub(super) fn active() -> Option<Pool> {
+    DATASET.get()
+}
+
+/// Leak `entries` into a `'static` pool.
+pub(super) fn leak(entries: Vec<(String, String)>) -> Pool {
+    let pool: Vec<(&'static str, &'static str)> = entries
+        .into_iter()
+        .map(|(h, p)| -> (&'static str, &'static str) { (h.leak(), p.leak()) })
+        .collect();
+    Box::leak(pool.into_boxed_slice())
+}
+
+fn install(entries: Vec<(String, String)>) {
+    DATASET.set(Some(leak(entries)));
+    EXTRA.with(|e| e.borrow_mut().clear());
+}
+
+/// `base` followed by the `add_hanzi` entries it does not already hold.
+pub(super) fn with_extras(base: Pool) -> Vec<Entry> {
+    EXTRA.with(|e| merged(base, &e.borrow()))
+}
+
+/// `base` followed by the entries of `extra` whose hanzi it does not hold.
+pub(super) fn merged(base: &[Entry], extra: &[Entry]) -> Vec<Entry> {
+    let mut pool = base.to_vec();
+    pool.extend(
+        extra
+            .iter()
+            .filter(|(h, _)| !base.iter().any(|(b, _)| b == h)),
+    );
+    pool
+}
+
+/// An error if `hanzi` is already in `pool`.
+pub(super) fn ensure_new(pool: &[Entry], hanzi: &str) -> Result<(), String> {
+    if pool.iter().any(|(h, _)| *h == hanzi) {
+        return Err(format!("'{hanzi}' is already in the dataset"));
+    }
+    Ok(())
+}
+
+/// Number of numbered-pinyin syllables in `pinyin` (each: lowercase letters,
+/// `v` for ü, then a tone digit 1-5), or `None` if it is malformed.
+pub(super) fn pinyin_syllables(pinyin: &str) -> Option<usize> {
+    let mut count = 0;
+    let mut letters = 0;
+    for c in pinyin.chars() {
+        match c {
+            'a'..='z' => letters += 1,
+            '1'..='5' if letters > 0 => {
+                count += 1;
+                letters = 0;
+            }
+            _ => return None,
+        }
+    }
+    (letters == 0 && count > 0).then_some(count)
+}
+
+/// Check one entry: non-empty fields, well-formed pinyin with one syllable per
+/// character, and `multi` agreeing with the character count.
+pub(super) fn validate_entry(hanzi: &str, pinyin: &str, multi: bool) -> Result<(), String> {
+    if hanzi.is_empty() || pinyin.is_empty() {
+        return Err("hanzi and pinyin must be non-empty".into());
+    }
+    let chars = hanzi.chars().count();
+    match pinyin_syllables(pinyin) {
+        None => Err(format!("invalid pinyin '{pinyin}' (expected e.g. ni3hao3)")),
+        Some(n) if n != chars => Err(format!(
+            "'{pinyin}' has {n} syllable(s) but '{hanzi}' has {chars} character(s)"
+        )),
+        Some(_) if multi != (chars > 1) => Err(format!("'{hanzi}': multi does not match length")),
+        Some(_) => Ok(()),
+    }
+}
+
+/// Replace the active vocabulary with a JSON array of
+/// `{"hanzi": .., "pinyin": .., "multi": ..}` objects. The whole array is
+/// validated first; on error the previous vocabulary stays active.
+#[wasm_bindgen]
+pub fn set_dataset(json: &str) -> Result<(), JsValue> {
+    install(parse_entries(json)?);
+    Ok(())
+}
+
+/// Parse and validate a non-empty JSON array of `{hanzi, pinyin, multi}`.
+pub(super) fn parse_entries(json: &str) -> Result<Vec<(String, String)>, JsValue> {
+    let err = |m: &str| JsValue::from_str(m);
+    let v = JSON::parse(json)?;
+    if !Array::is_array(&v) {
+        return Err(err("expected a JSON array"));
+    }
+    let items = Array::from(&v);
+    if items.length() == 0 {
+        return Err(err("dataset is empty"));
+    }
+    let mut entries = Vec::with_capacity(items.length() as usize);
+    for (i, item) in items.iter().enumerate() {
+        let field = |name: &str| Reflect::get(&item, &name.into()).unwrap_or(JsValue::UNDEFINED);
+        let (Some(hanzi), Some(pinyin)) = (field("hanzi").as_string(), field("pinyin").as_string())
+        else {
+            return Err(err(&format!("entry {i}: missing hanzi or pinyin")));
+        };
+        let multi = field("multi").as_bool().unwrap_or(false);
+        validate_entry(&hanzi, &pinyin, multi).map_err(|m| err(&format!("entry {i}: {m}")))?;
+        entries.push((hanzi, pinyin));
+    }
+    Ok(entries)
+}
+
+/// Add one entry to every level's vocabulary; it can spawn on the next
+/// refill. Rejects invalid pinyin and hanzi that are already present.
+#[wasm_bindgen]
+pub fn add_hanzi(hanzi: &str, pinyin: &str, multi: bool) -> Result<(), JsValue> {
+    validate_entry(hanzi, pinyin, multi).map_err(|m| JsValue::from_str(&m))?;
+    let pool = BOARD_STATE.with(|cell| {
+        cell.borrow().as_ref().map_or_else(
+            || with_extras(active().unwrap_or(crate::SINGLE_HANZI)),
+            |s| level_hanzi_pool(s.level),
+        )
+    });
+    EXTRA.with(|e| {
+        let mut extra = e.borrow_mut();
+        ensure_new(&pool, hanzi)
+            .and_then(|()| ensure_new(&extra, hanzi))
+            .map_err(|m| JsValue::from_str(&m))?;
+        extra.push((hanzi.to_string().leak(), pinyin.to_string().leak()));
+        Ok(())
+    })
+}
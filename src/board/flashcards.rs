@@ -0,0 +1,173 @@
+// Flashcard preview before a session: `show_flashcards(seconds)` cycles the
+// characters the next run will draw from (the active dataset or the opening
+// level's pool, after the spawn filter) through a DOM card showing the hanzi,
+// its pinyin with tone diacritics and its category / HSK level, then starts
+// the game. The time is split evenly between the cards; "Skip" starts at once.
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::summary::escape_html;
+use super::{level_hanzi_pool, levels, vocab};
+
+const OVERLAY_ID: &str = "hc-flashcards";
+/// Upper bound on the cards shown, so each one stays on screen long enough.
+const MAX_CARDS: usize = 20;
+/// Shortest time a card is shown.
+const MIN_CARD_MS: f64 = 600.0;
+
+type Entry = (&'static str, &'static str);
+
+thread_local! {
+    static TIMER: Cell<Option<i32>> = const { Cell::new(None) };
+    static TICK: RefCell<Option<Closure<dyn FnMut()>>> = const { RefCell::new(None) };
+}
+
+/// Numbered pinyin with tone diacritics ("ni3hao3" -> "nǐhǎo", "lv4" -> "lǜ").
+/// The mark goes on a or e, on the o of "ou", otherwise on the last vowel.
+pub(super) fn marked_pinyin(pinyin: &str) -> String {
+    const MARKS: [(char, [char; 4]); 6] = [
+        ('a', ['ā', 'á', 'ǎ', 'à']),
+        ('e', ['ē', 'é', 'ě', 'è']),
+        ('i', ['ī', 'í', 'ǐ', 'ì']),
+        ('o', ['ō', 'ó', 'ǒ', 'ò']),
+        ('u', ['ū', 'ú', 'ǔ', 'ù']),
+        ('ü', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+    ];
+    let mut out = String::new();
+    let mut syllable: Vec<char> = Vec::new();
+    for c in pinyin.chars() {
+        match c {
+            '1'..='5' => {
+                let tone = c as usize - '1' as usize;
+                let at = syllable
+                    .iter()
+                    .position(|&v| v == 'a' || v == 'e')
+                    .or_else(|| syllable.windows(2).position(|w| w == ['o', 'u']))
+                    .or_else(|| syllable.iter().rposition(|v| "iouü".contains(*v)));
+                if let Some(at) = at
+                    && tone < 4
+                    && let Some((_, marks)) = MARKS.iter().find(|(v, _)| *v == syllable[at])
+                {
+                    syllable[at] = marks[tone];
+                }
+                out.extend(syllable.drain(..));
+            }
+            'v' => syllable.push('ü'),
+            _ => syllable.push(c),
+        }
+    }
+    out.extend(syllable);
+    out
+}
+
+/// How long each of `cards` cards stays up when `seconds` are shared out.
+pub(super) fn card_ms(seconds: f64, cards: usize) -> f64 {
+    (seconds * 1000.0 / cards.max(1) as f64).max(MIN_CARD_MS)
+}
+
+/// Cards for the next run: the filtered opening pool (the whole pool if the
+/// filter leaves nothing), at most MAX_CARDS of them.
+fn deck() -> Vec<Entry> {
+    let pool = level_hanzi_pool(levels()[0]);
+    let mut cards = vocab::filtered_pool(&pool);
+    if cards.is_empty() {
+        cards = pool;
+    }
+    cards.truncate(MAX_CARDS);
+    cards
+}
+
+fn card_html((hanzi, pinyin): Entry, index: usize, total: usize) -> String {
+    let gloss = vocab::lookup(hanzi)
+        .map(|i| format!("{} · HSK {}", i.category, i.hsk))
+        .unwrap_or_default();
+    let hanzi = escape_html(hanzi);
+    format!(
+        "<div style='font-family:\"Noto Serif SC\",\"SimSun\",serif; font-size:96px; color:var(--hc-glyph);'>{hanzi}</div>\
+         <div style='font-size:28px; color:var(--hc-accent);'>{}</div>\
+         <div style='font-size:14px; color:var(--hc-text); min-height:1.4em;'>{gloss}</div>\
+         <div style='font-size:12px; color:var(--hc-text); margin-top:6px;'>{} / {total}</div>",
+        escape_html(&marked_pinyin(pinyin)),
+        index + 1,
+    )
+}
+
+/// Stop cycling and remove the overlay without starting the game.
+pub(super) fn cancel() {
+    if let (Some(id), Some(w)) = (TIMER.take(), window()) {
+        w.clear_interval_with_handle(id);
+    }
+    // TICK is left in place: this may run inside it. The next
+    // `show_flashcards` replaces (and drops) it.
+    if let Some(el) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(OVERLAY_ID))
+    {
+        el.remove();
+    }
+}
+
+fn finish() {
+    cancel();
+    crate::start_game().ok();
+}
+
+/// Show each character of the coming run as a flashcard, sharing `seconds`
+/// between them, then start the game. A "Skip" button starts it right away;
+/// with no time (or nothing to show) the game starts immediately.
+#[wasm_bindgen]
+pub fn show_flashcards(seconds: f64) -> Result<(), JsValue> {
+    cancel();
+    let cards = deck();
+    if seconds.is_nan() || seconds <= 0.0 || cards.is_empty() {
+        return crate::start_game();
+    }
+    let win = window().ok_or_else(|| JsValue::from_str("no window"))?;
+    let doc = win
+        .document()
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let overlay = doc.create_element("div")?;
+    overlay.set_id(OVERLAY_ID);
+    overlay.set_attribute("style", "position:fixed; left:50%; top:40%; transform:translate(-50%,-50%); min-width:260px; padding:22px 28px; background:var(--hc-panel); border:2px solid var(--hc-border); border-radius:14px; font-family:'Fira Code', monospace; text-align:center; z-index:60;").ok();
+    overlay.set_inner_html(&format!(
+        "<div id='{OVERLAY_ID}-card'>{}</div>\
+         <button id='{OVERLAY_ID}-skip' style='margin-top:12px; font-family:inherit;'>Skip</button>",
+        card_html(cards[0], 0, cards.len())
+    ));
+    doc.body()
+        .ok_or_else(|| JsValue::from_str("no body"))?
+        .append_child(&overlay)?;
+    if let Some(btn) = doc.get_element_by_id(&format!("{OVERLAY_ID}-skip")) {
+        let closure =
+            Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| finish()) as Box<dyn FnMut(_)>);
+        btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+            .ok();
+        // Removed together with the overlay.
+        closure.forget();
+    }
+
+    let mut shown = 0;
+    let total = cards.len();
+    let tick = Closure::wrap(Box::new(move || {
+        shown += 1;
+        if shown >= total {
+            finish();
+            return;
+        }
+        if let Some(card) = window()
+            .and_then(|w| w.document())
+            .and_then(|d| d.get_element_by_id(&format!("{OVERLAY_ID}-card")))
+        {
+            card.set_inner_html(&card_html(cards[shown], shown, total));
+        }
+    }) as Box<dyn FnMut()>);
+    let id = win.set_interval_with_callback_and_timeout_and_arguments_0(
+        tick.as_ref().unchecked_ref(),
+        card_ms(seconds, total) as i32,
+    )?;
+    TIMER.set(Some(id));
+    TICK.with(|t| *t.borrow_mut() = Some(tick));
+    Ok(())
+}
@@ -0,0 +1,80 @@
+// Fullscreen toggle for focused practice. The whole page goes fullscreen (the
+// board canvas and DOM overlays are fixed-position children of <body>, not of
+// #hanzi-cat-root); on entering, the canvas grows to fit the viewport and on
+// leaving it returns to its previous size.
+use std::cell::Cell;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::BOARD_STATE;
+
+/// Share of the smaller viewport side the canvas takes up in fullscreen.
+const FULLSCREEN_FILL: f64 = 0.85;
+
+thread_local! {
+    static LISTENING: Cell<bool> = const { Cell::new(false) };
+    // Canvas size before fullscreen, restored on exit.
+    static WINDOWED_SIZE: Cell<Option<(u32, u32)>> = const { Cell::new(None) };
+}
+
+/// Enter fullscreen, or leave it when already fullscreen. Browsers that deny
+/// the request (no user gesture, iframe policy) are ignored silently.
+#[wasm_bindgen]
+pub fn toggle_fullscreen() {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    if !LISTENING.with(|l| l.replace(true)) {
+        let closure = Closure::wrap(Box::new(on_fullscreen_change) as Box<dyn FnMut()>);
+        doc.add_event_listener_with_callback("fullscreenchange", closure.as_ref().unchecked_ref())
+            .ok();
+        closure.forget();
+    }
+    if doc.fullscreen_element().is_some() {
+        doc.exit_fullscreen();
+    } else if let Some(root) = doc.document_element() {
+        root.request_fullscreen().ok();
+    }
+}
+
+fn on_fullscreen_change() {
+    let Some(win) = window() else {
+        return;
+    };
+    let full = win
+        .document()
+        .and_then(|d| d.fullscreen_element())
+        .is_some();
+    BOARD_STATE.with(|cell| {
+        let cell = cell.borrow();
+        let Some(state) = cell.as_ref() else {
+            return;
+        };
+        let canvas = &state.canvas;
+        let size = if full {
+            WINDOWED_SIZE.with(|s| s.set(Some((canvas.width(), canvas.height()))));
+            let w = win
+                .inner_width()
+                .ok()
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            let h = win
+                .inner_height()
+                .ok()
+                .and_then(|v| v.as_f64())
+                .unwrap_or(0.0);
+            let side = (w.min(h) * FULLSCREEN_FILL).round() as u32;
+            (side > 0).then_some((side, side))
+        } else {
+            WINDOWED_SIZE.with(|s| s.take())
+        };
+        if let Some((w, h)) = size {
+            canvas.set_width(w);
+            canvas.set_height(h);
+            // Resizing resets the 2D context state.
+            state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+            state.ctx.set_text_align("center");
+        }
+    });
+}
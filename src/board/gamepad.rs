@@ -0,0 +1,103 @@
+// Gamepad support for board mode, polled once per animation frame. Uses the
+// first connected pad with the standard button layout:
+//   D-pad   - select the neighbouring tile in that direction (highlighted)
+//   A (0)   - hop onto the selected tile, like typing its pinyin
+//   Start   - pause / resume, like Escape: it only resumes from the pause
+//             menu, never a pause the embedder made (`pause_board()`)
+// Only presses count (a held button fires once), so the cat never runs off
+// while a direction is held.
+use std::cell::Cell;
+use wasm_bindgen::JsCast;
+use web_sys::window;
+
+use super::{BoardState, consume_and_hop, pause, pause_menu, resume, step_target};
+
+const BTN_A: u32 = 0;
+const BTN_START: u32 = 9;
+/// D-pad buttons and their directions.
+const DPAD: [(u32, (i8, i8)); 4] = [(12, (0, -1)), (13, (0, 1)), (14, (-1, 0)), (15, (1, 0))];
+
+thread_local! {
+    // Buttons held at the previous poll (bit i = button i).
+    static HELD: Cell<u32> = const { Cell::new(0) };
+    // Direction picked with the D-pad, relative to the cat.
+    static DIR: Cell<Option<(i8, i8)>> = const { Cell::new(None) };
+}
+
+/// Buttons that went down since the previous poll.
+pub(super) fn pressed_since(prev: u32, now: u32) -> u32 {
+    now & !prev
+}
+
+/// Bitmask of the buttons held on the first connected gamepad.
+fn held_buttons() -> u32 {
+    let Some(pads) = window().and_then(|w| w.navigator().get_gamepads().ok()) else {
+        return 0;
+    };
+    let Some(pad) = pads
+        .iter()
+        .filter_map(|p| p.dyn_into::<web_sys::Gamepad>().ok())
+        .find(|p| p.connected())
+    else {
+        return 0;
+    };
+    pad.buttons()
+        .iter()
+        .take(32)
+        .enumerate()
+        .filter(|(_, b)| {
+            b.dyn_ref::<web_sys::GamepadButton>()
+                .is_some_and(|b| b.pressed())
+        })
+        .fold(0, |mask, (i, _)| mask | (1 << i))
+}
+
+/// Read the gamepad and act on new presses.
+pub(super) fn poll(state: &mut BoardState, now: f64) {
+    let held = held_buttons();
+    let pressed = pressed_since(HELD.with(|h| h.replace(held)), held);
+    if state.won || state.game_over || state.countdown_until_ms.is_some() {
+        return;
+    }
+    if pressed & (1 << BTN_START) != 0 {
+        if state.paused {
+            if pause_menu::is_open() {
+                resume(state, now);
+            }
+        } else {
+            pause(state, now);
+            pause_menu::show();
+        }
+        return;
+    }
+    // Reverse mode is played with its hanzi buttons only.
+    if state.paused || state.reverse {
+        return;
+    }
+    for (btn, dir) in DPAD {
+        if pressed & (1 << btn) != 0 {
+            DIR.with(|d| d.set(Some(dir)));
+        }
+    }
+    let Some((dx, dy)) = DIR.with(|d| d.get()) else {
+        return;
+    };
+    let target = step_target(
+        state.level,
+        state.cat_x,
+        state.cat_y,
+        dx,
+        dy,
+        &state.collected_keys,
+        state.beat.last_beat_idx,
+    );
+    state.hover_tile = target;
+    if pressed & (1 << BTN_A) != 0
+        && let Some((tx, ty)) = target
+    {
+        let idx = ty as usize * state.level.width as usize + tx as usize;
+        if state.grid[idx].is_some() {
+            consume_and_hop(state, tx, ty, idx, now);
+        }
+    }
+}
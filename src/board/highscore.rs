@@ -0,0 +1,154 @@
+// Local high score with the player's name, kept in localStorage under
+// "hanzi-cat-highscore" as `{"name": ..., "score": ...}` JSON and shown in the
+// "Best:" overlay. When a run ends above the stored score the name comes from
+// `set_player_name` if the page provided one, otherwise from a small prompt.
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{HtmlInputElement, Storage, window};
+
+use super::format_score;
+
+const STORAGE_KEY: &str = "hanzi-cat-highscore";
+const PROMPT_ID: &str = "hc-name-prompt";
+pub(super) const DEFAULT_NAME: &str = "Anonymous";
+
+thread_local! {
+    static PLAYER_NAME: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Pre-fill the name stored with new high scores (blank clears it, so the
+/// prompt is shown again).
+#[wasm_bindgen]
+pub fn set_player_name(name: String) {
+    let name = name.trim().to_string();
+    PLAYER_NAME.with(|p| *p.borrow_mut() = (!name.is_empty()).then_some(name));
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok().flatten()
+}
+
+/// Stored `(name, score)`, if any.
+fn load() -> Option<(String, i64)> {
+    let raw = storage()?.get_item(STORAGE_KEY).ok().flatten()?;
+    let v = js_sys::JSON::parse(&raw).ok()?;
+    let score = js_sys::Reflect::get(&v, &"score".into()).ok()?.as_f64()? as i64;
+    let name = js_sys::Reflect::get(&v, &"name".into())
+        .ok()
+        .and_then(|n| n.as_string())
+        .unwrap_or_default();
+    Some((display_name(&name), score))
+}
+
+fn save(name: &str, score: i64) {
+    let obj = js_sys::Object::new();
+    js_sys::Reflect::set(&obj, &"name".into(), &name.into()).ok();
+    js_sys::Reflect::set(&obj, &"score".into(), &(score as f64).into()).ok();
+    if let (Some(s), Ok(json)) = (storage(), js_sys::JSON::stringify(&obj)) {
+        s.set_item(STORAGE_KEY, &String::from(json)).ok();
+    }
+}
+
+/// Trimmed name, or `DEFAULT_NAME` when blank.
+pub(super) fn display_name(raw: &str) -> String {
+    let name = raw.trim();
+    if name.is_empty() {
+        DEFAULT_NAME.to_string()
+    } else {
+        name.to_string()
+    }
+}
+
+/// "Best:" overlay text.
+pub(super) fn best_label(best: Option<(&str, i64)>, locale: &str) -> String {
+    match best {
+        Some((name, score)) => format!("Best: {} ({name})", format_score(score, locale)),
+        None => "Best: -".to_string(),
+    }
+}
+
+/// Refresh the "Best:" overlay from storage.
+pub(super) fn refresh(locale: &'static str) {
+    if let Some(el) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id("hc-best"))
+    {
+        let best = load();
+        el.set_text_content(Some(&best_label(
+            best.as_ref().map(|(n, s)| (n.as_str(), *s)),
+            locale,
+        )));
+    }
+}
+
+/// A run ended with `score`: record it if it beats the stored best, asking
+/// for a name unless one was provided.
+pub(super) fn on_run_end(score: i64, locale: &'static str) {
+    if load().is_some_and(|(_, best)| best >= score) || score <= 0 {
+        return;
+    }
+    match PLAYER_NAME.with(|p| p.borrow().clone()) {
+        Some(name) => {
+            save(&name, score);
+            refresh(locale);
+        }
+        None => show_prompt(score, locale),
+    }
+}
+
+fn show_prompt(score: i64, locale: &'static str) {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    if let Some(old) = doc.get_element_by_id(PROMPT_ID) {
+        old.remove();
+    }
+    let Ok(prompt) = doc.create_element("div") else {
+        return;
+    };
+    prompt.set_id(PROMPT_ID);
+    prompt.set_attribute("style", "position:fixed; left:50%; top:12%; transform:translateX(-50%); padding:12px 16px; background:rgba(12,12,12,0.96); border:2px solid #ffd166; border-radius:12px; font-family:'Fira Code', monospace; font-size:15px; color:#ffd166; z-index:65; display:flex; gap:8px; align-items:center;").ok();
+    prompt.set_inner_html(&format!(
+        "<span>New high score {}! Name:</span>\
+         <input id='{PROMPT_ID}-input' maxlength='20' placeholder='{DEFAULT_NAME}' style='font:inherit; width:140px; padding:3px 6px; background:#222; color:#fff; border:1px solid #555; border-radius:4px;'>\
+         <button id='{PROMPT_ID}-save' style='font:inherit; padding:3px 10px; background:#222; color:#ffd166; border:1px solid #555; border-radius:6px; cursor:pointer;'>Save</button>",
+        format_score(score, locale)
+    ));
+    if let Some(body) = doc.body() {
+        body.append_child(&prompt).ok();
+    }
+    if let Some(btn) = doc.get_element_by_id(&format!("{PROMPT_ID}-save")) {
+        let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+            let Some(doc) = window().and_then(|w| w.document()) else {
+                return;
+            };
+            let raw = doc
+                .get_element_by_id(&format!("{PROMPT_ID}-input"))
+                .and_then(|el| el.dyn_into::<HtmlInputElement>().ok())
+                .map(|input| input.value())
+                .unwrap_or_default();
+            let name = display_name(&raw);
+            // Remember the name for later records this session.
+            set_player_name(name.clone());
+            save(&name, score);
+            refresh(locale);
+            if let Some(p) = doc.get_element_by_id(PROMPT_ID) {
+                p.remove();
+            }
+        }) as Box<dyn FnMut(_)>);
+        btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+            .ok();
+        closure.forget();
+    }
+}
+
+/// Remove the name prompt if it is open.
+pub(super) fn hide_prompt() {
+    if let Some(p) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(PROMPT_ID))
+    {
+        p.remove();
+    }
+}
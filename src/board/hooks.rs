@@ -0,0 +1,98 @@
+// Optional JS callbacks for embedders (analytics, tutor panels). Passing
+// `null` / `undefined` to a setter unregisters its callback. Events are only
+// built when a callback is registered, and are queued while the board state is
+// borrowed; `flush()` delivers them once it is released, so a callback may
+// call back into the API (e.g. `get_stats()`).
+//   set_on_key(cb) - every key the board handles:
+//                    {key, correct, buffer, target_pinyin}
+//   set_on_note_spawn(cb) - every tile refilled on the beat:
+//                    {hanzi, pinyin, multi}
+//   set_on_level_up(cb) - the board advanced to a new level: {level, name}
+use js_sys::{Function, Object, Reflect};
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+thread_local! {
+    static ON_KEY: RefCell<Option<Function>> = const { RefCell::new(None) };
+    static ON_NOTE_SPAWN: RefCell<Option<Function>> = const { RefCell::new(None) };
+    static ON_LEVEL_UP: RefCell<Option<Function>> = const { RefCell::new(None) };
+    static QUEUE: RefCell<Vec<(Function, Object)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Call `cb` for every key the board handles with `{key, correct, buffer,
+/// target_pinyin}`. For letters and tone digits `correct` says whether the
+/// buffer still leads to an adjacent tile's pinyin (`target_pinyin`, else
+/// `null`); for Enter whether the submission matched (`buffer` is then the
+/// submitted text); editing keys report `false`.
+#[wasm_bindgen]
+pub fn set_on_key(cb: Option<Function>) {
+    ON_KEY.with(|h| *h.borrow_mut() = cb);
+}
+
+/// Call `cb` with `{hanzi, pinyin, multi}` each time a tile is refilled on the
+/// beat (not for the layout of a new level, nor while paused). `multi` marks
+/// multi-character words.
+#[wasm_bindgen]
+pub fn set_on_note_spawn(cb: Option<Function>) {
+    ON_NOTE_SPAWN.with(|h| *h.borrow_mut() = cb);
+}
+
+/// Call `cb` with `{level, name}` (1-based level number and level name) when
+/// the score advances the board to the next level.
+#[wasm_bindgen]
+pub fn set_on_level_up(cb: Option<Function>) {
+    ON_LEVEL_UP.with(|h| *h.borrow_mut() = cb);
+}
+
+fn object(fields: &[(&str, JsValue)]) -> Object {
+    let obj = Object::new();
+    for (key, value) in fields {
+        Reflect::set(&obj, &(*key).into(), value).ok();
+    }
+    obj
+}
+
+fn queue(
+    hook: &'static std::thread::LocalKey<RefCell<Option<Function>>>,
+    event: impl FnOnce() -> Object,
+) {
+    if let Some(cb) = hook.with(|h| h.borrow().clone()) {
+        let event = event();
+        QUEUE.with(|q| q.borrow_mut().push((cb, event)));
+    }
+}
+
+pub(super) fn key(key: &str, correct: bool, buffer: &str, target: Option<&str>) {
+    queue(&ON_KEY, || {
+        object(&[
+            ("key", key.into()),
+            ("correct", correct.into()),
+            ("buffer", buffer.into()),
+            ("target_pinyin", target.map_or(JsValue::NULL, JsValue::from)),
+        ])
+    });
+}
+
+pub(super) fn note_spawn(hanzi: &str, pinyin: &str) {
+    queue(&ON_NOTE_SPAWN, || {
+        object(&[
+            ("hanzi", hanzi.into()),
+            ("pinyin", pinyin.into()),
+            ("multi", (hanzi.chars().count() > 1).into()),
+        ])
+    });
+}
+
+pub(super) fn level_up(level: usize, name: &str) {
+    queue(&ON_LEVEL_UP, || {
+        object(&[("level", (level as u32).into()), ("name", name.into())])
+    });
+}
+
+/// Deliver queued events. Call only while the board state is not borrowed.
+pub(super) fn flush() {
+    let events = QUEUE.with(|q| std::mem::take(&mut *q.borrow_mut()));
+    for (cb, event) in events {
+        cb.call1(&JsValue::NULL, &event).ok();
+    }
+}
@@ -0,0 +1,138 @@
+// Rebindable control keys. Actions and their default `KeyboardEvent.key`s:
+//   clear        "Escape"    - empty the typing buffer
+//   backspace    "Backspace" - delete the last typed character
+//   pause        "Escape"    - open / close the pause menu
+//   toggle_hints "F2"        - show / hide tone marks
+// When clear and pause share a key, it clears a non-empty buffer and pauses
+// otherwise. Letters and digits cannot be bound, so they always type pinyin
+// (or hop, with number-key movement). Bindings are kept in localStorage ("hanzi-cat.key.<action>").
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use web_sys::{Storage, window};
+
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct KeyBindings {
+    pub(super) clear: String,
+    pub(super) backspace: String,
+    pub(super) pause: String,
+    pub(super) toggle_hints: String,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            clear: "Escape".into(),
+            backspace: "Backspace".into(),
+            pause: "Escape".into(),
+            toggle_hints: "F2".into(),
+        }
+    }
+}
+
+const ACTIONS: [&str; 4] = ["clear", "backspace", "pause", "toggle_hints"];
+
+impl KeyBindings {
+    fn slot(&mut self, action: &str) -> Option<&mut String> {
+        match action {
+            "clear" => Some(&mut self.clear),
+            "backspace" => Some(&mut self.backspace),
+            "pause" => Some(&mut self.pause),
+            "toggle_hints" => Some(&mut self.toggle_hints),
+            _ => None,
+        }
+    }
+
+    /// Whether `key` should pause / resume, given the typing buffer state.
+    pub(super) fn is_pause(&self, key: &str, paused: bool, typing_empty: bool) -> bool {
+        key == self.pause && (paused || typing_empty || self.pause != self.clear)
+    }
+
+    /// `key` as `handle_board_key` understands it: bound editing keys become
+    /// "Escape" / "Backspace", while unbound ones (e.g. Escape after clear
+    /// moved elsewhere) are dropped. Everything else passes through.
+    pub(super) fn canonical<'a>(&self, key: &'a str) -> Option<&'a str> {
+        if key == self.clear {
+            Some("Escape")
+        } else if key == self.backspace {
+            Some("Backspace")
+        } else if key == "Escape" || key == "Backspace" {
+            None
+        } else {
+            Some(key)
+        }
+    }
+}
+
+/// Whether `key` may be bound: single letters and digits are game input.
+pub(super) fn bindable(key: &str) -> bool {
+    let mut chars = key.chars();
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => !c.is_ascii_alphanumeric(),
+        _ => true,
+    }
+}
+
+thread_local! {
+    static BINDINGS: RefCell<KeyBindings> = RefCell::new(KeyBindings::default());
+    static LOADED: Cell<bool> = const { Cell::new(false) };
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok().flatten()
+}
+
+fn storage_key(action: &str) -> String {
+    format!("hanzi-cat.key.{action}")
+}
+
+/// Current bindings, loading stored ones on first use.
+pub(super) fn bindings() -> KeyBindings {
+    if !LOADED.with(|l| l.replace(true))
+        && let Some(s) = storage()
+    {
+        BINDINGS.with(|b| {
+            let mut b = b.borrow_mut();
+            for action in ACTIONS {
+                if let Some(key) = s.get_item(&storage_key(action)).ok().flatten()
+                    && bindable(&key)
+                    && let Some(slot) = b.slot(action)
+                {
+                    *slot = key;
+                }
+            }
+        });
+    }
+    BINDINGS.with(|b| b.borrow().clone())
+}
+
+/// Bind `action` ("clear", "backspace", "pause" or "toggle_hints") to a
+/// `KeyboardEvent.key` value such as "Delete"; an empty key restores the
+/// default. Returns `false` for unknown actions and for letters and digits,
+/// which always type.
+#[wasm_bindgen]
+pub fn set_binding(action: &str, key: &str) -> bool {
+    if !bindable(key) {
+        return false;
+    }
+    bindings();
+    let key = if key.is_empty() {
+        let mut defaults = KeyBindings::default();
+        match defaults.slot(action) {
+            Some(k) => std::mem::take(k),
+            None => return false,
+        }
+    } else {
+        key.to_string()
+    };
+    let bound = BINDINGS.with(|b| match b.borrow_mut().slot(action) {
+        Some(slot) => {
+            *slot = key.clone();
+            true
+        }
+        None => false,
+    });
+    if bound && let Some(s) = storage() {
+        s.set_item(&storage_key(action), &key).ok();
+    }
+    bound
+}
@@ -0,0 +1,88 @@
+// Opt-in leaderboard submission: the page calls `submit_score(url, name)`
+// (typically from the run summary) and the current run's stats are POSTed as
+// `{"name", "score", "accuracy", "mode", "level"}` JSON. Nothing is sent
+// automatically, and gameplay never waits on the request.
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Request, RequestInit, Response, window};
+
+use super::{BOARD_STATE, summary};
+
+/// POST the running board's score to `url` under `name`. Rejects with a
+/// readable message when no board is running, the request fails, or the
+/// server answers with a non-2xx status.
+#[wasm_bindgen]
+pub async fn submit_score(url: String, name: String) -> Result<(), JsValue> {
+    let err = |m: String| JsValue::from_str(&m);
+    let body = BOARD_STATE
+        .with(|cell| {
+            cell.borrow().as_ref().map(|s| {
+                let acc = summary::accuracy_pct(s.hits, s.misses);
+                let mode = if s.daily {
+                    "daily"
+                } else if s.zen {
+                    "zen"
+                } else {
+                    "board"
+                };
+                score_payload(&name, s.score, acc, mode, s.level_index)
+            })
+        })
+        .ok_or_else(|| err("board not started".into()))?;
+
+    let opts = RequestInit::new();
+    opts.set_method("POST");
+    opts.set_body(&JsValue::from_str(&body));
+    let req = Request::new_with_str_and_init(&url, &opts)
+        .map_err(|e| err(format!("invalid leaderboard request: {e:?}")))?;
+    req.headers().set("Content-Type", "application/json")?;
+    let win = window().ok_or_else(|| err("no window".into()))?;
+    let resp = JsFuture::from(win.fetch_with_request(&req))
+        .await
+        .map_err(|e| err(format!("leaderboard unreachable: {e:?}")))?;
+    let resp: Response = resp.dyn_into()?;
+    if !resp.ok() {
+        return Err(err(format!(
+            "leaderboard rejected score: HTTP {}",
+            resp.status()
+        )));
+    }
+    Ok(())
+}
+
+/// JSON body for a submission. `mode` is "board", "daily" or "zen"; `level` is
+/// reported 1-based like the menu.
+pub(super) fn score_payload(
+    name: &str,
+    score: i64,
+    accuracy: f64,
+    mode: &str,
+    level_index: usize,
+) -> String {
+    format!(
+        r#"{{"name":{},"score":{score},"accuracy":{accuracy},"mode":"{mode}","level":{}}}"#,
+        json_string(name),
+        level_index + 1
+    )
+}
+
+/// `s` as a quoted JSON string. Player names and runtime dataset entries are
+/// free text, so they need escaping.
+pub(super) fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -0,0 +1,90 @@
+// Level-select menu shown before board mode starts.
+// Progress is kept in localStorage as one flag per cleared level index
+// ("hanzi-cat.cleared.<i>"); level 0 is always open and level i unlocks once
+// level i-1 has been cleared.
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{Storage, window};
+
+use super::{levels, palette, start_board_mode_at};
+
+const MENU_ID: &str = "hc-level-select";
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok().flatten()
+}
+
+fn cleared_key(level_index: usize) -> String {
+    format!("hanzi-cat.cleared.{level_index}")
+}
+
+/// Remember that `level_index` was cleared, unlocking the next level.
+pub(super) fn mark_level_cleared(level_index: usize) {
+    if let Some(s) = storage() {
+        s.set_item(&cleared_key(level_index), "1").ok();
+    }
+}
+
+fn is_level_unlocked(level_index: usize) -> bool {
+    level_index == 0
+        || storage()
+            .and_then(|s| s.get_item(&cleared_key(level_index - 1)).ok().flatten())
+            .is_some()
+}
+
+/// Show the level list; clicking an unlocked level closes the menu and starts
+/// the board on it. Locked levels are greyed out and ignore clicks.
+#[wasm_bindgen]
+pub fn show_level_select() -> Result<(), JsValue> {
+    let doc = window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    if let Some(old) = doc.get_element_by_id(MENU_ID) {
+        old.remove();
+    }
+    palette::inject_css();
+    let menu = doc.create_element("div")?;
+    menu.set_id(MENU_ID);
+    menu.set_attribute("style", "position:fixed; left:50%; top:40%; transform:translate(-50%,-50%); min-width:260px; padding:18px 22px; background:rgba(12,12,12,0.94); border:2px solid #333; border-radius:14px; font-family:'Fira Code', monospace; color:#ffd166; z-index:60; display:flex; flex-direction:column; gap:8px;")?;
+    let title = doc.create_element("div")?;
+    title.set_text_content(Some("Select Level"));
+    title.set_attribute(
+        "style",
+        "font-size:20px; text-align:center; margin-bottom:6px;",
+    )?;
+    menu.append_child(&title)?;
+
+    for (i, lvl) in levels().iter().enumerate() {
+        let unlocked = is_level_unlocked(i);
+        let btn = doc.create_element("button")?;
+        let label = if unlocked {
+            format!("{}. {}", i + 1, lvl.name)
+        } else {
+            format!("{}. {} (locked)", i + 1, lvl.name)
+        };
+        btn.set_text_content(Some(&label));
+        if unlocked {
+            btn.set_attribute("style", "font:inherit; font-size:15px; padding:6px 10px; background:#222; color:#ffd166; border:1px solid #555; border-radius:6px; cursor:pointer; text-align:left;")?;
+            let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+                if let Some(m) = window()
+                    .and_then(|w| w.document())
+                    .and_then(|d| d.get_element_by_id(MENU_ID))
+                {
+                    m.remove();
+                }
+                start_board_mode_at(i).ok();
+            }) as Box<dyn FnMut(_)>);
+            btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+            closure.forget();
+        } else {
+            btn.set_attribute("disabled", "")?;
+            btn.set_attribute("style", "font:inherit; font-size:15px; padding:6px 10px; background:#1a1a1a; color:#666; border:1px solid #333; border-radius:6px; cursor:not-allowed; text-align:left;")?;
+        }
+        menu.append_child(&btn)?;
+    }
+
+    doc.body()
+        .ok_or_else(|| JsValue::from_str("no body"))?
+        .append_child(&menu)?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+// Start/stop lifecycle for board mode. The frame loop and the document/canvas
+// listeners are held here instead of being leaked (`start_board_mode_sized`
+// detaches the previous set before attaching its own), so `stop_board_mode()` can
+// cancel the loop, detach the listeners and drop the board state; a later
+// `start_board_mode*()` call then sets everything up from scratch.
+use std::any::Any;
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::JsCast;
+use wasm_bindgen::convert::FromWasmAbi;
+use wasm_bindgen::prelude::*;
+use web_sys::{EventTarget, window};
+
+use super::{
+    BOARD_STATE, FrameCallback, flashcards, highscore, pause_menu, practice, quiz, reverse, summary,
+};
+
+/// Overlays (and the theme stylesheet) created by `start_board_mode_sized`,
+/// removed again on stop.
+const OVERLAY_IDS: &[&str] = &[
+    "hc-board-canvas",
+    "hc-typing",
+    "hc-score",
+    "hc-lives",
+    "hc-coins",
+    "hc-time",
+    "hc-tier",
+    "hc-combo-meter",
+    "hc-best",
+    "hc-mute",
+    "hc-theme",
+    "hc-ready",
+];
+
+struct Listener {
+    target: EventTarget,
+    kind: &'static str,
+    func: js_sys::Function,
+    // Keeps the Rust side of `func` alive until the listener is removed.
+    _closure: Box<dyn Any>,
+}
+
+thread_local! {
+    static RUNNING: Cell<bool> = const { Cell::new(false) };
+    static FRAME: RefCell<Option<FrameCallback>> = const { RefCell::new(None) };
+    static FRAME_ID: Cell<Option<i32>> = const { Cell::new(None) };
+    static LISTENERS: RefCell<Vec<Listener>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Attach `handler` for `kind` events on `target` and keep its closure so the
+/// listener can be removed by `stop_board_mode()`.
+pub(super) fn listen<E: FromWasmAbi + 'static>(
+    target: &EventTarget,
+    kind: &'static str,
+    handler: impl FnMut(E) + 'static,
+) -> Result<(), JsValue> {
+    let closure = Closure::wrap(Box::new(handler) as Box<dyn FnMut(E)>);
+    let func: js_sys::Function = closure.as_ref().unchecked_ref::<js_sys::Function>().clone();
+    target.add_event_listener_with_callback(kind, &func)?;
+    LISTENERS.with(|l| {
+        l.borrow_mut().push(Listener {
+            target: target.clone(),
+            kind,
+            func,
+            _closure: Box::new(closure),
+        })
+    });
+    Ok(())
+}
+
+/// Detach every listener registered through `listen()`.
+pub(super) fn remove_listeners() {
+    let listeners = LISTENERS.with(|l| std::mem::take(&mut *l.borrow_mut()));
+    for l in listeners {
+        l.target
+            .remove_event_listener_with_callback(l.kind, &l.func)
+            .ok();
+    }
+}
+
+/// Whether the frame loop should keep re-scheduling itself.
+pub(super) fn is_running() -> bool {
+    RUNNING.get()
+}
+
+/// Take ownership of a freshly built frame loop and schedule its first frame.
+pub(super) fn run_loop(frame: FrameCallback) {
+    RUNNING.set(true);
+    request_frame(&frame);
+    FRAME.with(|f| *f.borrow_mut() = Some(frame));
+}
+
+/// Schedule the next call of `frame`, remembering the id for cancellation.
+pub(super) fn request_frame(frame: &FrameCallback) {
+    if let (Some(w), Some(cb)) = (window(), frame.borrow().as_ref()) {
+        FRAME_ID.set(w.request_animation_frame(cb.as_ref().unchecked_ref()).ok());
+    }
+}
+
+/// Cancel the pending frame and drop the loop closure.
+pub(super) fn stop_loop() {
+    RUNNING.set(false);
+    if let (Some(id), Some(w)) = (FRAME_ID.take(), window()) {
+        w.cancel_animation_frame(id).ok();
+    }
+    // The closure holds an Rc to its own cell; emptying the cell breaks the
+    // cycle so both are freed.
+    if let Some(frame) = FRAME.with(|f| f.borrow_mut().take()) {
+        frame.borrow_mut().take();
+    }
+}
+
+/// Shut board mode down: stop the loop, detach listeners, drop the board state
+/// and remove the canvas and overlays. Safe to call when nothing is running.
+pub(crate) fn stop_board_mode() {
+    stop_loop();
+    remove_listeners();
+    BOARD_STATE.with(|cell| cell.borrow_mut().take());
+    practice::clear();
+    quiz::clear();
+    flashcards::cancel();
+    summary::hide();
+    pause_menu::hide();
+    reverse::hide();
+    highscore::hide_prompt();
+    if let Some(doc) = window().and_then(|w| w.document()) {
+        for id in OVERLAY_IDS {
+            if let Some(el) = doc.get_element_by_id(id) {
+                el.remove();
+            }
+        }
+    }
+}
@@ -0,0 +1,294 @@
+// Board audio: a short synthesized metronome click on each whole beat, a tone
+// cue on every cleared tile whose pitch contour follows the hanzi's tone, and a
+// combo blip that climbs a major scale as the combo grows.
+// The AudioContext is created lazily on the first keypress (browsers block audio
+// until a user gesture), so sounds before that are silently skipped.
+// Every sound plays through one master GainNode set by `set_volume`; muting
+// zeroes that gain so sounds already playing stop too. Volume and mute are
+// kept in localStorage ("hanzi-cat.volume", "hanzi-cat.muted") and loaded when
+// the board starts, before the context exists.
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+use web_sys::{AudioContext, GainNode, OscillatorType, Storage, window};
+
+const VOLUME_KEY: &str = "hanzi-cat.volume";
+const MUTED_KEY: &str = "hanzi-cat.muted";
+const ICON_ID: &str = "hc-mute";
+
+struct Metronome {
+    ctx: Option<AudioContext>,
+    // Master gain every sound connects to (created with the context)
+    master: Option<GainNode>,
+    volume: f64,
+    enabled: bool,
+    // Global mute over every board sound (clicks and tone cues)
+    muted: bool,
+}
+
+thread_local! {
+    static METRONOME: RefCell<Metronome> = const {
+        RefCell::new(Metronome {
+            ctx: None,
+            master: None,
+            volume: 1.0,
+            enabled: true,
+            muted: false,
+        })
+    };
+}
+
+fn storage() -> Option<Storage> {
+    window()?.local_storage().ok().flatten()
+}
+
+/// Clamp a requested volume to `[0, 1]` (NaN counts as silent).
+pub(super) fn clamp_volume(v: f64) -> f64 {
+    if v.is_nan() { 0.0 } else { v.clamp(0.0, 1.0) }
+}
+
+/// Gain of the master node: the volume, or silence while muted.
+pub(super) fn master_level(volume: f64, muted: bool) -> f64 {
+    if muted { 0.0 } else { volume }
+}
+
+fn apply_master(m: &Metronome) {
+    if let Some(master) = m.master.as_ref() {
+        master
+            .gain()
+            .set_value(master_level(m.volume, m.muted) as f32);
+    }
+}
+
+/// Load the stored volume and mute setting.
+pub(super) fn load_prefs() {
+    let Some(s) = storage() else {
+        return;
+    };
+    let volume = s
+        .get_item(VOLUME_KEY)
+        .ok()
+        .flatten()
+        .and_then(|v| v.parse::<f64>().ok());
+    let muted = s.get_item(MUTED_KEY).ok().flatten();
+    METRONOME.with(|m| {
+        let mut m = m.borrow_mut();
+        if let Some(v) = volume {
+            m.volume = clamp_volume(v);
+        }
+        if let Some(muted) = muted {
+            m.muted = muted == "1";
+        }
+        apply_master(&m);
+    });
+}
+
+/// Set the master volume of all board sounds (clamped to `[0, 1]`) and
+/// remember it for later visits.
+#[wasm_bindgen]
+pub fn set_volume(v: f64) {
+    let v = apply_volume(v);
+    if let Some(s) = storage() {
+        s.set_item(VOLUME_KEY, &v.to_string()).ok();
+    }
+}
+
+/// Set the master volume for this session only, without saving it; returns
+/// the clamped value.
+pub(super) fn apply_volume(v: f64) -> f64 {
+    let v = clamp_volume(v);
+    METRONOME.with(|m| {
+        let mut m = m.borrow_mut();
+        m.volume = v;
+        apply_master(&m);
+    });
+    v
+}
+
+/// Mute (`false`) or unmute (`true`) the board metronome click track.
+#[wasm_bindgen]
+pub fn set_metronome(on: bool) {
+    METRONOME.with(|m| m.borrow_mut().enabled = on);
+}
+
+/// Mute (`true`) or unmute (`false`) all board sounds at once, including any
+/// still ringing out. The setting is remembered for later visits.
+#[wasm_bindgen]
+pub fn set_muted(on: bool) {
+    METRONOME.with(|m| {
+        let mut m = m.borrow_mut();
+        m.muted = on;
+        apply_master(&m);
+    });
+    if let Some(s) = storage() {
+        s.set_item(MUTED_KEY, if on { "1" } else { "0" }).ok();
+    }
+    refresh_icon();
+}
+
+pub(super) fn is_muted() -> bool {
+    METRONOME.with(|m| m.borrow().muted)
+}
+
+/// Show the current mute state on the speaker icon.
+pub(super) fn refresh_icon() {
+    if let Some(el) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(ICON_ID))
+    {
+        let (icon, title) = if is_muted() {
+            ("🔇", "Sound off (click to unmute)")
+        } else {
+            ("🔊", "Sound on (click to mute)")
+        };
+        el.set_text_content(Some(icon));
+        el.set_attribute("title", title).ok();
+    }
+}
+
+/// Create the AudioContext and master gain once a user gesture has happened
+/// (idempotent), honoring the current volume and mute setting.
+pub(super) fn unlock() {
+    METRONOME.with(|m| {
+        let mut m = m.borrow_mut();
+        if m.ctx.is_some() {
+            return;
+        }
+        let Ok(ctx) = AudioContext::new() else {
+            return;
+        };
+        let master = ctx.create_gain().ok();
+        if let Some(g) = master.as_ref() {
+            g.connect_with_audio_node(&ctx.destination()).ok();
+        }
+        m.ctx = Some(ctx);
+        m.master = master;
+        apply_master(&m);
+    });
+}
+
+/// Play one click now. Accented clicks (bar downbeats) are pitched higher.
+pub(super) fn click(accent: bool) {
+    METRONOME.with(|m| {
+        let m = m.borrow();
+        if !m.enabled || m.muted {
+            return;
+        }
+        if let (Some(ctx), Some(out)) = (m.ctx.as_ref(), m.master.as_ref()) {
+            play_click(ctx, out, accent).ok();
+        }
+    });
+}
+
+/// Play the pitch contour of Mandarin `tone` (1-5) now.
+pub(super) fn tone_cue(tone: u8) {
+    METRONOME.with(|m| {
+        let m = m.borrow();
+        if m.muted {
+            return;
+        }
+        if let (Some(ctx), Some(out)) = (m.ctx.as_ref(), m.master.as_ref()) {
+            play_tone(ctx, out, tone).ok();
+        }
+    });
+}
+
+/// Semitone offsets of the combo blip: a major scale climbed one step per
+/// match, holding at the top after `COMBO_SCALE.len()` matches.
+const COMBO_SCALE: [u8; 12] = [0, 2, 4, 5, 7, 9, 11, 12, 14, 16, 17, 19];
+
+/// Pitch (Hz) of the blip for the `combo`-th consecutive match (1-based);
+/// a broken combo starts again at the bottom.
+pub(super) fn combo_pitch(combo: u32) -> f64 {
+    let step = (combo.max(1) as usize - 1).min(COMBO_SCALE.len() - 1);
+    660.0 * 2f64.powf(COMBO_SCALE[step] as f64 / 12.0)
+}
+
+/// Play the combo blip for the current `combo` now.
+pub(super) fn combo_blip(combo: u32) {
+    METRONOME.with(|m| {
+        let m = m.borrow();
+        if m.muted {
+            return;
+        }
+        if let (Some(ctx), Some(out)) = (m.ctx.as_ref(), m.master.as_ref()) {
+            play_blip(ctx, out, combo_pitch(combo)).ok();
+        }
+    });
+}
+
+fn play_blip(ctx: &AudioContext, out: &GainNode, hz: f64) -> Result<(), JsValue> {
+    let osc = ctx.create_oscillator()?;
+    let gain = ctx.create_gain()?;
+    osc.set_type(OscillatorType::Sine);
+    osc.frequency().set_value(hz as f32);
+    let now = ctx.current_time();
+    gain.gain().set_value_at_time(0.0001, now)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.10, now + 0.005)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.0001, now + 0.09)?;
+    osc.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(out)?;
+    osc.start_with_when(now)?;
+    osc.stop_with_when(now + 0.1)?;
+    Ok(())
+}
+
+/// `(seconds from start, Hz)` points of a tone's pitch contour: 1 steady high,
+/// 2 rising, 3 dip then rise, 4 falling, 5 (neutral) a short mid blip.
+pub(super) fn tone_contour(tone: u8) -> &'static [(f64, f64)] {
+    match tone {
+        1 => &[(0.0, 523.0), (0.22, 523.0)],
+        2 => &[(0.0, 330.0), (0.22, 523.0)],
+        3 => &[(0.0, 330.0), (0.1, 247.0), (0.24, 440.0)],
+        4 => &[(0.0, 587.0), (0.18, 262.0)],
+        _ => &[(0.0, 392.0), (0.08, 392.0)],
+    }
+}
+
+fn play_tone(ctx: &AudioContext, out: &GainNode, tone: u8) -> Result<(), JsValue> {
+    let contour = tone_contour(tone);
+    let end = contour.last().map(|&(t, _)| t).unwrap_or(0.0);
+    let osc = ctx.create_oscillator()?;
+    let gain = ctx.create_gain()?;
+    osc.set_type(OscillatorType::Triangle);
+    let now = ctx.current_time();
+    let freq = osc.frequency();
+    for (i, &(t, hz)) in contour.iter().enumerate() {
+        if i == 0 {
+            freq.set_value_at_time(hz as f32, now)?;
+        } else {
+            freq.linear_ramp_to_value_at_time(hz as f32, now + t)?;
+        }
+    }
+    gain.gain().set_value_at_time(0.0001, now)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.14, now + 0.01)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.0001, now + end + 0.04)?;
+    osc.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(out)?;
+    osc.start_with_when(now)?;
+    osc.stop_with_when(now + end + 0.05)?;
+    Ok(())
+}
+
+fn play_click(ctx: &AudioContext, out: &GainNode, accent: bool) -> Result<(), JsValue> {
+    let osc = ctx.create_oscillator()?;
+    let gain = ctx.create_gain()?;
+    osc.set_type(OscillatorType::Square);
+    osc.frequency()
+        .set_value(if accent { 1760.0 } else { 1320.0 });
+    let now = ctx.current_time();
+    let peak = if accent { 0.16 } else { 0.10 };
+    gain.gain().set_value_at_time(0.0001, now)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(peak, now + 0.002)?;
+    gain.gain()
+        .exponential_ramp_to_value_at_time(0.0001, now + 0.045)?;
+    osc.connect_with_audio_node(&gain)?;
+    gain.connect_with_audio_node(out)?;
+    osc.start_with_when(now)?;
+    osc.stop_with_when(now + 0.05)?;
+    Ok(())
+}
@@ -1,8 +1,9 @@
-//! Board-based rhythmic gameplay prototype (feature = "board_mode").
-//! This module introduces a chess / grid board where Hanzi "pieces" hop tile-to-tile
-//! in time with the musical beat instead of falling vertically. It is intentionally
-//! scaffolded and non-invasive: nothing here is invoked unless the `board_mode`
-//! feature is enabled and `start_board_mode()` is called from JS.
+//! Board-based rhythmic gameplay (the only board implementation; there is no
+//! sibling `src/board.rs`). A grid of Hanzi tiles refills in time with the musical
+//! beat and the player moves the cat tile-to-tile by typing a neighbour's pinyin.
+//! The grid + cat model is canonical; the older `Piece` hopping logic is kept
+//! below (dead-code allowed) for tile-effect experiments and its unit tests.
+//! Entry points are `start_board_mode()` / `start_board_mode_at()`, called from JS.
 //!
 //! Goals (future steps, referenced by top-level TODO IDs in main plan):
 //! - c4: Beat clock & scheduled piece spawning / hopping logic
@@ -22,9 +23,10 @@ use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, window};
 
 /// BeatClock tracks timing relative to BPM for scheduling hops.
 struct BeatClock {
-    bpm: f64,           // beats per minute
-    start_ms: f64,      // performance.now() when started
-    last_beat_idx: i64, // index of last processed whole beat
+    bpm: f64,              // beats per minute
+    start_ms: f64,         // performance.now() when started
+    last_beat_idx: i64,    // index of last processed whole beat
+    last_subbeat_idx: i64, // index of last processed subdivision tick
 }
 
 impl BeatClock {
@@ -33,6 +35,7 @@ impl BeatClock {
             bpm,
             start_ms: now,
             last_beat_idx: -1,
+            last_subbeat_idx: -1,
         }
     }
     fn beat_duration_ms(&self) -> f64 {
@@ -41,8 +44,124 @@ impl BeatClock {
     fn current_beat(&self, now: f64) -> f64 {
         (now - self.start_ms) / self.beat_duration_ms()
     }
+    /// Position in subdivision ticks (`div` ticks per beat, e.g. 2 = eighths).
+    fn current_subbeat(&self, now: f64, div: u32) -> f64 {
+        self.current_beat(now) * div.max(1) as f64
+    }
+    /// Move the clock origin forward (e.g. by a pause duration) so the beat phase
+    /// continues where it left off.
+    fn shift(&mut self, delta_ms: f64) {
+        self.start_ms += delta_ms;
+    }
+    /// Change tempo at `now` without jumping: the current beat position is kept
+    /// and only later beats arrive faster / slower.
+    fn set_bpm(&mut self, bpm: f64, now: f64) {
+        let beat = self.current_beat(now);
+        self.bpm = bpm;
+        self.start_ms = now - beat * self.beat_duration_ms();
+    }
+    /// Signed distance (in beats, -0.5..0.5) from `now` to the nearest whole beat.
+    fn beat_offset(&self, now: f64) -> f64 {
+        let b = self.current_beat(now);
+        b - b.round()
+    }
 }
 
+/// Base duration of a player-initiated cat hop (ms), before tempo modifiers.
+const CAT_HOP_MS: f64 = 220.0;
+/// Maximum automatic follow-up hops (conveyors, ice) chained after one player move.
+const MAX_CAT_AUTO_HOPS: u8 = 8;
+/// Total duration of the teleport shrink/expand animation (ms).
+const TELEPORT_MS: f64 = 360.0;
+/// Enter presses within this fraction of a beat from a whole beat count as on-beat.
+const ON_BEAT_WINDOW: f64 = 0.12;
+/// Widened on-beat window (each side) used by assist mode.
+const ASSIST_BEAT_WINDOW: f64 = 0.25;
+/// Score multiplier applied to matches submitted on the beat.
+const ON_BEAT_BONUS: f64 = 1.5;
+/// Height of the beat strip along the bottom of the board (px).
+const JUDGE_STRIP_H: f64 = 8.0;
+/// Lifetime of the "ON BEAT!" flash (ms).
+const ON_BEAT_FLASH_MS: f64 = 500.0;
+/// A combo breaks if no match lands within this many beats of the previous one.
+const COMBO_TIMEOUT_BEATS: i64 = 4;
+/// Combo above which each match grants a temporary speed boost.
+const COMBO_BOOST_THRESHOLD: u32 = 20;
+/// Peak hop-speed and score factor of the combo boost (decays to 1 over `COMBO_BOOST_MS`).
+const COMBO_BOOST_FACTOR: f64 = 1.3;
+/// Duration of the combo speed boost after the latest qualifying match (ms).
+const COMBO_BOOST_MS: f64 = 4000.0;
+/// Lives at the start of a run, and the most that can be regained.
+const MAX_LIVES: i32 = 3;
+/// Default score interval between regained lives (`set_life_award_interval`).
+const LIFE_AWARD_INTERVAL: i64 = 5000;
+/// Lifetime of the glow on a regained heart (ms).
+const LIFE_AWARD_FLASH_MS: f64 = 800.0;
+/// Recent outcomes (hit / miss) the adaptive tempo looks back over.
+const ADAPTIVE_WINDOW: usize = 12;
+/// Accuracy the adaptive tempo steers towards: above it speeds up, below slows down.
+const ADAPTIVE_TARGET_ACCURACY: f64 = 0.8;
+/// Largest change to `difficulty_progress` per outcome, kept small so the
+/// tempo drifts rather than oscillates.
+const ADAPTIVE_STEP: f64 = 0.03;
+/// Tempo factor at `difficulty_progress` 0 and 1 (0.5 is the level's own bpm).
+const ADAPTIVE_TEMPO_RANGE: (f64, f64) = (0.85, 1.15);
+/// Horizontal sway of wobbling tile glyphs (px) and its angular speed (rad/ms).
+const WOBBLE_AMPLITUDE: f64 = 4.0;
+const WOBBLE_FREQ: f64 = 0.004;
+/// Combo at which the combo meter is full.
+const COMBO_METER_CAP: u32 = 30;
+/// Vibration lengths (ms) for a hit and a miss when haptics are on.
+const HAPTIC_HIT_MS: u32 = 20;
+const HAPTIC_MISS_MS: u32 = 80;
+/// Lifetime of the red flash on the cat's tile after a missed submission (ms).
+const MISS_FLASH_MS: f64 = 250.0;
+/// Duration of the sweep drawn across the board by the "clear" powerup (ms).
+const CLEAR_SWEEP_MS: f64 = 450.0;
+/// How long a level or tier banner stays up on the canvas (ms).
+const BANNER_MS: f64 = 1500.0;
+/// Length of the "3, 2, 1, Go!" countdown that opens every run (ms); each of
+/// the four steps gets a quarter of it.
+const COUNTDOWN_MS: f64 = 3000.0;
+/// Named difficulty tiers and the run progress (`difficulty_progress`) each
+/// starts at.
+const TIERS: [(&str, f64); 5] = [
+    ("Warmup", 0.0),
+    ("Cruise", 0.2),
+    ("Heat", 0.4),
+    ("Storm", 0.6),
+    ("Frenzy", 0.8),
+];
+/// Lifetime of the crack drawn where a life was lost or a shield broke (ms).
+const LIFE_LOSS_FX_MS: f64 = 300.0;
+/// Bursty refills: ticks per cycle, and how many of them (at the start) refill.
+const BURST_CYCLE: u32 = 6;
+const BURST_TICKS: u32 = 2;
+/// Combo milestones (every N matches) that set off fireworks.
+const FIREWORK_EVERY: u32 = 25;
+/// Lifetime of one firework burst (ms), sparks per burst and most bursts alive.
+const FIREWORK_MS: f64 = 1200.0;
+const FIREWORK_SPARKS: usize = 20;
+const MAX_FIREWORKS: usize = 6;
+/// Correct submissions a boss tile needs before it clears.
+const BOSS_HITS: u32 = 2;
+/// Flat score bonus for clearing a boss tile.
+const BOSS_BONUS: i64 = 1000;
+/// Flat score bonus for clearing a two-tile word in one submission.
+const WORD_BONUS: i64 = 300;
+/// Points for the n-th extra tile of a chain clear (n * CHAIN_STEP_POINTS).
+const CHAIN_STEP_POINTS: i64 = 100;
+/// First level index (0-based) on which boss tiles can appear.
+const BOSS_MIN_LEVEL: usize = 2;
+/// One in this many refilled tiles becomes a boss (at most one at a time).
+const BOSS_CHANCE: usize = 40;
+/// Lifetime of the flash after a boss tile takes a non-final hit (ms).
+const BOSS_HIT_FLASH_MS: f64 = 220.0;
+/// Fade-in time of a (re)filled tile's glyph (ms).
+const TILE_FADE_IN_MS: f64 = 200.0;
+/// Fade-out time of a cleared tile's glyph before it disappears (ms).
+const TILE_FADE_OUT_MS: f64 = 120.0;
+
 // --- Board / Tiles / Obstacles / Modifiers ----------------------------------
 
 /// Kinds of obstacles that occupy or affect tiles.
@@ -73,6 +192,23 @@ pub enum ObstacleKind {
         strength: u8,
     },
     Transform, // Placeholder: triggers Hanzi transformation mapping (handled by ModifierKind::TransformMap)
+    /// Spike: danger tile. The cat loses a life when landing here; pieces are destroyed.
+    Spike,
+    /// Key: collected when the cat lands on it; unlocks every `Door` with the same id.
+    Key {
+        id: u8,
+    },
+    /// Door: acts like a `Block` until the matching `Key` has been collected.
+    Door {
+        id: u8,
+    },
+    /// MovingBlock: a block patrolling `path`, advancing one waypoint every
+    /// `period_beats` beats. Only its current waypoint is blocked; landing on the
+    /// cat costs a life.
+    MovingBlock {
+        path: &'static [(u8, u8)],
+        period_beats: u32,
+    },
 }
 
 /// Tile modifiers (non-exclusive with some obstacles) that adjust piece / hanzi logic.
@@ -97,6 +233,9 @@ pub enum ModifierKind {
 pub struct TileDesc {
     pub obstacle: Option<ObstacleKind>,
     pub modifier: Option<ModifierKind>,
+    /// Designer-pinned (hanzi, pinyin) placed when the level loads; never
+    /// randomly refilled, so levels can spell a phrase along a path.
+    pub fixed_hanzi: Option<(&'static str, &'static str)>,
 }
 
 /// Level grid descriptor (immutable). We use a flat vector row-major.
@@ -106,9 +245,12 @@ pub struct LevelDesc {
     pub width: u8,
     pub height: u8,
     pub bpm: f64,
-    pub tiles: &'static [TileDesc],        // length = width * height
+    pub subdivision: u32, // beat ticks per beat (1 = quarters, 2 = eighths, 4 = sixteenths)
+    pub wrap: bool,       // toroidal edges: stepping off one side enters the opposite one
+    pub chain_clear: bool, // a cleared tile also clears connected tiles with the same hanzi
+    pub tiles: &'static [TileDesc], // length = width * height
     pub spawn_points: &'static [(u8, u8)], // where new hanzi pieces can appear
-    pub goal_region: &'static [(u8, u8)],  // reaching here could score / advance
+    pub goal_region: &'static [(u8, u8)], // reaching here could score / advance
 }
 
 impl LevelDesc {
@@ -116,6 +258,21 @@ impl LevelDesc {
         let idx = y as usize * self.width as usize + x as usize;
         &self.tiles[idx]
     }
+
+    /// Tile one step from `(x, y)` in direction `(dx, dy)`, wrapping around the
+    /// edges on `wrap` levels; `None` if the step leaves a non-wrapping board.
+    pub fn offset(&self, x: u8, y: u8, dx: i8, dy: i8) -> Option<(u8, u8)> {
+        let (w, h) = (self.width as i16, self.height as i16);
+        let nx = x as i16 + dx as i16;
+        let ny = y as i16 + dy as i16;
+        if self.wrap {
+            return Some((nx.rem_euclid(w) as u8, ny.rem_euclid(h) as u8));
+        }
+        if nx < 0 || ny < 0 || nx >= w || ny >= h {
+            return None;
+        }
+        Some((nx as u8, ny as u8))
+    }
 }
 
 /// Active piece on the board (represents a Hanzi / word). For now only one piece hops;
@@ -195,6 +352,104 @@ struct SlashEffect {
     start_ms: f64,
 }
 
+/// A tile that must be typed `BOSS_HITS` times before it clears. Tied to the
+/// hanzi it spawned with so it lapses if the cell is cleared or refilled.
+#[derive(Clone, Copy)]
+struct BossTile {
+    idx: usize,
+    hanzi: &'static str,
+    hits_done: u32,
+    hit_flash_ms: Option<f64>,
+}
+
+impl BossTile {
+    /// Count one correct submission; returns whether the boss is now cleared.
+    fn hit(&mut self, now: f64) -> bool {
+        self.hits_done += 1;
+        self.hit_flash_ms = Some(now);
+        self.hits_done >= BOSS_HITS
+    }
+}
+
+/// How refills are spread over beat ticks.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SpawnPattern {
+    /// Every tick refills (`refill_per_beat` tiles, or all).
+    Steady,
+    /// `BURST_TICKS` ticks refill generously, then the rest of the
+    /// `BURST_CYCLE` refills nothing.
+    Bursty,
+}
+
+// One firework burst over the board at a combo milestone; sparks are computed
+// from the age, so there is no per-spark state. Position is in cell units.
+struct Firework {
+    x: f64,
+    y: f64,
+    start_ms: f64,
+    hue: u32,
+}
+
+// Title card flashed across the canvas (level up, new tier).
+struct Banner {
+    title: String,
+    subtitle: String,
+    start_ms: f64,
+}
+
+// Transient crack on the tile where a life was lost (red) or a shield absorbed
+// the hit (blue)
+struct LifeLossEffect {
+    x: u8,
+    y: u8,
+    start_ms: f64,
+    shielded: bool,
+}
+
+/// Per-cell glyph fade bookkeeping, kept parallel to `BoardState::grid`.
+/// Synced against the grid every frame, so any code path that fills or clears a
+/// cell gets the fade without having to report it.
+#[derive(Clone, Copy, Default)]
+struct CellFade {
+    shown: Option<&'static str>, // glyph currently drawn (lingers while fading out)
+    spawn_ms: f64,               // when `shown` was (re)filled
+    cleared_ms: Option<f64>,     // when the grid cell went empty, if fading out
+}
+
+impl CellFade {
+    fn sync(&mut self, cur: Option<&'static str>, now: f64) {
+        if cur.is_some() && cur != self.shown {
+            self.shown = cur;
+            self.spawn_ms = now;
+            self.cleared_ms = None;
+        } else if cur.is_none() && self.shown.is_some() && self.cleared_ms.is_none() {
+            self.cleared_ms = Some(now);
+        }
+        if let Some(c) = self.cleared_ms
+            && now - c >= TILE_FADE_OUT_MS
+        {
+            self.shown = None;
+            self.cleared_ms = None;
+        }
+    }
+
+    fn alpha(&self, now: f64) -> f64 {
+        match self.cleared_ms {
+            Some(c) => 1.0 - ((now - c) / TILE_FADE_OUT_MS).clamp(0.0, 1.0),
+            None => ((now - self.spawn_ms) / TILE_FADE_IN_MS).clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// Teleport animation: the cat shrinks on the portal, is relocated at the midpoint,
+/// then expands on the destination tile.
+struct TeleportEffect {
+    to_x: u8,
+    to_y: u8,
+    start_ms: f64,
+    relocated: bool,
+}
+
 /// Runtime board state.
 struct BoardState {
     canvas: HtmlCanvasElement,
@@ -202,6 +457,7 @@ struct BoardState {
     level: &'static LevelDesc,
     beat: BeatClock,
     grid: Vec<Option<(&'static str, &'static str)>>,
+    tile_spawn_ms: Vec<f64>, // when each grid tile was last filled (timing log)
     cat_x: u8,
     cat_y: u8,
     // Hop animation (cat reuse) - when true, interpolate between from and target
@@ -212,6 +468,9 @@ struct BoardState {
     cat_hop_start_ms: f64,
     cat_hop_duration_ms: f64,
     cat_hopping: bool,
+    // Automatic follow-up hops (conveyors, ice) chained since the last player move
+    cat_auto_hops: u8,
+    queued_hop: Option<(u8, u8)>, // second tile of a matched word, hopped to on landing
     level_index: usize,
     // --- Dynamic state for modifiers ---
     score: i64,
@@ -222,12 +481,88 @@ struct BoardState {
     // --- Lives / End State ---
     lives: i32,
     game_over: bool,
+    won: bool,
+    // --- Pause ---
+    paused: bool,
+    pause_started_ms: f64,
     // --- Typing ---
     typing: String, // Current pinyin buffer user is entering
     // --- Visual transient effects ---
     slash_effects: Vec<SlashEffect>,
+    life_loss_effects: Vec<LifeLossEffect>,
+    fireworks: Vec<Firework>,
+    last_firework_combo: u32, // milestone that last set off fireworks
+    cat_teleport: Option<TeleportEffect>,
+    // Start time of the most recent "ON BEAT!" flash
+    on_beat_flash_ms: Option<f64>,
+    // Level / tier banner currently on the canvas
+    banner: Option<Banner>,
+    tier: usize,                     // index into TIERS
+    countdown_until_ms: Option<f64>, // board frozen until then at run start
     // Hovered tile (for future selection / interaction); None if outside canvas
     hover_tile: Option<(u8, u8)>,
+    // Max tiles refilled per beat tick (0 = refill every empty tile)
+    refill_per_beat: usize,
+    spawn_pattern: SpawnPattern,
+    spawn_tick: u32, // refill ticks so far, for the bursty cycle
+    // Key ids collected on the current level (open matching doors)
+    collected_keys: Vec<u8>,
+    // --- Combo ---
+    board_combo: u32,          // consecutive matches without a miss or timeout
+    combo_last_beat: i64,      // beat of the most recent match (combo timer)
+    speed_boost_until_ms: f64, // high-combo speed boost decays until this time
+    // On-beat window in beat fractions before (early) / after (late) a whole beat
+    beat_window_early: f64,
+    beat_window_late: f64,
+    // --- Misses ---
+    strict_mode: bool,  // missed submissions also cost a life
+    forgiving: bool,    // characters no adjacent pinyin continues with are ignored
+    ignore_tones: bool, // tone digits are optional and not checked
+    numpad_nav: bool,   // digits 1-9 hop in numpad directions instead of typing tones
+    // Start time of the red miss flash on the cat's tile
+    miss_flash_ms: Option<f64>,
+    // Glyph fade-in / fade-out per grid cell (same length as `grid`)
+    cell_fades: Vec<CellFade>,
+    // Draw tone-contour marks (ˉ ˊ ˇ ˋ) above each tile's hanzi
+    show_tone_marks: bool,
+    // Draw tone-contour polylines below each tile's hanzi
+    show_contours: bool,
+    // Let tile glyphs sway gently from side to side
+    wobble: bool,
+    // Vibrate on hits and misses where the device supports it
+    haptics: bool,
+    // Language for number formatting ("en", "de" or "fr")
+    locale: &'static str,
+    // --- Powerups ---
+    coins: i64,           // powerup currency, earned from score
+    last_coin_score: i64, // score already converted into coins (watermark)
+    shield: u8,           // pending life losses to absorb
+    slow_until_ms: f64,   // "slow" powerup halves the tempo until this time (0 = off)
+    // Start time of the "clear" powerup sweep effect
+    clear_sweep_ms: Option<f64>,
+    // Current multi-hit boss tile, if any
+    boss: Option<BossTile>,
+    // --- Heart regeneration ---
+    life_award_interval: i64,   // points per regained life (0 = off)
+    last_life_award_score: i64, // score already counted towards lives (watermark)
+    // Start time of the glow on the most recently regained heart
+    life_award_flash_ms: Option<f64>,
+    // --- Adaptive tempo ---
+    adaptive: bool,
+    recent_outcomes: std::collections::VecDeque<bool>, // last ADAPTIVE_WINDOW hits (true) / misses
+    difficulty_progress: f64,                          // 0..=1, mapped onto ADAPTIVE_TEMPO_RANGE
+    // --- Run stats (end-of-run summary) ---
+    hits: u32,
+    misses: u32,
+    max_combo: u32,
+    run_start_ms: f64,
+    run_end_ms: Option<f64>, // freezes the play-time display once the run is over
+    summary_shown: bool,
+    daily: bool, // run started by `start_daily` (reported as mode "daily")
+    zen: bool,   // run started by `start_zen`: no lives, no game over
+    // --- Reverse mode (tiles show pinyin, hanzi buttons below the board) ---
+    reverse: bool,
+    reverse_adjacent: Vec<&'static str>, // neighbour hanzi the buttons were built for
 }
 
 // --- Static Prototype Level --------------------------------------------------
@@ -239,6 +574,33 @@ mod board_level4;
 mod board_level5;
 mod board_level6;
 mod board_level7;
+mod config;
+mod dataset;
+mod flashcards;
+mod fullscreen;
+mod gamepad;
+mod highscore;
+mod hooks;
+mod keys;
+mod leaderboard;
+mod level_select;
+mod lifecycle;
+mod metronome;
+mod palette;
+mod pause_menu;
+mod powerups;
+mod practice;
+mod quiz;
+mod ready;
+#[cfg(feature = "serde_json")]
+mod replay;
+mod reverse;
+mod review;
+#[cfg(feature = "serde_json")]
+mod save;
+mod summary;
+mod timing;
+mod vocab;
 // child level modules live under src/board/*.rs
 
 // Export per-level hanzi arrays where present for external code
@@ -248,6 +610,9 @@ pub use board_level4::LEVEL4_HANZI;
 pub use board_level5::LEVEL5_HANZI;
 pub use board_level6::LEVEL6_HANZI;
 pub use board_level7::LEVEL7_HANZI;
+pub(crate) use lifecycle::stop_board_mode;
+pub(crate) use powerups::purchase_powerup;
+pub(crate) use ready::mount_board_mode;
 
 // Runtime-built static levels array. Some level modules provide `levelN()` getters
 // (used where tiles are runtime-built), others keep `LEVELN` statics; we unify
@@ -268,115 +633,62 @@ fn levels() -> &'static [&'static LevelDesc] {
 }
 
 pub static LEVEL_SCORE_THRESHOLDS: [i64; 7] = [0, 2500, 6000, 12000, 20000, 32000, 50000];
+/// Score that clears the final level (must exceed the last entry of `LEVEL_SCORE_THRESHOLDS`).
+pub static FINAL_LEVEL_CLEAR_SCORE: i64 = 80000;
+
+/// Default board canvas size (px) used by `start_board_mode()`.
+const DEFAULT_BOARD_SIZE: f64 = 640.0;
 
 #[wasm_bindgen]
 pub fn start_board_mode() -> Result<(), JsValue> {
+    start_board_mode_sized(DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE)
+}
+
+/// Create / reuse the canvas with id hc-board-canvas (also used by the quiz and
+/// the start screen, so stopping removes it whichever one created it).
+fn board_canvas(doc: &web_sys::Document) -> Result<HtmlCanvasElement, JsValue> {
+    if let Some(el) = doc.get_element_by_id("hc-board-canvas") {
+        return el.dyn_into().map_err(JsValue::from);
+    }
+    let c: HtmlCanvasElement = doc.create_element("canvas")?.dyn_into()?;
+    c.set_id("hc-board-canvas");
+    // Center the board using CSS
+    // Shift board upward so it does not overlap the cat at the bottom center
+    c.set_attribute("style", "position:fixed; left:50%; top:38%; transform:translate(-50%,-50%); box-shadow:0 0 32px 0 rgba(0,0,0,0.18); border-radius:18px; border:2px solid #222; background:#181818; z-index:20;").ok();
+    doc.body().unwrap().append_child(&c)?;
+    Ok(c)
+}
+
+/// Like `start_board_mode()`, but with an explicit canvas size for embedding
+/// in a fixed-size widget. Cell size and the cat follow the canvas dimensions.
+#[wasm_bindgen]
+pub fn start_board_mode_sized(width: f64, height: f64) -> Result<(), JsValue> {
     let win = window().ok_or_else(|| JsValue::from_str("no window"))?;
     let doc = win
         .document()
         .ok_or_else(|| JsValue::from_str("no document"))?;
 
-    // Create / reuse canvas with id board-canvas (separate from falling mode for now)
-    let canvas: HtmlCanvasElement = if let Some(el) = doc.get_element_by_id("hc-board-canvas") {
-        el.dyn_into()?
-    } else {
-        let c: HtmlCanvasElement = doc.create_element("canvas")?.dyn_into()?;
-        c.set_id("hc-board-canvas");
-        c.set_width(640);
-        c.set_height(640);
-        // Center the board using CSS
-        // Shift board upward so it does not overlap the cat at the bottom center
-        c.set_attribute("style", "position:fixed; left:50%; top:38%; transform:translate(-50%,-50%); box-shadow:0 0 32px 0 rgba(0,0,0,0.18); border-radius:18px; border:2px solid #222; background:#181818; z-index:20;").ok();
-        doc.body().unwrap().append_child(&c)?;
-        c
-    };
+    let canvas = board_canvas(&doc)?;
+    canvas.set_width(width.max(1.0).round() as u32);
+    canvas.set_height(height.max(1.0).round() as u32);
     let ctx: CanvasRenderingContext2d = canvas.get_context("2d")?.unwrap().dyn_into()?;
     ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
     ctx.set_text_align("center");
 
     let now = win.performance().unwrap().now();
+    practice::clear();
+    quiz::clear();
+    ready::hide();
     let mut board = BoardState {
         canvas: canvas.clone(),
         ctx: ctx.clone(),
         level: levels()[0],
         beat: BeatClock::new(levels()[0].bpm, now),
-        grid: {
-            let lvl = levels()[0];
-            let mut g: Vec<Option<(&'static str, &'static str)>> =
-                Vec::with_capacity(lvl.width as usize * lvl.height as usize);
-            for yy in 0..lvl.height {
-                for xx in 0..lvl.width {
-                    let tile = lvl.tile(xx, yy);
-                    if matches!(tile.obstacle, Some(ObstacleKind::Block)) {
-                        g.push(None);
-                    } else {
-                        let (hanzi, pinyin) = match lvl.name {
-                            "Conveyor Crossing" => {
-                                let hidx = rand_index(LEVEL2_HANZI.len());
-                                LEVEL2_HANZI[hidx]
-                            }
-                            "Zigzag Express" => {
-                                let hidx = rand_index(LEVEL4_HANZI.len());
-                                LEVEL4_HANZI[hidx]
-                            }
-                            "Maze Challenge" => {
-                                let hidx = rand_index(LEVEL3_HANZI.len());
-                                LEVEL3_HANZI[hidx]
-                            }
-                            "Spiral Dream" => {
-                                let hidx = rand_index(LEVEL5_HANZI.len());
-                                LEVEL5_HANZI[hidx]
-                            }
-                            "Crystal Isle" => {
-                                let hidx = rand_index(LEVEL6_HANZI.len());
-                                LEVEL6_HANZI[hidx]
-                            }
-                            "Neon Bastion" => {
-                                let hidx = rand_index(LEVEL7_HANZI.len());
-                                LEVEL7_HANZI[hidx]
-                            }
-                            _ => ("你", "ni3"),
-                        };
-                        g.push(Some((hanzi, pinyin)));
-                    }
-                }
-            }
-            g
-        },
-        cat_x: {
-            let lvl = levels()[0];
-            let mut cx = lvl.width / 2;
-            let mut _cy = lvl.height / 2;
-            if matches!(lvl.tile(cx, _cy).obstacle, Some(ObstacleKind::Block)) {
-                'search_free: for yy in 0..lvl.height {
-                    for xx in 0..lvl.width {
-                        if !matches!(lvl.tile(xx, yy).obstacle, Some(ObstacleKind::Block)) {
-                            cx = xx;
-                            _cy = yy;
-                            break 'search_free;
-                        }
-                    }
-                }
-            }
-            cx
-        },
-        cat_y: {
-            let lvl = levels()[0];
-            let mut _cx = lvl.width / 2;
-            let mut cy = lvl.height / 2;
-            if matches!(lvl.tile(_cx, cy).obstacle, Some(ObstacleKind::Block)) {
-                'search_free2: for yy in 0..lvl.height {
-                    for xx in 0..lvl.width {
-                        if !matches!(lvl.tile(xx, yy).obstacle, Some(ObstacleKind::Block)) {
-                            _cx = xx;
-                            cy = yy;
-                            break 'search_free2;
-                        }
-                    }
-                }
-            }
-            cy
-        },
+        // Filled by `set_level` below, like every later level.
+        grid: Vec::new(),
+        tile_spawn_ms: Vec::new(),
+        cat_x: 0,
+        cat_y: 0,
         cat_from_x: 0,
         cat_from_y: 0,
         cat_target_x: 0,
@@ -384,6 +696,8 @@ pub fn start_board_mode() -> Result<(), JsValue> {
         cat_hop_start_ms: now,
         cat_hop_duration_ms: 220.0,
         cat_hopping: false,
+        cat_auto_hops: 0,
+        queued_hop: None,
         level_index: 0,
         score: 0,
         score_multiplier: 1.0,
@@ -391,259 +705,246 @@ pub fn start_board_mode() -> Result<(), JsValue> {
         hop_time_factor: 1.0,
         hop_time_end_beat: -1,
         // Lives / end state initialization
-        lives: 3,
+        lives: MAX_LIVES,
         game_over: false,
+        won: false,
+        paused: false,
+        pause_started_ms: 0.0,
         typing: String::new(),
         slash_effects: Vec::new(),
+        life_loss_effects: Vec::new(),
+        fireworks: Vec::new(),
+        last_firework_combo: 0,
+        cat_teleport: None,
+        on_beat_flash_ms: None,
+        banner: None,
+        countdown_until_ms: Some(now + COUNTDOWN_MS),
+        tier: 0,
         hover_tile: None,
+        refill_per_beat: 0,
+        spawn_pattern: SpawnPattern::Steady,
+        spawn_tick: 0,
+        collected_keys: Vec::new(),
+        board_combo: 0,
+        combo_last_beat: 0,
+        speed_boost_until_ms: 0.0,
+        beat_window_early: ON_BEAT_WINDOW,
+        beat_window_late: ON_BEAT_WINDOW,
+        strict_mode: false,
+        forgiving: false,
+        ignore_tones: false,
+        numpad_nav: false,
+        miss_flash_ms: None,
+        cell_fades: Vec::new(),
+        show_tone_marks: false,
+        show_contours: false,
+        wobble: false,
+        haptics: false,
+        locale: "en",
+        coins: 0,
+        last_coin_score: 0,
+        shield: 0,
+        slow_until_ms: 0.0,
+        clear_sweep_ms: None,
+        boss: None,
+        life_award_interval: LIFE_AWARD_INTERVAL,
+        last_life_award_score: 0,
+        life_award_flash_ms: None,
+        adaptive: false,
+        recent_outcomes: std::collections::VecDeque::new(),
+        difficulty_progress: 0.5,
+        hits: 0,
+        misses: 0,
+        max_combo: 0,
+        run_start_ms: now,
+        run_end_ms: None,
+        summary_shown: false,
+        daily: false,
+        zen: false,
+        reverse: false,
+        reverse_adjacent: Vec::new(),
     };
 
-    // Initialize cat hop fields to current cat position
-    board.cat_from_x = board.cat_x;
-    board.cat_from_y = board.cat_y;
-    board.cat_target_x = board.cat_x;
-    board.cat_target_y = board.cat_y;
-    board.cat_hop_start_ms = now;
-    board.cat_hop_duration_ms = 220.0;
-    board.cat_hopping = false;
-
-    // Ensure the player's current tile is empty and that adjacent tiles around the
-    // player are populated with distinct hanzi for the first board. The remaining
-    // cells are filled with an alternating two-character pattern.
-    {
-        let lvl = board.level;
-        let w = lvl.width as usize;
-        let h = lvl.height as usize;
-        let cx = board.cat_x as i32;
-        let cy = board.cat_y as i32;
-
-        // Collect 8-connected neighbor indices, skip blocked tiles
-        let mut neighbors: Vec<usize> = Vec::new();
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                if dx == 0 && dy == 0 {
-                    continue;
-                }
-                let nx = cx + dx;
-                let ny = cy + dy;
-                if nx < 0 || ny < 0 {
-                    continue;
-                }
-                let nxu = nx as u8;
-                let nyu = ny as u8;
-                if nxu >= lvl.width || nyu >= lvl.height {
-                    continue;
-                }
-                if matches!(lvl.tile(nxu, nyu).obstacle, Some(ObstacleKind::Block)) {
-                    continue;
-                }
-                neighbors.push(ny as usize * w + nx as usize);
-            }
-        }
-
-        // Clear player's tile
-        if cx >= 0 && cy >= 0 && (cx as u8) < lvl.width && (cy as u8) < lvl.height {
-            let cat_idx = cy as usize * w + cx as usize;
-            if cat_idx < board.grid.len() {
-                board.grid[cat_idx] = None;
-            }
-        }
-
-        if board.level_index == 0 && !neighbors.is_empty() {
-            let pool = crate::SINGLE_HANZI;
-            let pool_len = pool.len();
-            if pool_len > 0 {
-                // Choose a contiguous run from the pool (random start) and take
-                // enough unique entries for neighbors plus two for the alternating pattern.
-                let mut selected: Vec<(&'static str, &'static str)> = Vec::new();
-                let mut start = rand_index(pool_len);
-                while selected.len() < neighbors.len() + 2 && selected.len() < pool_len {
-                    let cand = pool[start % pool_len];
-                    if !selected.iter().any(|(h, _)| *h == cand.0) {
-                        selected.push(cand);
-                    }
-                    start = (start + 1) % pool_len;
-                }
-
-                // Assign unique characters to the neighbor tiles.
-                for (i, &idx) in neighbors.iter().enumerate() {
-                    if i < selected.len() {
-                        board.grid[idx] = Some(selected[i]);
-                    } else {
-                        let (h, p) = pick_random_hanzi(lvl);
-                        board.grid[idx] = Some((h, p));
-                    }
-                }
-
-                // Pick two characters for the alternating fill pattern.
-                let (pat0, pat1) = if selected.len() >= neighbors.len() + 2 {
-                    (selected[neighbors.len()], selected[neighbors.len() + 1])
-                } else {
-                    (crate::SINGLE_HANZI[0], crate::SINGLE_HANZI[1 % pool_len])
-                };
-
-                // Fill remaining empty, non-block tiles with an (x+y) parity pattern.
-                for y in 0..h {
-                    for x in 0..w {
-                        let idx = y * w + x;
-                        // Do not fill the player's tile (cat) so it remains empty.
-                        if x == board.cat_x as usize && y == board.cat_y as usize {
-                            continue;
-                        }
-                        if board.grid[idx].is_none()
-                            && !matches!(
-                                lvl.tile(x as u8, y as u8).obstacle,
-                                Some(ObstacleKind::Block)
-                            )
-                        {
-                            let parity = (x + y) % 2;
-                            board.grid[idx] = Some(if parity == 0 { pat0 } else { pat1 });
-                        }
-                    }
-                }
-            }
-        }
-    }
+    // Build the first board from the level's pool (so a dataset, practice set
+    // or spawn filter applies from the start), place the cat and empty its tile.
+    set_level(&mut board, 0, now, 0);
 
     BOARD_STATE.with(|b| b.replace(Some(board)));
 
     // Ensure typing overlay exists
-    if doc.get_element_by_id("hc-typing").is_none() {
-        if let Some(body) = doc.body() {
-            let div = doc.create_element("div")?;
-            div.set_id("hc-typing");
-            div.set_text_content(Some(""));
-            // Basic styling (absolute overlay centered above board) can be added via CSS later
-            div.set_attribute("style", "position:fixed; bottom:220px; left:50%; transform:translateX(-50%); font-family:'Fira Code', monospace; font-size:20px; padding:4px 10px; background:rgba(0,0,0,0.35); border:1px solid #333; border-radius:6px; color:#ffd166; z-index:30;").ok();
-            body.append_child(&div)?;
-        }
+    if doc.get_element_by_id("hc-typing").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-typing");
+        div.set_text_content(Some(""));
+        // Basic styling (absolute overlay centered above board) can be added via CSS later
+        div.set_attribute("style", "position:fixed; bottom:220px; left:50%; transform:translateX(-50%); font-family:'Fira Code', monospace; font-size:20px; padding:4px 10px; background:rgba(0,0,0,0.35); border:1px solid var(--hc-border); border-radius:6px; color:var(--hc-accent); z-index:30;").ok();
+        body.append_child(&div)?;
     }
+    palette::inject_css();
     // Ensure score overlay exists (top-left)
-    if doc.get_element_by_id("hc-score").is_none() {
-        if let Some(body) = doc.body() {
-            let div = doc.create_element("div")?;
-            div.set_id("hc-score");
-            div.set_text_content(Some("Score: 0"));
-            div.set_attribute("style", "position:fixed; top:10px; left:12px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:rgba(0,0,0,0.42); border:1px solid #333; border-radius:6px; color:#ffd166; z-index:45; letter-spacing:0.5px;").ok();
-            body.append_child(&div)?;
-        }
+    if doc.get_element_by_id("hc-score").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-score");
+        div.set_text_content(Some("Score: 0"));
+        div.set_attribute("style", "position:fixed; top:10px; left:12px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; color:var(--hc-accent); z-index:45; letter-spacing:0.5px;").ok();
+        body.append_child(&div)?;
     }
     // Ensure lives overlay exists (top-left, next to score)
-    if doc.get_element_by_id("hc-lives").is_none() {
-        if let Some(body) = doc.body() {
-            let div = doc.create_element("div")?;
-            div.set_id("hc-lives");
-            // Render hearts (Minecraft-style) - start with 3 filled hearts
-            div.set_inner_html("<span style='color:#ff4d4d;font-size:16px;margin-right:6px;'>♥</span><span style='color:#ff4d4d;font-size:16px;margin-right:6px;'>♥</span><span style='color:#ff4d4d;font-size:16px;'>♥</span>");
-            div.set_attribute("style", "position:fixed; top:10px; left:170px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:rgba(0,0,0,0.42); border:1px solid #333; border-radius:6px; z-index:44; letter-spacing:0.5px;").ok();
-            body.append_child(&div)?;
-        }
+    if doc.get_element_by_id("hc-lives").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-lives");
+        // Render hearts (Minecraft-style) - start with 3 filled hearts
+        div.set_inner_html("<span style='color:var(--hc-danger);font-size:16px;margin-right:6px;'>♥</span><span style='color:var(--hc-danger);font-size:16px;margin-right:6px;'>♥</span><span style='color:var(--hc-danger);font-size:16px;'>♥</span>");
+        div.set_attribute("style", "position:fixed; top:10px; left:170px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; z-index:44; letter-spacing:0.5px;").ok();
+        body.append_child(&div)?;
+    }
+    // Ensure coins overlay exists (top-left, below score)
+    if doc.get_element_by_id("hc-coins").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-coins");
+        div.set_text_content(Some("Coins: 0"));
+        div.set_attribute("style", "position:fixed; top:44px; left:12px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; color:#f2c94c; z-index:45; letter-spacing:0.5px;").ok();
+        body.append_child(&div)?;
+    }
+    // Ensure difficulty-tier overlay exists (top, right of the hearts)
+    if doc.get_element_by_id("hc-tier").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-tier");
+        div.set_text_content(Some(TIERS[0].0));
+        div.set_attribute("style", "position:fixed; top:10px; left:280px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; color:#ff9f68; z-index:45; letter-spacing:0.5px;").ok();
+        body.append_child(&div)?;
+    }
+    // Ensure play-time overlay exists (next to coins)
+    if doc.get_element_by_id("hc-time").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-time");
+        div.set_text_content(Some("Time: 0:00"));
+        div.set_attribute("style", "position:fixed; top:44px; left:170px; font-family:'Fira Code', monospace; font-size:15px; padding:4px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; color:var(--hc-text); z-index:45; letter-spacing:0.5px;").ok();
+        body.append_child(&div)?;
+    }
+    // Ensure combo meter exists (thin bar below coins); the fill's width
+    // transition animates both growth and the drain when a combo breaks.
+    if doc.get_element_by_id("hc-combo-meter").is_none()
+        && let Some(body) = doc.body()
+    {
+        let bar = doc.create_element("div")?;
+        bar.set_id("hc-combo-meter");
+        bar.set_attribute("style", "position:fixed; top:80px; left:12px; width:150px; height:6px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:4px; overflow:hidden; z-index:45;").ok();
+        let fill = doc.create_element("div")?;
+        fill.set_id("hc-combo-fill");
+        fill.set_attribute("style", "width:0%; height:100%; background:#ffffff; transition:width 0.35s ease-out, background 0.2s;").ok();
+        bar.append_child(&fill)?;
+        body.append_child(&bar)?;
+    }
+    // Ensure best-score overlay exists (below the combo meter)
+    if doc.get_element_by_id("hc-best").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-best");
+        div.set_attribute("style", "position:fixed; top:94px; left:12px; font-family:'Fira Code', monospace; font-size:13px; padding:3px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; color:var(--hc-text); z-index:45;").ok();
+        body.append_child(&div)?;
+    }
+    let locale = BOARD_STATE.with(|cell| cell.borrow().as_ref().map_or("en", |s| s.locale));
+    highscore::refresh(locale);
+    // Speaker icon (top-right) showing and toggling the global mute
+    metronome::load_prefs();
+    if doc.get_element_by_id("hc-mute").is_none()
+        && let Some(body) = doc.body()
+    {
+        let div = doc.create_element("div")?;
+        div.set_id("hc-mute");
+        div.set_attribute("style", "position:fixed; top:10px; right:12px; font-size:18px; padding:2px 8px; background:var(--hc-panel); border:1px solid var(--hc-border); border-radius:6px; cursor:pointer; user-select:none; z-index:45;").ok();
+        body.append_child(&div)?;
+    }
+    metronome::refresh_icon();
+
+    // Listeners from a previous start are dropped first so handlers never
+    // stack; all of them are re-attached below.
+    lifecycle::remove_listeners();
+    if let Some(mute) = doc.get_element_by_id("hc-mute") {
+        lifecycle::listen(&mute, "click", |_evt: web_sys::MouseEvent| {
+            metronome::set_muted(!metronome::is_muted());
+        })?;
     }
 
     // Keyboard listener for pinyin typing
     {
-        let closure = Closure::wrap(Box::new(move |evt: web_sys::KeyboardEvent| {
+        let on_key = move |evt: web_sys::KeyboardEvent| {
             BOARD_STATE.with(|state_cell| {
                 if let Some(state) = state_cell.borrow_mut().as_mut() {
                     let key = evt.key();
-                    if key == "Escape" {
-                        state.typing.clear();
-                    } else if key == "Backspace" {
-                        state.typing.pop();
-                    } else if key == "Enter" {
-                        if !state.typing.is_empty() {
-                            let typed = state.typing.clone();
-                            // Look for matching adjacent tile (up, right, down, left)
-                            let dirs: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
-                            let mut found: Option<((u8, u8), usize)> = None;
-                            for (dx, dy) in dirs.iter() {
-                                let nx_i = state.cat_x as i8 + *dx;
-                                let ny_i = state.cat_y as i8 + *dy;
-                                if nx_i < 0 || ny_i < 0 {
-                                    continue;
-                                }
-                                let nx = nx_i as u8;
-                                let ny = ny_i as u8;
-                                if nx >= state.level.width || ny >= state.level.height {
-                                    continue;
-                                }
-                                // skip blocked tiles
-                                if matches!(
-                                    state.level.tile(nx, ny).obstacle,
-                                    Some(ObstacleKind::Block)
-                                ) {
-                                    continue;
-                                }
-                                let idx = ny as usize * state.level.width as usize + nx as usize;
-                                if let Some((_, pinyin)) = state.grid[idx] {
-                                    if pinyin == typed.as_str() {
-                                        found = Some(((nx, ny), idx));
-                                        break;
-                                    }
-                                }
-                            }
-                            if let Some(((mx, my), gidx)) = found {
-                                // Queue a hop animation (reuse canonical cat) instead of
-                                // instant teleport. We'll still consume the tile and
-                                // award score immediately; the visual hop will play out.
-                                let now_ts = window()
-                                    .and_then(|w| w.performance())
-                                    .map(|p| p.now())
-                                    .unwrap_or(0.0);
-
-                                state.cat_from_x = state.cat_x;
-                                state.cat_from_y = state.cat_y;
-                                state.cat_target_x = mx;
-                                state.cat_target_y = my;
-                                state.cat_hop_start_ms = now_ts;
-                                state.cat_hop_duration_ms = 220.0 * state.hop_time_factor;
-                                state.cat_hopping = true;
-
-                                // Consume tile and award score immediately (visual slash plays)
-                                state.grid[gidx] = None;
-                                let per = (180.0 * state.score_multiplier) as i64;
-                                state.score += per;
-                                state.slash_effects.push(SlashEffect {
-                                    x: mx,
-                                    y: my,
-                                    start_ms: now_ts,
-                                });
-                            }
-                            state.typing.clear();
-                        }
-                    } else if key.len() == 1 {
-                        let c = key.chars().next().unwrap();
-                        if c.is_ascii_alphabetic() {
-                            state.typing.push(c.to_ascii_lowercase());
-                        } else if c.is_ascii_digit() && matches!(c, '1' | '2' | '3' | '4' | '5') {
-                            if state
-                                .typing
-                                .chars()
-                                .last()
-                                .map(|lc| lc.is_ascii_alphabetic())
-                                .unwrap_or(false)
-                            {
-                                state.typing.push(c);
+                    // First keypress unlocks Web Audio for the metronome.
+                    metronome::unlock();
+                    if state.won || state.game_over || state.countdown_until_ms.is_some() {
+                        return;
+                    }
+                    let now = window()
+                        .and_then(|w| w.performance())
+                        .map(|p| p.now())
+                        .unwrap_or(0.0);
+                    // The pause key (Escape by default) closes the pause menu, or
+                    // opens it when there is no typing to clear; other keys are
+                    // ignored while paused. A pause without the menu (e.g. the
+                    // embedder's `pause_board()`) is left to whoever paused.
+                    let bindings = keys::bindings();
+                    if bindings.is_pause(&key, state.paused, state.typing.is_empty()) {
+                        if state.paused {
+                            if pause_menu::is_open() {
+                                resume(state, now);
                             }
+                        } else {
+                            pause(state, now);
+                            pause_menu::show();
                         }
+                        return;
+                    }
+                    // Reverse mode is played with the hanzi buttons only.
+                    if state.paused || state.reverse {
+                        return;
+                    }
+                    if key == bindings.toggle_hints {
+                        state.show_tone_marks = !state.show_tone_marks;
+                        return;
+                    }
+                    // Replays record the canonical key, so they do not depend
+                    // on the bindings in effect.
+                    let Some(key) = bindings.canonical(&key) else {
+                        return;
+                    };
+                    if handle_board_key(state, key, now) {
+                        #[cfg(feature = "serde_json")]
+                        replay::record_key(key, now);
                     }
                     // Update DOM element
-                    if let Some(doc) = window().and_then(|w| w.document()) {
-                        if let Some(el) = doc.get_element_by_id("hc-typing") {
-                            el.set_text_content(Some(&state.typing));
-                        }
+                    if let Some(doc) = window().and_then(|w| w.document())
+                        && let Some(el) = doc.get_element_by_id("hc-typing")
+                    {
+                        el.set_inner_html(&typing_html(state));
                     }
                 }
             });
-        }) as Box<dyn FnMut(_)>);
-        doc.add_event_listener_with_callback("keydown", closure.as_ref().unchecked_ref())?;
-        closure.forget();
+            hooks::flush();
+        };
+        lifecycle::listen(&doc, "keydown", on_key)?;
     }
 
     // Mouse move listener for hover tile tracking (visual placeholder only)
     {
         let canvas_move = canvas.clone();
-        let closure = Closure::wrap(Box::new(move |evt: web_sys::MouseEvent| {
+        let on_move = move |evt: web_sys::MouseEvent| {
             // Use offset coordinates relative to the event target (canvas) to avoid
             // depending on js_sys / DomRect. offset_x/offset_y are available on
             // MouseEvent and are simpler for canvas-local coordinates.
@@ -666,1152 +967,4485 @@ pub fn start_board_mode() -> Result<(), JsValue> {
                     }
                 }
             });
-        }) as Box<dyn FnMut(_)>);
-        canvas.add_event_listener_with_callback("mousemove", closure.as_ref().unchecked_ref())?;
-        closure.forget();
+        };
+        lifecycle::listen(&canvas, "mousemove", on_move)?;
     }
-    // Mouse leave clears hover
+    // Click on an adjacent (8-connected) hanzi tile moves the cat there, same as
+    // typing its pinyin. Gives mouse / touch players a way to play.
     {
-        let canvas_leave = canvas.clone();
-        let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+        let canvas_click = canvas.clone();
+        let on_click = move |evt: web_sys::MouseEvent| {
+            let x = evt.offset_x() as f64;
+            let y = evt.offset_y() as f64;
             BOARD_STATE.with(|cell| {
                 if let Some(st) = cell.borrow_mut().as_mut() {
-                    st.hover_tile = None;
+                    if st.paused
+                        || st.won
+                        || st.game_over
+                        || st.reverse
+                        || st.countdown_until_ms.is_some()
+                    {
+                        return;
+                    }
+                    let cw = canvas_click.width() as f64 / st.level.width as f64;
+                    let ch = canvas_click.height() as f64 / st.level.height as f64;
+                    if x < 0.0 || y < 0.0 {
+                        return;
+                    }
+                    let tx = (x / cw).floor() as i32;
+                    let ty = (y / ch).floor() as i32;
+                    let mut dx = tx - st.cat_x as i32;
+                    let mut dy = ty - st.cat_y as i32;
+                    if st.level.wrap {
+                        // A tile on the opposite edge is adjacent across the seam.
+                        let (w, h) = (st.level.width as i32, st.level.height as i32);
+                        if dx.abs() == w - 1 {
+                            dx = -dx.signum();
+                        }
+                        if dy.abs() == h - 1 {
+                            dy = -dy.signum();
+                        }
+                    }
+                    if dx.abs() > 1 || dy.abs() > 1 {
+                        return;
+                    }
+                    let now_ts = window()
+                        .and_then(|w| w.performance())
+                        .map(|p| p.now())
+                        .unwrap_or(0.0);
+                    hop_toward(st, dx as i8, dy as i8, now_ts);
                 }
             });
-        }) as Box<dyn FnMut(_)>);
-        canvas_leave
-            .add_event_listener_with_callback("mouseleave", closure.as_ref().unchecked_ref())?;
-        closure.forget();
+        };
+        lifecycle::listen(&canvas, "click", on_click)?;
     }
+    // Mouse leave clears hover
+    lifecycle::listen(&canvas, "mouseleave", |_evt: web_sys::MouseEvent| {
+        BOARD_STATE.with(|cell| {
+            if let Some(st) = cell.borrow_mut().as_mut() {
+                st.hover_tile = None;
+            }
+        });
+    })?;
 
     start_board_loop();
     Ok(())
 }
 
-// RefCell::new isn't const on this toolchain; allow Clippy lint until a const initializer is feasible.
-thread_local! {
-    static BOARD_STATE: std::cell::RefCell<Option<BoardState>> = std::cell::RefCell::new(None);
+/// Start (or restart) the board on `level_index`, used by the level-select menu.
+/// The run begins with that level's score threshold so later thresholds still
+/// line up, and with full lives.
+#[wasm_bindgen]
+pub fn start_board_mode_at(level_index: usize) -> Result<(), JsValue> {
+    let running = BOARD_STATE.with(|cell| cell.borrow().is_some());
+    if !running {
+        start_board_mode()?;
+    }
+    let idx = level_index.min(levels().len() - 1);
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    reseed_rng();
+    practice::clear();
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            #[cfg(feature = "serde_json")]
+            replay::begin_run(state, idx, now);
+            set_level(state, idx, now, 0);
+            state.score = LEVEL_SCORE_THRESHOLDS.get(idx).copied().unwrap_or(0);
+            state.lives = MAX_LIVES;
+            state.game_over = false;
+            state.won = false;
+            state.paused = false;
+            state.slash_effects.clear();
+            state.life_loss_effects.clear();
+            state.fireworks.clear();
+            state.last_firework_combo = 0;
+            state.banner = None;
+            state.countdown_until_ms = Some(now + COUNTDOWN_MS);
+            state.tier = tier_index(difficulty_progress(state.score));
+            state.board_combo = 0;
+            state.speed_boost_until_ms = 0.0;
+            // Coins carry over between runs; only new score earns more.
+            state.last_coin_score = state.score;
+            state.last_life_award_score = state.score;
+            state.life_award_flash_ms = None;
+            state.recent_outcomes.clear();
+            state.difficulty_progress = 0.5;
+            apply_tempo(state, now);
+            state.shield = 0;
+            state.hits = 0;
+            state.misses = 0;
+            state.max_combo = 0;
+            state.run_start_ms = now;
+            state.run_end_ms = None;
+            state.summary_shown = false;
+            state.daily = false;
+            state.zen = false;
+            state.reverse = false;
+        }
+    });
+    review::reset();
+    timing::reset();
+    summary::hide();
+    reverse::hide();
+    pause_menu::hide();
+    Ok(())
 }
 
-type FrameCallback = std::rc::Rc<std::cell::RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+/// Refill rhythm: `"steady"` (every tick, the default) or `"bursty"` (a couple
+/// of generous refill ticks, then a pause). Returns `false` for unknown names.
+#[wasm_bindgen]
+pub fn set_spawn_pattern(pattern: &str) -> bool {
+    let pattern = match pattern {
+        "steady" => SpawnPattern::Steady,
+        "bursty" => SpawnPattern::Bursty,
+        _ => return false,
+    };
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.spawn_pattern = pattern;
+            state.spawn_tick = 0;
+        }
+    });
+    true
+}
 
-fn start_board_loop() {
-    use wasm_bindgen::JsCast;
-    let f: FrameCallback = std::rc::Rc::new(std::cell::RefCell::new(None));
-    let g = f.clone();
-    *g.borrow_mut() = Some(Closure::wrap(Box::new(move |ts: f64| {
-        BOARD_STATE.with(|state_cell| {
-            if let Some(state) = state_cell.borrow_mut().as_mut() {
-                board_tick(state, ts);
-            }
-        });
-        if let Some(w) = window() {
-            let _ =
-                w.request_animation_frame(f.borrow().as_ref().unwrap().as_ref().unchecked_ref());
+/// Limit how many empty tiles refill per beat (`0` restores full refill).
+#[wasm_bindgen]
+pub fn set_refill_rate(n: usize) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.refill_per_beat = n;
         }
-    }) as Box<dyn FnMut(f64)>));
-    if let Some(w) = window() {
-        let _ = w.request_animation_frame(g.borrow().as_ref().unwrap().as_ref().unchecked_ref());
-    }
+    });
 }
 
-// --- Tick & Rendering (prototype) -------------------------------------------
+/// Regain a life (up to the maximum) every `points` of score; `0` turns heart
+/// regeneration off. Only score gained after the call counts.
+#[wasm_bindgen]
+pub fn set_life_award_interval(points: i64) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.life_award_interval = points.max(0);
+            state.last_life_award_score = state.score;
+        }
+    });
+}
 
-fn board_tick(state: &mut BoardState, now: f64) {
-    // Beat detection (whole beats only for now)
-    let cur_beat = state.beat.current_beat(now);
-    let whole = cur_beat.floor() as i64;
-    if whole > state.beat.last_beat_idx {
-        for b in state.beat.last_beat_idx + 1..=whole {
-            on_new_beat(state, b, now);
+/// Adaptive tempo: recent accuracy gently speeds the beat up (up to x1.15) or
+/// slows it down (to x0.85) around the level's bpm. Turning it off restores
+/// the level tempo.
+#[wasm_bindgen]
+pub fn set_adaptive(on: bool) {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.adaptive = on;
+            state.recent_outcomes.clear();
+            state.difficulty_progress = 0.5;
+            apply_tempo(state, now);
         }
-        state.beat.last_beat_idx = whole;
-    }
-    // Expire temporary effects
-    expire_effects(state, whole);
-    update_pieces(state, now, whole);
-    check_level_progression(state, now, whole);
-    // Expire slash effects (>300ms)
-    state.slash_effects.retain(|e| now - e.start_ms < 300.0);
-    render_board(state, now);
-    // Keep DOM overlays (typing + score + lives) updated each frame
-    if let Some(win) = window() {
-        if let Some(doc) = win.document() {
-            if let Some(el) = doc.get_element_by_id("hc-typing") {
-                el.set_text_content(Some(&state.typing));
-            }
-            if let Some(score_el) = doc.get_element_by_id("hc-score") {
-                score_el.set_text_content(Some(&format!("Score: {}", state.score)));
-            }
-            if let Some(lives_el) = doc.get_element_by_id("hc-lives") {
-                // Build hearts HTML (3 hearts max)
-                let max_hearts: i32 = 3;
-                let mut html = String::new();
-                let filled = (state.lives.max(0).min(max_hearts)) as usize;
-                for _ in 0..filled {
-                    html.push_str(
-                        "<span style='color:#ff4d4d;font-size:16px;margin-right:6px;'>♥</span>",
-                    );
-                }
-                for _ in filled..(max_hearts as usize) {
-                    html.push_str(
-                        "<span style='color:#6b6b6b;font-size:16px;margin-right:6px;'>♡</span>",
-                    );
-                }
-                lives_el.set_inner_html(&html);
-            }
+    });
+}
+
+/// Strict mode: a submission that matches no adjacent tile also costs a life,
+/// and so does typing a character that no adjacent pinyin continues with.
+#[wasm_bindgen]
+pub fn set_strict_mode(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.strict_mode = on;
         }
-    }
+    });
 }
 
-fn on_new_beat(state: &mut BoardState, _beat_idx: i64, _now: f64) {
-    // Grid-based refill: on each beat, refill any empty (None) cells
-    // with a randomly chosen hanzi/pinyin appropriate for the current level.
-    // Skip tiles that are blocked and avoid overwriting the player's tile or
-    // the cat's destination tile while a hop animation is in progress.
-    if state.game_over {
-        return;
-    }
-    let lvl = state.level;
-    for y in 0..lvl.height {
-        for x in 0..lvl.width {
-            // skip blocked tiles
-            if matches!(lvl.tile(x, y).obstacle, Some(ObstacleKind::Block)) {
-                continue;
-            }
+/// Forgiving mode: a typed character that no adjacent pinyin continues with
+/// is ignored instead of entering the buffer, so only correct letters build
+/// up and nothing breaks the combo. Takes precedence over strict mode.
+#[wasm_bindgen]
+pub fn set_forgiving(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.forgiving = on;
+        }
+    });
+}
 
-            // Do not refill the player's current tile; it must remain empty.
-            if x == state.cat_x && y == state.cat_y {
-                continue;
-            }
+/// Ignore tones: a submission matches an adjacent tile when its letters do,
+/// whatever tone digits were typed (if any), e.g. `ni` or `ni2` for 你 (ni3).
+#[wasm_bindgen]
+pub fn set_ignore_tones(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.ignore_tones = on;
+        }
+    });
+}
 
-            // While the cat is mid-hop, avoid refilling the target tile so the
-            // arriving tile remains empty until arrival handling runs.
-            if state.cat_hopping && x == state.cat_target_x && y == state.cat_target_y {
-                continue;
-            }
+/// Number-key movement: digits 1-9 hop to the neighbour in that numpad
+/// direction (7 = up-left, 8 = up, 9 = up-right, ..., 5 = stay) when it holds
+/// a hanzi, without typing its pinyin. Digits then no longer type tones, so
+/// pair it with `set_ignore_tones(true)` if pinyin is typed as well.
+#[wasm_bindgen]
+pub fn set_numpad_navigation(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.numpad_nav = on;
+        }
+    });
+}
 
-            let idx = y as usize * lvl.width as usize + x as usize;
-            if state.grid[idx].is_none() {
-                let (h, p) = pick_random_hanzi(lvl);
-                state.grid[idx] = Some((h, p));
-            }
+/// Set the on-beat window as fractions of a beat before (`early`) and after
+/// (`late`) each whole beat; values are clamped to `0.0..=0.5`.
+#[wasm_bindgen]
+pub fn set_judge_window(early: f64, late: f64) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.beat_window_early = early.clamp(0.0, 0.5);
+            state.beat_window_late = late.clamp(0.0, 0.5);
         }
-    }
-    // Future: consider refilling only a subset per beat to tune pacing.
+    });
 }
 
-fn update_pieces(state: &mut BoardState, now: f64, _whole_beat: i64) {
-    // Advance cat hop animation (if any). We keep this function to preserve the
-    // previous call site but now use it to finish the hop and update canonical
-    // cat coordinates when the animation completes.
-    if state.cat_hopping {
-        let elapsed = now - state.cat_hop_start_ms;
-        let dur = if state.cat_hop_duration_ms <= 0.0 {
-            1.0
-        } else {
-            state.cat_hop_duration_ms
-        };
-        let t = (elapsed / dur).clamp(0.0, 1.0);
-        if t >= 1.0 {
-            // Finish hop animation and update canonical coordinates
-            state.cat_hopping = false;
-            state.cat_x = state.cat_target_x;
-            state.cat_y = state.cat_target_y;
-
-            // Consume the landing tile so it remains empty after arrival.
-            let lvl = state.level;
-            let w = lvl.width as usize;
-            let idx = state.cat_y as usize * w + state.cat_x as usize;
-            if idx < state.grid.len() {
-                state.grid[idx] = None;
-            }
+/// Assist mode for beginners: widen the on-beat window to ±`ASSIST_BEAT_WINDOW`
+/// (`false` restores the default ±`ON_BEAT_WINDOW`).
+#[wasm_bindgen]
+pub fn set_assist_mode(on: bool) {
+    let w = if on {
+        ASSIST_BEAT_WINDOW
+    } else {
+        ON_BEAT_WINDOW
+    };
+    set_judge_window(w, w);
+}
 
-            // For the first level, refresh up-to-8 neighbor tiles with unique
-            // hanzi drawn from SINGLE_HANZI and then parity-fill remaining empties.
-            if state.level_index == 0 {
-                let lvl = state.level;
-                let w = lvl.width as usize;
-                let h = lvl.height as usize;
-                let cx = state.cat_x as i32;
-                let cy = state.cat_y as i32;
-
-                // Collect neighbor indices (8-connected), skipping blocked tiles.
-                let mut neighbors: Vec<usize> = Vec::new();
-                for dy in -1..=1 {
-                    for dx in -1..=1 {
-                        if dx == 0 && dy == 0 {
-                            continue;
-                        }
-                        let nx = cx + dx;
-                        let ny = cy + dy;
-                        if nx < 0 || ny < 0 {
-                            continue;
-                        }
-                        let nxu = nx as u8;
-                        let nyu = ny as u8;
-                        if nxu >= lvl.width || nyu >= lvl.height {
-                            continue;
-                        }
-                        if matches!(lvl.tile(nxu, nyu).obstacle, Some(ObstacleKind::Block)) {
-                            continue;
-                        }
-                        neighbors.push(ny as usize * w + nx as usize);
-                    }
-                }
+/// Show (`true`) or hide tone-contour marks above the board's hanzi.
+#[wasm_bindgen]
+pub fn set_tone_marks(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.show_tone_marks = on;
+        }
+    });
+}
 
-                if !neighbors.is_empty() {
-                    let pool = crate::SINGLE_HANZI;
-                    let pool_len = pool.len();
-                    if pool_len > 0 {
-                        let mut selected: Vec<(&'static str, &'static str)> = Vec::new();
-                        let mut start = rand_index(pool_len);
-                        while selected.len() < neighbors.len() + 2 && selected.len() < pool_len {
-                            let cand = pool[start % pool_len];
-                            if !selected.iter().any(|(h, _)| *h == cand.0) {
-                                selected.push(cand);
-                            }
-                            start = (start + 1) % pool_len;
-                        }
+/// Show (`true`) or hide small tone-contour lines below the board's hanzi.
+#[wasm_bindgen]
+pub fn set_tone_contours(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.show_contours = on;
+        }
+    });
+}
 
-                        for (i, &nidx) in neighbors.iter().enumerate() {
-                            if i < selected.len() {
-                                state.grid[nidx] = Some(selected[i]);
-                            } else {
-                                let (h, p) = pick_random_hanzi(lvl);
-                                state.grid[nidx] = Some((h, p));
-                            }
-                        }
+/// Turn the decorative side-to-side sway of tile glyphs on or off.
+#[wasm_bindgen]
+pub fn set_wobble(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.wobble = on;
+        }
+    });
+}
 
-                        let (pat0, pat1) = if selected.len() >= neighbors.len() + 2 {
-                            (selected[neighbors.len()], selected[neighbors.len() + 1])
-                        } else {
-                            (crate::SINGLE_HANZI[0], crate::SINGLE_HANZI[1 % pool_len])
-                        };
-
-                        for y in 0..h {
-                            for x in 0..w {
-                                let idx = y * w + x;
-                                // Do not fill the player's tile (cat) so it remains empty.
-                                if x == state.cat_x as usize && y == state.cat_y as usize {
-                                    continue;
-                                }
-                                if state.grid[idx].is_none()
-                                    && !matches!(
-                                        lvl.tile(x as u8, y as u8).obstacle,
-                                        Some(ObstacleKind::Block)
-                                    )
-                                {
-                                    let parity = (x + y) % 2;
-                                    state.grid[idx] = Some(if parity == 0 { pat0 } else { pat1 });
-                                }
-                            }
-                        }
-                    }
-                }
-            }
+/// Turn vibration feedback on or off: a short buzz on each hit and a longer
+/// one on a miss. A no-op on devices without `navigator.vibrate`.
+#[wasm_bindgen]
+pub fn set_haptics(on: bool) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.haptics = on;
         }
+    });
+}
+
+/// Vibrate for `ms` if haptics are on and the browser supports it.
+fn buzz(state: &BoardState, ms: u32) {
+    if !state.haptics {
+        return;
+    }
+    if let Some(nav) = window().map(|w| w.navigator())
+        && js_sys::Reflect::has(&nav, &"vibrate".into()).unwrap_or(false)
+    {
+        nav.vibrate_with_duration(ms);
     }
 }
 
-fn render_board(state: &mut BoardState, now: f64) {
-    // Render background with a subtle beat pulse.
-    let beat_phase = {
-        let cb = state.beat.current_beat(now);
-        cb - cb.floor()
-    };
-    let pulse = ((beat_phase * std::f64::consts::TAU).sin() * 0.5 + 0.5) * 0.25;
-    let cell_w = state.canvas.width() as f64 / state.level.width as f64;
-    let cell_h = state.canvas.height() as f64 / state.level.height as f64;
-    let bg = (15.0 + pulse * 40.0) as i32;
-    let color = format!(
-        "rgb({},{},{})",
-        (bg + 18).clamp(0, 255),
-        (bg + 14).clamp(0, 255),
-        (bg + 12).clamp(0, 255)
-    );
-    state.ctx.set_fill_style_str(&color);
-    state.ctx.fill_rect(
-        0.0,
-        0.0,
-        state.canvas.width() as f64,
-        state.canvas.height() as f64,
-    );
+/// Language used to format scores: `"de"` groups digits with periods, `"fr"`
+/// with spaces, anything else (e.g. `"en"`) with commas. Region suffixes like
+/// `"de-AT"` are accepted.
+#[wasm_bindgen]
+pub fn set_locale(lang: &str) {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.locale = normalize_lang(lang);
+            highscore::refresh(state.locale);
+        }
+    });
+}
 
-    // Top accent band (spawn row visual)
-    state.ctx.set_fill_style_str("rgba(255,220,120,0.08)");
-    state
-        .ctx
-        .fill_rect(0.0, 0.0, state.canvas.width() as f64, cell_h);
+/// Coins available for powerups (0 before the board starts).
+#[wasm_bindgen]
+pub fn get_coins() -> i32 {
+    BOARD_STATE.with(|cell| cell.borrow().as_ref().map(|s| s.coins as i32).unwrap_or(0))
+}
 
-    // Highlight goal region tiles
-    state.ctx.set_fill_style_str("rgba(120,200,255,0.10)");
-    for &(gx, gy) in state.level.goal_region.iter() {
-        let px = gx as f64 * cell_w;
-        let py = gy as f64 * cell_h;
-        state.ctx.fill_rect(px, py, cell_w, cell_h);
-    }
+/// Whether the final board level has been cleared.
+#[wasm_bindgen]
+pub fn board_has_won() -> bool {
+    BOARD_STATE.with(|cell| cell.borrow().as_ref().map(|s| s.won).unwrap_or(false))
+}
 
-    // Grid lines
-    state.ctx.set_stroke_style_str("#222");
-    state.ctx.set_line_width(2.0);
-    for x in 0..=state.level.width {
-        let fx = x as f64 * cell_w;
-        line(&state.ctx, fx, 0.0, fx, state.canvas.height() as f64);
-    }
-    for y in 0..=state.level.height {
-        let fy = y as f64 * cell_h;
-        line(&state.ctx, 0.0, fy, state.canvas.width() as f64, fy);
-    }
+/// PNG data URL of the board canvas, for sharing e.g. a game-over screen. Only
+/// the playfield is captured (the cat and HUD are DOM), so the score is stamped
+/// into the bottom-left corner first; the next frame repaints over it. A tainted
+/// canvas surfaces the browser's `SecurityError`.
+#[wasm_bindgen]
+pub fn screenshot_data_url() -> Result<String, JsValue> {
+    BOARD_STATE.with(|cell| {
+        let cell = cell.borrow();
+        let state = cell
+            .as_ref()
+            .ok_or_else(|| JsValue::from_str("board not started"))?;
+        let text = format!("Score: {}", format_score(state.score, state.locale));
+        let y = state.canvas.height() as f64 - 14.0;
+        state.ctx.set_font("bold 18px 'Fira Code', monospace");
+        state.ctx.set_text_align("left");
+        state.ctx.set_line_width(4.0);
+        state.ctx.set_stroke_style_str("#000000");
+        state.ctx.stroke_text(&text, 12.0, y).ok();
+        state.ctx.set_fill_style_str("#ffd166");
+        state.ctx.fill_text(&text, 12.0, y).ok();
+        state.ctx.set_text_align("center");
+        state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+        state.canvas.to_data_url()
+    })
+}
 
-    // Hover highlight
-    if let Some((hx, hy)) = state.hover_tile {
-        if hx < state.level.width && hy < state.level.height {
-            let px = hx as f64 * cell_w;
-            let py = hy as f64 * cell_h;
-            state.ctx.set_stroke_style_str("rgba(255,240,150,0.55)");
-            state.ctx.set_line_width(3.0);
-            state
-                .ctx
-                .stroke_rect(px + 1.5, py + 1.5, cell_w - 3.0, cell_h - 3.0);
+/// Freeze the board: beats, hops and effects stop advancing until `resume_board()`.
+#[wasm_bindgen]
+pub fn pause_board() {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            pause(state, now);
         }
-    }
+    });
+}
 
-    // Obstacles (draw before cell content so they sit beneath Hanzi when appropriate)
-    for y in 0..state.level.height {
-        for x in 0..state.level.width {
-            let t = state.level.tile(x, y);
-            if let Some(obs) = &t.obstacle {
-                draw_obstacle(&state.ctx, obs, x, y, cell_w, cell_h);
-            }
+/// Resume a paused board, shifting all timestamps forward by the pause duration
+/// so the beat phase and any in-flight hop continue without jumping.
+#[wasm_bindgen]
+pub fn resume_board() {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            resume(state, now);
         }
+    });
+}
+
+fn pause(state: &mut BoardState, now: f64) {
+    if !state.paused {
+        state.paused = true;
+        state.pause_started_ms = now;
     }
+}
 
-    // Draw cell hanzi (centered). Use a consistent layered stroke+fill like the piece renderer.
-    state.ctx.set_shadow_color("rgba(0,0,0,0.55)");
-    state.ctx.set_shadow_blur(12.0);
-    state.ctx.set_shadow_offset_x(0.0);
-    state.ctx.set_shadow_offset_y(3.0);
+/// See `resume_board()`; also closes the pause menu.
+fn resume(state: &mut BoardState, now: f64) {
+    pause_menu::hide();
+    if !state.paused {
+        return;
+    }
+    let delta = (now - state.pause_started_ms).max(0.0);
+    shift_timestamps(state, delta);
+    state.paused = false;
+}
 
-    for y in 0..state.level.height {
-        for x in 0..state.level.width {
-            let idx = y as usize * state.level.width as usize + x as usize;
-            if let Some((hanzi, _pinyin)) = state.grid[idx] {
-                let cx = x as f64 * cell_w + cell_w / 2.0;
-                let cy = y as f64 * cell_h + cell_h / 2.0 + 8.0; // small vertical offset
-                state.ctx.set_line_width(6.0);
-                state.ctx.set_stroke_style_str("rgba(0,0,0,0.85)");
-                state.ctx.stroke_text(hanzi, cx, cy).ok();
-                // crisp fill
-                state.ctx.set_shadow_blur(0.0);
-                state.ctx.set_fill_style_str("#ffffff");
-                state.ctx.fill_text(hanzi, cx, cy).ok();
-                state.ctx.set_line_width(2.0);
-                state.ctx.set_stroke_style_str("rgba(255,210,120,0.55)");
-                state.ctx.stroke_text(hanzi, cx, cy).ok();
-                // restore shadow for next glyph
-                state.ctx.set_shadow_blur(12.0);
-            }
+/// Move every timestamp of the running board `delta` ms forward, so it
+/// continues after a freeze (pause, countdown) as if no time had passed.
+fn shift_timestamps(state: &mut BoardState, delta: f64) {
+    state.beat.shift(delta);
+    state.cat_hop_start_ms += delta;
+    for eff in &mut state.slash_effects {
+        eff.start_ms += delta;
+    }
+    for eff in &mut state.life_loss_effects {
+        eff.start_ms += delta;
+    }
+    for fw in &mut state.fireworks {
+        fw.start_ms += delta;
+    }
+    #[cfg(feature = "serde_json")]
+    replay::shift(delta);
+    if let Some(start) = state.boss.as_mut().and_then(|b| b.hit_flash_ms.as_mut()) {
+        *start += delta;
+    }
+    if let Some(start) = state.life_award_flash_ms.as_mut() {
+        *start += delta;
+    }
+    state.run_start_ms += delta;
+    for t in &mut state.tile_spawn_ms {
+        *t += delta;
+    }
+    if let Some(tp) = state.cat_teleport.as_mut() {
+        tp.start_ms += delta;
+    }
+    if let Some(start) = state.on_beat_flash_ms.as_mut() {
+        *start += delta;
+    }
+    if let Some(banner) = state.banner.as_mut() {
+        banner.start_ms += delta;
+    }
+    if let Some(start) = state.miss_flash_ms.as_mut() {
+        *start += delta;
+    }
+    state.speed_boost_until_ms += delta;
+    if state.slow_until_ms > 0.0 {
+        state.slow_until_ms += delta;
+    }
+    if let Some(start) = state.clear_sweep_ms.as_mut() {
+        *start += delta;
+    }
+    for fade in &mut state.cell_fades {
+        fade.spawn_ms += delta;
+        if let Some(c) = fade.cleared_ms.as_mut() {
+            *c += delta;
         }
     }
+    if let Some(until) = state.countdown_until_ms.as_mut() {
+        *until += delta;
+    }
+}
 
-    // Clear shadows after drawing text
-    state.ctx.set_shadow_blur(0.0);
-    state.ctx.set_shadow_offset_x(0.0);
-    state.ctx.set_shadow_offset_y(0.0);
+/// Play time of the current run (ms), excluding pauses and stopping once the
+/// run is over.
+fn elapsed_ms(state: &BoardState, now: f64) -> f64 {
+    let end = match state.run_end_ms {
+        Some(end) => end,
+        None if state.paused => state.pause_started_ms,
+        None => now,
+    };
+    (end - state.run_start_ms).max(0.0)
+}
 
-    // Compute the cat center (as before) and position the canonical DOM SVG (#hc-cat)
-    // over the canvas. We preserve the SVG's internal animation by moving the element
-    // instead of rasterizing it to the canvas.
-    let (cat_cx, cat_cy) = if state.cat_hopping {
-        let elapsed = now - state.cat_hop_start_ms;
-        let dur = if state.cat_hop_duration_ms <= 0.0 {
-            1.0
+/// Countdown step shown with `remaining_ms` of the countdown left.
+fn countdown_label(remaining_ms: f64) -> Option<&'static str> {
+    let step = COUNTDOWN_MS / 4.0;
+    match remaining_ms {
+        r if r > 3.0 * step => Some("3"),
+        r if r > 2.0 * step => Some("2"),
+        r if r > step => Some("1"),
+        r if r > 0.0 => Some("Go!"),
+        _ => None,
+    }
+}
+
+/// Draw the countdown step over the frozen board; each step shrinks and fades
+/// over its quarter.
+fn render_countdown(state: &BoardState, remaining_ms: f64) {
+    let Some(label) = countdown_label(remaining_ms) else {
+        return;
+    };
+    let step = COUNTDOWN_MS / 4.0;
+    let t = (remaining_ms % step) / step; // 1 at the step's start, 0 at its end
+    let pal = palette::current();
+    let (w, h) = (state.canvas.width() as f64, state.canvas.height() as f64);
+    state.ctx.set_fill_style_str("rgba(0,0,0,0.35)");
+    state.ctx.fill_rect(0.0, 0.0, w, h);
+    state.ctx.set_text_align("center");
+    state.ctx.set_font(&format!(
+        "bold {}px 'Fira Code', monospace",
+        64.0 + 48.0 * t
+    ));
+    state.ctx.set_line_width(6.0);
+    state
+        .ctx
+        .set_stroke_style_str(&palette::rgba(pal.outline, 0.8));
+    state.ctx.stroke_text(label, w / 2.0, h / 2.0).ok();
+    state
+        .ctx
+        .set_fill_style_str(&palette::rgba(pal.accent, 0.4 + 0.6 * t));
+    state.ctx.fill_text(label, w / 2.0, h / 2.0).ok();
+    state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+}
+
+/// Share (0..1) of the final-level clear score reached by `score`.
+fn difficulty_progress(score: i64) -> f64 {
+    (score as f64 / FINAL_LEVEL_CLEAR_SCORE as f64).clamp(0.0, 1.0)
+}
+
+/// `m:ss` for the play-time display.
+fn format_play_time(secs: f64) -> String {
+    let total = secs.max(0.0) as u64;
+    format!("{}:{:02}", total / 60, total % 60)
+}
+
+/// Seconds played in the current run (0 before the board starts). Pauses are
+/// not counted and the clock stops at game over.
+#[wasm_bindgen]
+pub fn get_elapsed_seconds() -> f64 {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        cell.borrow()
+            .as_ref()
+            .map_or(0.0, |state| elapsed_ms(state, now) / 1000.0)
+    })
+}
+
+/// All run metrics in one object: `{score, combo, max_combo, hits, misses,
+/// accuracy, cpm, lives, coins, elapsed_seconds, difficulty_progress}`, where
+/// accuracy is in percent and difficulty_progress is the share (0..1) of the
+/// final-level score reached. `null` before the board starts.
+#[wasm_bindgen]
+pub fn get_stats() -> JsValue {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        let cell = cell.borrow();
+        let Some(state) = cell.as_ref() else {
+            return JsValue::NULL;
+        };
+        let play_ms = elapsed_ms(state, now);
+        let cpm = if play_ms > 0.0 {
+            state.hits as f64 * 60_000.0 / play_ms
         } else {
-            state.cat_hop_duration_ms
+            0.0
         };
-        let t = (elapsed / dur).clamp(0.0, 1.0);
-        // ease-in-out-ish (simple quadratic ease)
-        let ease_t = 1.0 - (1.0 - t).powf(2.0);
-        let from_x = state.cat_from_x as f64;
-        let from_y = state.cat_from_y as f64;
-        let to_x = state.cat_target_x as f64;
-        let to_y = state.cat_target_y as f64;
-        let ix = from_x + (to_x - from_x) * ease_t;
-        let iy = from_y + (to_y - from_y) * ease_t;
-        // vertical arc for hop
-        let hop_h = (t * std::f64::consts::PI).sin() * 0.20 * cell_h;
-        (
-            ix * cell_w + cell_w / 2.0,
-            iy * cell_h + cell_h / 2.0 - hop_h,
-        )
-    } else {
-        (
-            state.cat_x as f64 * cell_w + cell_w / 2.0,
-            state.cat_y as f64 * cell_h + cell_h / 2.0,
-        )
-    };
+        let obj = js_sys::Object::new();
+        for (key, value) in [
+            ("score", state.score as f64),
+            ("combo", state.board_combo as f64),
+            ("max_combo", state.max_combo as f64),
+            ("hits", state.hits as f64),
+            ("misses", state.misses as f64),
+            ("accuracy", summary::accuracy_pct(state.hits, state.misses)),
+            ("cpm", cpm),
+            ("lives", state.lives as f64),
+            ("coins", state.coins as f64),
+            ("elapsed_seconds", play_ms / 1000.0),
+            ("difficulty_progress", difficulty_progress(state.score)),
+        ] {
+            js_sys::Reflect::set(&obj, &key.into(), &value.into()).ok();
+        }
+        obj.into()
+    })
+}
 
-    // Position the DOM cat SVG (#hc-cat) over the canvas at the computed tile center.
-    // The canvas is positioned using fixed left/top + transform:translate(-50%,-50%).
-    // We'll place the cat with the same anchor and apply pixel offsets relative to
-    // the canvas center to avoid requiring additional web-sys features.
-    if let Some(win) = window() {
-        if let Some(doc) = win.document() {
-            if let Some(el) = doc.get_element_by_id("hc-cat") {
-                let canvas_w = state.canvas.width() as f64;
-                let canvas_h = state.canvas.height() as f64;
-                // offset from canvas center in canvas pixels
-                let offset_x = cat_cx - (canvas_w / 2.0);
-                let offset_y = cat_cy - (canvas_h / 2.0);
-                // Use the same left/top anchor used for the canvas (50% / 38%) so the
-                // cat sits correctly above the canvas. We apply a translation that
-                // adjusts from the anchor by the computed offsets.
-                // Compute a square pixel size for the DOM cat so it fits within a
-                // single grid cell with some padding. Use the smaller of cell_w
-                // and cell_h to remain consistent across non-square boards.
-                let cat_size = (cell_w.min(cell_h) * 0.75).round() as i32;
-                let style = format!(
-                    "position:fixed; left:50%; top:38%; transform:translate(calc(-50% + {ox}px), calc(-50% + {oy}px)); pointer-events:none; z-index:40; width:{w}px; height:{h}px;",
-                    ox = offset_x,
-                    oy = offset_y,
-                    w = cat_size,
-                    h = cat_size
-                );
-                el.set_attribute("style", &style).ok();
+/// Flip the in-game hints (tone marks above each hanzi).
+fn toggle_hints() {
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.show_tone_marks = !state.show_tone_marks;
+        }
+    });
+}
+
+// Global board state shared between the animation loop and DOM event closures.
+thread_local! {
+    static BOARD_STATE: std::cell::RefCell<Option<BoardState>> = const { std::cell::RefCell::new(None) };
+    // Seed set by `seed_board_rng` (`None` = time-based randomness) and the
+    // stream it drives; the stream restarts from the seed at every run start.
+    static RNG_SEED: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+    static RNG_STATE: std::cell::Cell<Option<u64>> = const { std::cell::Cell::new(None) };
+}
+
+type FrameCallback = std::rc::Rc<std::cell::RefCell<Option<Closure<dyn FnMut(f64)>>>>;
+
+fn start_board_loop() {
+    // Restarting replaces a loop that is already running.
+    lifecycle::stop_loop();
+    let f: FrameCallback = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let g = f.clone();
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move |ts: f64| {
+        BOARD_STATE.with(|state_cell| {
+            if let Some(state) = state_cell.borrow_mut().as_mut() {
+                gamepad::poll(state, ts);
+                board_tick(state, ts);
             }
+        });
+        hooks::flush();
+        if lifecycle::is_running() {
+            lifecycle::request_frame(&f);
         }
-    }
+    }) as Box<dyn FnMut(f64)>));
+    lifecycle::run_loop(g);
+}
 
-    // Slash effects (tile-space, same visual as before)
-    for eff in &state.slash_effects {
-        let age = now - eff.start_ms;
-        let alpha = 1.0 - (age / 300.0).clamp(0.0, 1.0);
-        if alpha <= 0.0 {
-            continue;
+// --- Tick & Rendering (prototype) -------------------------------------------
+
+fn board_tick(state: &mut BoardState, now: f64) {
+    // While paused, keep rendering the frozen frame (plus overlay) but do not
+    // process beats, hops or effect expiry.
+    if state.paused {
+        render_board(state, state.pause_started_ms);
+        return;
+    }
+    // The run-start countdown holds the board on its first frame, then shifts
+    // the clock so the first beat (and refill) comes right after "Go!".
+    if let Some(until) = state.countdown_until_ms {
+        let start = until - COUNTDOWN_MS;
+        if now < until {
+            render_board(state, start);
+            render_countdown(state, until - now);
+            return;
         }
-        let px = eff.x as f64 * cell_w;
-        let py = eff.y as f64 * cell_h;
-        let inset = 6.0;
-        let left = px + inset;
-        let top = py + inset;
-        let right = px + cell_w - inset;
-        let bottom = py + cell_h - inset;
-        state.ctx.set_line_width(4.0);
-        state
-            .ctx
-            .set_stroke_style_str(&format!("rgba(255,80,80,{alpha})"));
-        for i in 0..3 {
-            let offset = i as f64 * 6.0;
-            state.ctx.begin_path();
-            state.ctx.move_to(left + offset, top);
-            state.ctx.line_to(right + offset - 18.0, bottom);
-            state.ctx.stroke();
+        state.countdown_until_ms = None;
+        shift_timestamps(state, (now - start).max(0.0));
+    }
+    #[cfg(feature = "serde_json")]
+    replay::drive(state, now);
+    // Beat detection on the level's subdivision grid (div = 1 -> whole beats)
+    let div = state.level.subdivision.max(1) as i64;
+    let whole = state.beat.current_beat(now).floor() as i64;
+    let sub_whole = state.beat.current_subbeat(now, div as u32).floor() as i64;
+    if sub_whole > state.beat.last_subbeat_idx {
+        for sb in state.beat.last_subbeat_idx + 1..=sub_whole {
+            on_new_beat(state, sb.div_euclid(div), sb.rem_euclid(div) as u32, now);
         }
+        state.beat.last_subbeat_idx = sub_whole;
     }
-
-    // GAME OVER overlay (unchanged)
-    if state.game_over {
-        state.ctx.set_fill_style_str("rgba(0,0,0,0.55)");
-        state.ctx.fill_rect(
-            0.0,
-            0.0,
-            state.canvas.width() as f64,
-            state.canvas.height() as f64,
+    state.beat.last_beat_idx = state.beat.last_beat_idx.max(whole);
+    // End the "slow" powerup: back to the level's tempo without a beat jump.
+    if state.slow_until_ms > 0.0 && now >= state.slow_until_ms {
+        state.slow_until_ms = 0.0;
+        apply_tempo(state, now);
+    }
+    // A boss lapses once its cell no longer holds the hanzi it spawned with.
+    if let Some(boss) = state.boss
+        && state.grid.get(boss.idx).copied().flatten().map(|(h, _)| h) != Some(boss.hanzi)
+    {
+        state.boss = None;
+    }
+    // Expire temporary effects
+    expire_effects(state, whole);
+    powerups::accrue_coins(state.score, &mut state.last_coin_score, &mut state.coins);
+    let awarded = life_awards(
+        state.score,
+        &mut state.last_life_award_score,
+        state.life_award_interval,
+    );
+    if awarded > 0 && state.lives < MAX_LIVES && !state.game_over {
+        state.lives = (state.lives + awarded).min(MAX_LIVES);
+        state.life_award_flash_ms = Some(now);
+    }
+    // HSK progression mode: run progress is score towards the final clear.
+    vocab::update_progression(difficulty_progress(state.score));
+    let tier = tier_index(difficulty_progress(state.score));
+    if tier != state.tier {
+        state.tier = tier;
+        let (name, _) = TIERS[tier];
+        show_banner(state, name.to_string(), format!("Tier {}", tier + 1), now);
+    }
+    update_pieces(state, now, whole);
+    check_level_progression(state, now, whole);
+    if !state.won && practice::all_mastered() {
+        let (_, total) = practice::progress();
+        show_banner(
+            state,
+            "Mastered!".into(),
+            format!("{total} characters"),
+            now,
         );
-        state.ctx.set_fill_style_str("#ffffff");
-        state.ctx.set_font("72px 'Noto Serif SC', serif");
-        state.ctx.set_text_align("center");
-        state.ctx.set_line_width(6.0);
-        state.ctx.set_stroke_style_str("#000000");
-        let cx = state.canvas.width() as f64 / 2.0;
-        let cy = state.canvas.height() as f64 / 2.0;
-        state.ctx.stroke_text("GAME OVER", cx, cy).ok();
-        state.ctx.fill_text("GAME OVER", cx, cy).ok();
-        state.ctx.set_font("20px 'Fira Code', monospace");
-        state
-            .ctx
-            .fill_text("Refresh to try again", cx, cy + 44.0)
-            .ok();
+        state.won = true;
     }
-}
-
-fn draw_obstacle(
-    ctx: &CanvasRenderingContext2d,
-    obs: &ObstacleKind,
-    x: u8,
-    y: u8,
-    cw: f64,
-    ch: f64,
-) {
-    let px = x as f64 * cw;
-    let py = y as f64 * ch;
-    match *obs {
-        ObstacleKind::Block => {
-            // Solid block with subtle inner X pattern
-            ctx.set_fill_style_str("#552222");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_stroke_style_str("rgba(255,200,200,0.15)");
-            ctx.set_line_width(3.0);
-            ctx.begin_path();
-            ctx.move_to(px + 4.0, py + 4.0);
-            ctx.line_to(px + cw - 4.0, py + ch - 4.0);
-            ctx.move_to(px + cw - 4.0, py + 4.0);
-            ctx.line_to(px + 4.0, py + ch - 4.0);
-            ctx.stroke();
+    // Expire slash effects (>300ms)
+    state.slash_effects.retain(|e| now - e.start_ms < 300.0);
+    state
+        .life_loss_effects
+        .retain(|e| now - e.start_ms < LIFE_LOSS_FX_MS);
+    state.fireworks.retain(|f| now - f.start_ms < FIREWORK_MS);
+    render_board(state, now);
+    if state.reverse {
+        reverse::sync(state);
+    }
+    if (state.game_over || state.won) && !state.summary_shown {
+        state.summary_shown = true;
+        state.run_end_ms = Some(now);
+        summary::show(state, now);
+        highscore::on_run_end(state.score, state.locale);
+    }
+    // Keep DOM overlays (typing + score + lives) updated each frame
+    if let Some(win) = window()
+        && let Some(doc) = win.document()
+    {
+        if let Some(el) = doc.get_element_by_id("hc-typing") {
+            el.set_inner_html(&typing_html(state));
         }
-        ObstacleKind::Teleport { .. } => {
-            // Portal: ring + inner glow square
-            ctx.set_stroke_style_str("#4aa3ff");
-            ctx.set_line_width(4.0);
-            ctx.begin_path();
-            let cx = px + cw / 2.0;
-            let cy = py + ch / 2.0;
-            let r = (cw.min(ch)) * 0.33;
-            ctx.arc(cx, cy, r, 0.0, std::f64::consts::TAU).ok();
-            ctx.stroke();
-            ctx.set_fill_style_str("rgba(70,140,255,0.25)");
-            let side = r * 1.1;
-            ctx.fill_rect(cx - side / 2.0, cy - side / 2.0, side, side);
+        if let Some(score_el) = doc.get_element_by_id("hc-score") {
+            let score = format_score(state.score, state.locale);
+            let text = if state.score_multiplier != 1.0 {
+                format!("Score: {} (x{})", score, state.score_multiplier)
+            } else {
+                format!("Score: {}", score)
+            };
+            score_el.set_text_content(Some(&text));
         }
-        ObstacleKind::Conveyor { dx, dy } => {
-            // Belt: darker base + directional chevrons
-            ctx.set_fill_style_str("#334433");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_fill_style_str("#88cc88");
-            // Draw 3 small chevrons along movement axis
-            let chevrons = 3;
-            for i in 0..chevrons {
-                let t = (i as f64 + 0.5) / chevrons as f64;
-                let (cx, cy) = (px + 2.0 + (cw - 4.0) * t, py + 2.0 + (ch - 4.0) * t);
-                ctx.begin_path();
-                let size = 6.0;
-                match (dx, dy) {
-                    (1, 0) => {
-                        // right
-                        ctx.move_to(cx - size, py + ch / 2.0 - size * 0.8);
-                        ctx.line_to(cx, py + ch / 2.0);
-                        ctx.line_to(cx - size, py + ch / 2.0 + size * 0.8);
-                    }
-                    (-1, 0) => {
-                        // left
-                        ctx.move_to(cx + size, py + ch / 2.0 - size * 0.8);
-                        ctx.line_to(cx, py + ch / 2.0);
-                        ctx.line_to(cx + size, py + ch / 2.0 + size * 0.8);
-                    }
-                    (0, 1) => {
-                        // down
-                        ctx.move_to(px + cw / 2.0 - size * 0.8, cy - size);
-                        ctx.line_to(px + cw / 2.0, cy);
-                        ctx.line_to(px + cw / 2.0 + size * 0.8, cy - size);
-                    }
-                    (0, -1) => {
-                        // up
-                        ctx.move_to(px + cw / 2.0 - size * 0.8, cy + size);
-                        ctx.line_to(px + cw / 2.0, cy);
-                        ctx.line_to(px + cw / 2.0 + size * 0.8, cy + size);
-                    }
-                    _ => {}
-                }
-                ctx.fill();
-            }
+        if let Some(coins_el) = doc.get_element_by_id("hc-coins") {
+            coins_el.set_text_content(Some(&format!("Coins: {}", state.coins)));
         }
-        ObstacleKind::TempoShift { .. } => {
-            // Metronome tile: base + swinging arm representation
-            ctx.set_fill_style_str("#444455");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_stroke_style_str("#b0b0ff");
-            ctx.set_line_width(3.0);
-            let base_w = cw * 0.28;
-            let base_x = px + cw / 2.0 - base_w / 2.0;
-            let base_y = py + ch * 0.68;
-            ctx.begin_path();
-            ctx.move_to(base_x, base_y);
-            ctx.line_to(base_x + base_w, base_y);
-            ctx.stroke();
-            // Arm
-            ctx.begin_path();
-            ctx.move_to(px + cw / 2.0, base_y);
-            ctx.line_to(px + cw / 2.0 + cw * 0.18, py + ch * 0.30);
-            ctx.stroke();
+        if let Some(tier_el) = doc.get_element_by_id("hc-tier") {
+            tier_el.set_text_content(Some(TIERS[state.tier].0));
         }
-        ObstacleKind::Ice => {
-            // Ice: slippery pale tile with a snowflake-like symbol
-            ctx.set_fill_style_str("#224466");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_stroke_style_str("rgba(200,230,255,0.9)");
-            ctx.set_line_width(2.0);
-            // simple snowflake lines
-            let cx = px + cw / 2.0;
-            let cy = py + ch / 2.0;
-            let r = (cw.min(ch)) * 0.18;
-            ctx.begin_path();
-            ctx.move_to(cx - r, cy);
-            ctx.line_to(cx + r, cy);
-            ctx.move_to(cx, cy - r);
-            ctx.line_to(cx, cy + r);
-            ctx.move_to(cx - r * 0.7, cy - r * 0.7);
-            ctx.line_to(cx + r * 0.7, cy + r * 0.7);
-            ctx.move_to(cx - r * 0.7, cy + r * 0.7);
-            ctx.line_to(cx + r * 0.7, cy - r * 0.7);
-            ctx.stroke();
+        if let Some(time_el) = doc.get_element_by_id("hc-time") {
+            let secs = elapsed_ms(state, now) / 1000.0;
+            time_el.set_text_content(Some(&format!("Time: {}", format_play_time(secs))));
         }
-        ObstacleKind::JumpPad { dx, dy, strength } => {
-            // JumpPad: bright pad with arrow showing direction and a strength number
-            ctx.set_fill_style_str("#554488");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_fill_style_str("#ffdd88");
-            // arrow center
-            let cx = px + cw / 2.0;
-            let cy = py + ch / 2.0;
-            let size = (cw.min(ch)) * 0.18;
-            ctx.begin_path();
-            match (dx, dy) {
-                (1, 0) => {
-                    ctx.move_to(cx - size, cy - size);
-                    ctx.line_to(cx + size, cy);
-                    ctx.line_to(cx - size, cy + size);
-                }
-                (-1, 0) => {
-                    ctx.move_to(cx + size, cy - size);
-                    ctx.line_to(cx - size, cy);
-                    ctx.line_to(cx + size, cy + size);
-                }
-                (0, 1) => {
-                    ctx.move_to(cx - size, cy - size);
-                    ctx.line_to(cx, cy + size);
-                    ctx.line_to(cx + size, cy - size);
-                }
-                (0, -1) => {
-                    ctx.move_to(cx - size, cy + size);
-                    ctx.line_to(cx, cy - size);
-                    ctx.line_to(cx + size, cy + size);
-                }
-                _ => {
-                    // no direction: draw a burst
-                    ctx.arc(cx, cy, size, 0.0, std::f64::consts::TAU).ok();
+        if let Some(fill) = doc.get_element_by_id("hc-combo-fill") {
+            let (pct, color) = combo_meter(state.board_combo);
+            fill.set_attribute("style", &format!("width:{pct}%; height:100%; background:{color}; transition:width 0.35s ease-out, background 0.2s;")).ok();
+        }
+        if let Some(lives_el) = doc.get_element_by_id("hc-lives") {
+            lives_el
+                .toggle_attribute_with_force("hidden", state.zen)
+                .ok();
+            // Build hearts HTML (MAX_LIVES hearts); a just-regained heart glows
+            let max_hearts: i32 = MAX_LIVES;
+            let mut html = String::new();
+            let filled = (state.lives.max(0).min(max_hearts)) as usize;
+            let glowing = state
+                .life_award_flash_ms
+                .is_some_and(|t| now - t < LIFE_AWARD_FLASH_MS);
+            for i in 0..filled {
+                if glowing && i + 1 == filled {
+                    html.push_str("<span style='color:#ff9d9d;font-size:16px;margin-right:6px;text-shadow:0 0 8px var(--hc-danger),0 0 14px var(--hc-accent);'>♥</span>");
+                } else {
+                    html.push_str(
+                        "<span style='color:var(--hc-danger);font-size:16px;margin-right:6px;'>♥</span>",
+                    );
                 }
             }
-            ctx.fill();
-            // strength number in corner
-            ctx.set_fill_style_str("#ffffff");
-            ctx.set_font("12px 'Fira Code', monospace");
-            ctx.fill_text(&format!("{}", strength), px + cw - 14.0, py + ch - 8.0)
-                .ok();
-        }
-        ObstacleKind::Transform => {
-            // Transform tile: gradient-like base + double arrow
-            ctx.set_fill_style_str("#333355");
-            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
-            ctx.set_stroke_style_str("#aac");
-            ctx.set_line_width(3.0);
-            ctx.begin_path();
-            let mid_y = py + ch / 2.0;
-            ctx.move_to(px + cw * 0.25, mid_y - 6.0);
-            ctx.line_to(px + cw * 0.75, mid_y - 6.0);
-            ctx.move_to(px + cw * 0.75 - 8.0, mid_y - 12.0);
-            ctx.line_to(px + cw * 0.75, mid_y - 6.0);
-            ctx.line_to(px + cw * 0.75 - 8.0, mid_y);
-            ctx.move_to(px + cw * 0.75, mid_y + 6.0);
-            ctx.line_to(px + cw * 0.25, mid_y + 6.0);
-            ctx.move_to(px + cw * 0.25 + 8.0, mid_y);
-            ctx.line_to(px + cw * 0.25, mid_y + 6.0);
-            ctx.line_to(px + cw * 0.25 + 8.0, mid_y + 12.0);
-            ctx.stroke();
+            for _ in filled..(max_hearts as usize) {
+                html.push_str(
+                    "<span style='color:#6b6b6b;font-size:16px;margin-right:6px;'>♡</span>",
+                );
+            }
+            // Shield charges (powerup) next to the hearts
+            if state.shield > 0 {
+                html.push_str(&format!(
+                    "<span style='color:#5ec8ff;font-size:16px;'>🛡x{}</span>",
+                    state.shield
+                ));
+            }
+            // "slow" powerup countdown
+            if state.slow_until_ms > now {
+                let secs = ((state.slow_until_ms - now) / 1000.0).ceil() as i64;
+                html.push_str(&format!(
+                    "<span style='color:#b4a7ff;font-size:15px;margin-left:6px;'>⏱{secs}s</span>"
+                ));
+            }
+            lives_el.set_inner_html(&html);
         }
     }
 }
 
-#[allow(dead_code)]
-fn apply_tile_effects(piece: &mut Piece, state: &mut BoardState, current_beat: i64, _now: f64) {
-    let tile = state.level.tile(piece.x, piece.y);
-    // Obstacles with post-arrival effects
-    if let Some(obs) = &tile.obstacle {
-        match obs {
-            ObstacleKind::Teleport { to: (tx, ty) } => {
-                piece.x = *tx;
-                piece.y = *ty; // instant relocate
-            }
-            ObstacleKind::Conveyor { dx, dy } => {
-                let nx = piece.x as i8 + *dx;
-                let ny = piece.y as i8 + *dy;
-                if nx >= 0
-                    && ny >= 0
-                    && (nx as u8) < state.level.width
-                    && (ny as u8) < state.level.height
-                {
-                    let nxu = nx as u8;
-                    let nyu = ny as u8;
-                    if !matches!(
-                        state.level.tile(nxu, nyu).obstacle,
-                        Some(ObstacleKind::Block)
-                    ) {
-                        // Queue immediate hop (small duration)
-                        piece.begin_hop(nxu, nyu, _now, piece.hop_duration_ms * 0.8);
-                    }
-                }
+fn on_new_beat(state: &mut BoardState, beat_idx: i64, sub: u32, now: f64) {
+    // `sub` is the subdivision tick within `beat_idx` (0 = on the beat itself).
+    // Grid-based refill: on each tick, refill any empty (None) cells
+    // with a randomly chosen hanzi/pinyin appropriate for the current level.
+    // Skip tiles that are blocked and avoid overwriting the player's tile or
+    // the cat's destination tile while a hop animation is in progress.
+    if state.game_over || state.won {
+        return;
+    }
+    // Metronome: several beats may elapse in one frame (e.g. a throttled tab);
+    // only click for the most recent one instead of a burst.
+    if sub == 0 && beat_idx == state.beat.current_beat(now).floor() as i64 {
+        metronome::click(beat_idx % 4 == 0);
+    }
+    if sub == 0 {
+        update_moving_blocks(state, beat_idx, now);
+    }
+    let lvl = state.level;
+    let mut empties: Vec<usize> = Vec::new();
+    for y in 0..lvl.height {
+        for x in 0..lvl.width {
+            // skip blocked tiles (locked doors stay empty until opened)
+            // `last_beat_idx` still lags behind while beats are being
+            // processed; use the beat the moving blocks were just advanced to.
+            if tile_blocked(lvl, x, y, &state.collected_keys, beat_idx) {
+                continue;
             }
-            ObstacleKind::TempoShift { mult, beats } => {
-                state.hop_time_factor *= 1.0 / mult; // faster tempo => shorter hops
-                state.hop_time_end_beat = current_beat + *beats as i64;
+
+            // Pinned tiles are placed once at level load and never refilled.
+            if lvl.tile(x, y).fixed_hanzi.is_some() {
+                continue;
             }
-            ObstacleKind::Ice => {
-                // If the piece has a known direction, enable sliding momentum.
-                if piece.dir_dx == 0 && piece.dir_dy == 0 {
-                    // choose a greedy direction toward goal so the piece will slide
-                    if let Some((nx, ny)) = choose_next_step(state.level, piece.x, piece.y) {
-                        piece.dir_dx = (nx as i8 - piece.x as i8).signum();
-                        piece.dir_dy = (ny as i8 - piece.y as i8).signum();
-                    }
-                }
-                piece.momentum = 1;
+
+            // Do not refill the player's current tile; it must remain empty.
+            if x == state.cat_x && y == state.cat_y {
+                continue;
             }
-            ObstacleKind::JumpPad { dx, dy, strength } => {
-                // Launch the piece `strength` tiles in the given direction,
-                // or toward the first goal if dx/dy == 0.
-                let mut ldx = *dx;
-                let mut ldy = *dy;
-                if ldx == 0 && ldy == 0 {
-                    // pick direction toward nearest goal
-                    if let Some(&(gx, gy)) = state.level.goal_region.first() {
-                        ldx = (gx as i8 - piece.x as i8).signum();
-                        ldy = (gy as i8 - piece.y as i8).signum();
-                    }
-                }
-                let mut tx = piece.x as i8;
-                let mut ty = piece.y as i8;
-                for _ in 0..*strength {
-                    let nx = tx + ldx;
-                    let ny = ty + ldy;
-                    if nx < 0
-                        || ny < 0
-                        || (nx as u8) >= state.level.width
-                        || (ny as u8) >= state.level.height
-                    {
-                        break;
-                    }
-                    if matches!(
-                        state.level.tile(nx as u8, ny as u8).obstacle,
-                        Some(ObstacleKind::Block)
-                    ) {
-                        break;
-                    }
-                    tx = nx;
-                    ty = ny;
-                }
-                // Queue a faster hop to the landing tile
-                piece.begin_hop(tx as u8, ty as u8, _now, piece.hop_duration_ms * 0.6);
-                piece.momentum = 0; // jump breaks sliding momentum
+
+            // While the cat is mid-hop, avoid refilling the target tile so the
+            // arriving tile remains empty until arrival handling runs; the same
+            // goes for the second tile of a matched word.
+            if state.cat_hopping && x == state.cat_target_x && y == state.cat_target_y {
+                continue;
+            }
+            if state.queued_hop == Some((x, y)) {
+                continue;
+            }
+
+            let idx = y as usize * lvl.width as usize + x as usize;
+            if state.grid[idx].is_none() {
+                empties.push(idx);
             }
-            ObstacleKind::Block => { /* cannot stand here normally (shouldn't happen) */ }
-            ObstacleKind::Transform => { /* handled via modifier if present */ }
         }
     }
-    if let Some(modf) = &tile.modifier {
-        match modf {
-            ModifierKind::ScoreMult { factor, beats } => {
-                state.score_multiplier *= *factor;
-                state.score_mult_end_beat = current_beat + *beats as i64;
+    // Bounded refill: only `refill_per_beat` random empties are filled per tick
+    // (0 = refill everything), so fast play can drain the board.
+    let count = refill_count(
+        state.spawn_pattern,
+        state.refill_per_beat,
+        empties.len(),
+        state.spawn_tick,
+    );
+    state.spawn_tick = state.spawn_tick.wrapping_add(1);
+    for _ in 0..count {
+        let idx = empties.swap_remove(rand_index(empties.len()));
+        let (hanzi, pinyin) = pick_random_hanzi(lvl);
+        state.grid[idx] = Some((hanzi, pinyin));
+        state.tile_spawn_ms[idx] = now;
+        hooks::note_spawn(hanzi, pinyin);
+        if state.boss.is_none()
+            && state.level_index >= BOSS_MIN_LEVEL
+            && rand_index(BOSS_CHANCE) == 0
+        {
+            state.boss = Some(BossTile {
+                idx,
+                hanzi,
+                hits_done: 0,
+                hit_flash_ms: None,
+            });
+        }
+    }
+}
+
+/// Tiles to refill on refill tick `tick` out of `empties`, with `per_tick`
+/// the bounded-refill limit (0 = all). Bursty ticks refill three times the
+/// limit and rest ticks nothing, so the average stays close to steady.
+fn refill_count(pattern: SpawnPattern, per_tick: usize, empties: usize, tick: u32) -> usize {
+    let steady = if per_tick == 0 {
+        empties
+    } else {
+        per_tick.min(empties)
+    };
+    match pattern {
+        SpawnPattern::Steady => steady,
+        SpawnPattern::Bursty if tick % BURST_CYCLE < BURST_TICKS => {
+            if per_tick == 0 {
+                empties
+            } else {
+                (per_tick * 3).min(empties)
             }
-            ModifierKind::SlowHop { factor, beats } => {
-                state.hop_time_factor *= *factor;
-                state.hop_time_end_beat = current_beat + *beats as i64;
+        }
+        SpawnPattern::Bursty => 0,
+    }
+}
+
+fn update_pieces(state: &mut BoardState, now: f64, _whole_beat: i64) {
+    // Advance cat hop animation (if any). We keep this function to preserve the
+    // previous call site but now use it to finish the hop and update canonical
+    // cat coordinates when the animation completes.
+    if state.cat_hopping {
+        let elapsed = now - state.cat_hop_start_ms;
+        let dur = if state.cat_hop_duration_ms <= 0.0 {
+            1.0
+        } else {
+            state.cat_hop_duration_ms
+        };
+        let t = (elapsed / dur).clamp(0.0, 1.0);
+        if t >= 1.0 {
+            // Finish hop animation and update canonical coordinates
+            state.cat_hopping = false;
+            state.cat_x = state.cat_target_x;
+            state.cat_y = state.cat_target_y;
+
+            // Clear the landing tile and refresh neighbors (level 0).
+            refresh_cat_neighbors(state);
+
+            // Landing tile effects may queue an automatic follow-up hop.
+            apply_cat_tile_effects(state, now);
+
+            // Walk on to the second tile of a matched word, unless the landing
+            // tile sent the cat elsewhere.
+            if let Some((qx, qy)) = state.queued_hop.take()
+                && !state.cat_hopping
+                && state.cat_teleport.is_none()
+                && !state.game_over
+            {
+                let dur = state.cat_hop_duration_ms;
+                begin_cat_hop(state, qx, qy, now, dur);
             }
-            ModifierKind::TransformMap { pairs } => {
-                for (from, to) in *pairs {
-                    if piece.hanzi == *from {
-                        piece.hanzi = to; // keep pinyin placeholder; future: pinyin map
-                        break;
-                    }
+        }
+    }
+
+    // Advance a pending teleport: relocate once fully shrunk, clear when expanded.
+    if let Some(tp) = state.cat_teleport.as_ref() {
+        let elapsed = now - tp.start_ms;
+        if !tp.relocated && elapsed >= TELEPORT_MS / 2.0 {
+            let (to_x, to_y) = (tp.to_x, tp.to_y);
+            state.cat_x = to_x;
+            state.cat_y = to_y;
+            state.cat_from_x = to_x;
+            state.cat_from_y = to_y;
+            state.cat_target_x = to_x;
+            state.cat_target_y = to_y;
+            refresh_cat_neighbors(state);
+            if let Some(tp) = state.cat_teleport.as_mut() {
+                tp.relocated = true;
+            }
+        }
+        if elapsed >= TELEPORT_MS {
+            state.cat_teleport = None;
+        }
+    }
+}
+
+/// Move the cat onto a matched tile: queue the hop animation (instead of an
+/// instant teleport), consume the tile and award score immediately while the
+/// visual hop and slash play out. Shared by typed (Enter) and clicked matches.
+fn consume_and_hop(state: &mut BoardState, mx: u8, my: u8, gidx: usize, now: f64) {
+    if state.cat_teleport.is_some() {
+        return;
+    }
+    state.hits += 1;
+    record_outcome(state, true, now);
+    buzz(state, HAPTIC_HIT_MS);
+    // A boss tile absorbs every hit but the last; the cat stays put.
+    let mut boss_bonus = 0;
+    if let Some(boss) = state.boss.as_mut()
+        && boss.idx == gidx
+    {
+        if !boss.hit(now) {
+            bump_combo(state, now);
+            return;
+        }
+        state.boss = None;
+        boss_bonus = BOSS_BONUS;
+    }
+    if let Some(tone) = state.grid[gidx].and_then(|(_, p)| first_tone(p)) {
+        metronome::tone_cue(tone);
+    }
+    state.cat_auto_hops = 0;
+    let boost = speed_boost(now, state.speed_boost_until_ms);
+    let dur = CAT_HOP_MS * state.hop_time_factor / boost;
+    begin_cat_hop(state, mx, my, now, dur);
+    clear_matched_tile(state, mx, my, gidx, now, boss_bonus);
+}
+
+/// Clear a matched tile and score it (plus `bonus`): on-beat bonus, combo,
+/// combo speed boost and the slash effect.
+fn clear_matched_tile(state: &mut BoardState, mx: u8, my: u8, gidx: usize, now: f64, bonus: i64) {
+    let boost = speed_boost(now, state.speed_boost_until_ms);
+    let hit_on_beat = on_beat(
+        state.beat.beat_offset(now),
+        state.beat_window_early,
+        state.beat_window_late,
+    );
+    let chain = if state.level.chain_clear {
+        let (w, h) = (state.level.width as usize, state.level.height as usize);
+        flood_same(&state.grid, w, h, gidx)
+    } else {
+        Vec::new()
+    };
+    if let Some((hanzi, pinyin)) = state.grid[gidx].take() {
+        practice::on_match(hanzi, hit_on_beat);
+        let spawned = state.tile_spawn_ms.get(gidx).copied().unwrap_or(now);
+        timing::record_hit(hanzi, pinyin, now - spawned);
+    }
+    let mut per = 180.0 * state.score_multiplier * combo_multiplier(state.board_combo) * boost;
+    if hit_on_beat {
+        per *= ON_BEAT_BONUS;
+        state.on_beat_flash_ms = Some(now);
+    }
+    state.score += per as i64 + bonus;
+    bump_combo(state, now);
+    if state.board_combo > COMBO_BOOST_THRESHOLD {
+        state.speed_boost_until_ms = now + COMBO_BOOST_MS;
+    }
+    state.slash_effects.push(SlashEffect {
+        x: mx,
+        y: my,
+        start_ms: now,
+    });
+    clear_chain(state, &chain, now, hit_on_beat);
+}
+
+/// Indices of the tiles orthogonally connected to `start` (itself included,
+/// first) that show the same hanzi, on a `w` x `h` grid. Empty if `start` is.
+fn flood_same(
+    grid: &[Option<(&'static str, &'static str)>],
+    w: usize,
+    h: usize,
+    start: usize,
+) -> Vec<usize> {
+    let Some(Some((hanzi, _))) = grid.get(start) else {
+        return Vec::new();
+    };
+    let mut found = vec![start];
+    let mut i = 0;
+    while i < found.len() {
+        let idx = found[i];
+        let (x, y) = (idx % w, idx / w);
+        let neighbors = [
+            (x > 0).then(|| idx - 1),
+            (x + 1 < w).then(|| idx + 1),
+            (y > 0).then(|| idx - w),
+            (y + 1 < h).then(|| idx + w),
+        ];
+        for n in neighbors.into_iter().flatten() {
+            if !found.contains(&n) && matches!(grid[n], Some((other, _)) if other == *hanzi) {
+                found.push(n);
+            }
+        }
+        i += 1;
+    }
+    found
+}
+
+/// Clear the extra tiles of a chain (`chain[0]` is the matched tile itself),
+/// each worth more than the one before; a boss tile stays. Each counts as a
+/// match (`on_beat` like the tile that set it off) for practice and timing.
+fn clear_chain(state: &mut BoardState, chain: &[usize], now: f64, on_beat: bool) {
+    let w = state.level.width as usize;
+    let boss = state.boss.as_ref().map(|b| b.idx);
+    let extra = chain.iter().skip(1).filter(|&&idx| Some(idx) != boss);
+    for (n, &idx) in extra.enumerate() {
+        if let Some((hanzi, pinyin)) = state.grid[idx].take() {
+            practice::on_match(hanzi, on_beat);
+            let spawned = state.tile_spawn_ms.get(idx).copied().unwrap_or(now);
+            timing::record_hit(hanzi, pinyin, now - spawned);
+        }
+        state.score += CHAIN_STEP_POINTS * (n as i64 + 1);
+        state.slash_effects.push(SlashEffect {
+            x: (idx % w) as u8,
+            y: (idx / w) as u8,
+            start_ms: now,
+        });
+    }
+}
+
+/// Apply one typed key to the board: build / edit the pinyin buffer or submit it
+/// with Enter. Returns whether the key was one the board handles. Shared by the
+/// keyboard listener and replay playback.
+fn handle_board_key(state: &mut BoardState, key: &str, now: f64) -> bool {
+    if state.numpad_nav
+        && let Some((dx, dy)) = numpad_direction(key)
+    {
+        let hopped = hop_toward(state, dx, dy, now);
+        hooks::key(key, hopped, &state.typing, None);
+        return true;
+    }
+    if key == "Escape" {
+        state.typing.clear();
+        hooks::key(key, false, &state.typing, None);
+    } else if key == "Backspace" {
+        state.typing.pop();
+        hooks::key(key, false, &state.typing, None);
+    } else if key == "Enter" {
+        if !state.typing.is_empty() {
+            let typed = state.typing.clone();
+            // Look for matching adjacent tile (up, right, down, left)
+            let dirs: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+            let mut found: Option<((u8, u8), usize)> = None;
+            for (dx, dy) in dirs.iter() {
+                let Some((nx, ny)) = state.level.offset(state.cat_x, state.cat_y, *dx, *dy) else {
+                    continue;
+                };
+                // skip blocked tiles (incl. locked doors)
+                if tile_blocked(
+                    state.level,
+                    nx,
+                    ny,
+                    &state.collected_keys,
+                    state.beat.last_beat_idx,
+                ) {
+                    continue;
+                }
+                let idx = ny as usize * state.level.width as usize + nx as usize;
+                if let Some((_, pinyin)) = state.grid[idx]
+                    && pinyin_matches(&typed, pinyin, state.ignore_tones)
+                {
+                    found = Some(((nx, ny), idx));
+                    break;
                 }
             }
+            // Otherwise try a word spelled by two tiles in a row from the cat.
+            let word = if found.is_none() {
+                word_paths(state)
+                    .into_iter()
+                    .find(|w| pinyin_matches(&typed, w.pinyin, state.ignore_tones))
+            } else {
+                None
+            };
+            let target = found
+                .and_then(|(_, gidx)| state.grid[gidx])
+                .map(|(_, p)| p)
+                .or(word.as_ref().map(|w| w.pinyin));
+            hooks::key(key, target.is_some(), &typed, target);
+            if let Some(((mx, my), gidx)) = found {
+                consume_and_hop(state, mx, my, gidx, now);
+            } else if let Some(word) = word {
+                match_word(state, &word, now);
+            } else {
+                register_miss(state, &typed, now);
+            }
+            state.typing.clear();
+        }
+    } else if key.len() == 1 {
+        let c = key.chars().next().unwrap();
+        let ch = if c.is_ascii_alphabetic() {
+            c.to_ascii_lowercase()
+        } else if c.is_ascii_digit()
+            && matches!(c, '1' | '2' | '3' | '4' | '5')
+            && state
+                .typing
+                .chars()
+                .last()
+                .map(|lc| lc.is_ascii_alphabetic())
+                .unwrap_or(false)
+        {
+            c
+        } else {
+            return false;
+        };
+        // Forgiving mode drops a wrong character instead of typing it.
+        if state.forgiving {
+            let next = format!("{}{ch}", state.typing);
+            if breaks_adjacent_prefix(state, &next) {
+                hooks::key(key, false, &state.typing, None);
+                return false;
+            }
+        }
+        state.typing.push(ch);
+        let target = typing_target(state, &state.typing);
+        hooks::key(key, target.is_some(), &state.typing, target);
+        // Strict mode punishes the wrong character right away, like a missed
+        // submission (a shield absorbs it).
+        if state.strict_mode && breaks_adjacent_prefix(state, &state.typing) {
+            let typed = std::mem::take(&mut state.typing);
+            register_miss(state, &typed, now);
+        }
+    } else {
+        return false;
+    }
+    true
+}
+
+/// Direction of a number key in numpad layout (7 8 9 on top, 1 2 3 at the
+/// bottom); 5 is the cat's own tile.
+fn numpad_direction(key: &str) -> Option<(i8, i8)> {
+    let digit = match key.as_bytes() {
+        [d @ b'1'..=b'9'] => (d - b'1') as i8,
+        _ => return None,
+    };
+    Some((digit % 3 - 1, 1 - digit / 3))
+}
+
+/// Hop onto the neighbour in direction `(dx, dy)` if it is reachable (in
+/// bounds or across a wrap, not blocked) and holds a hanzi, like clicking it.
+/// Returns whether the cat hopped.
+fn hop_toward(state: &mut BoardState, dx: i8, dy: i8, now: f64) -> bool {
+    if (dx, dy) == (0, 0) {
+        return false;
+    }
+    let Some((x, y)) = step_target(
+        state.level,
+        state.cat_x,
+        state.cat_y,
+        dx,
+        dy,
+        &state.collected_keys,
+        state.beat.last_beat_idx,
+    ) else {
+        return false;
+    };
+    let idx = y as usize * state.level.width as usize + x as usize;
+    if state.grid[idx].is_none() || state.cat_teleport.is_some() {
+        return false;
+    }
+    consume_and_hop(state, x, y, idx, now);
+    true
+}
+
+/// A non-empty submission matched no adjacent tile: break the combo, flash the
+/// cat's tile red and, in strict mode, cost a life (a shield absorbs that miss
+/// and keeps the combo). Adjacent hanzi whose pinyin differs from `typed` only
+/// in tone go on the review sheet. The tile `typed` was aimed at, if any (see
+/// `missed_target`), logs a miss in the timing log and resets its practice
+/// streak.
+fn register_miss(state: &mut BoardState, typed: &str, now: f64) {
+    let tiles = adjacent_tiles(state);
+    let pinyins: Vec<&str> = tiles.iter().map(|&(.., p)| p).collect();
+    if let Some(i) = missed_target(typed, &pinyins) {
+        let (x, y, pinyin) = tiles[i];
+        if let Some((hanzi, _)) = state.grid[y as usize * state.level.width as usize + x as usize] {
+            timing::record_miss(hanzi, pinyin);
+            practice::on_miss(hanzi);
+        }
+    }
+    for (x, y, pinyin) in tiles {
+        if review::is_tone_miss(typed, pinyin)
+            && let Some((hanzi, _)) =
+                state.grid[y as usize * state.level.width as usize + x as usize]
+        {
+            review::record_miss(hanzi, pinyin);
+        }
+    }
+    state.miss_flash_ms = Some(now);
+    state.misses += 1;
+    record_outcome(state, false, now);
+    buzz(state, HAPTIC_MISS_MS);
+    // Zen runs cannot lose lives, so a shield has nothing to absorb there.
+    if state.strict_mode && !state.zen && state.shield > 0 {
+        state.shield -= 1;
+        push_life_loss_effect(state, true, now);
+        return;
+    }
+    state.board_combo = 0;
+    state.speed_boost_until_ms = 0.0;
+    if state.strict_mode {
+        lose_life(state, now);
+    }
+}
+
+/// Occupied tiles an Enter submission can match (up, right, down, left), with
+/// their pinyin.
+fn adjacent_tiles(state: &BoardState) -> Vec<(u8, u8, &'static str)> {
+    [(0, -1), (1, 0), (0, 1), (-1, 0)]
+        .into_iter()
+        .filter_map(|(dx, dy)| {
+            let (nx, ny) = step_target(
+                state.level,
+                state.cat_x,
+                state.cat_y,
+                dx,
+                dy,
+                &state.collected_keys,
+                state.beat.last_beat_idx,
+            )?;
+            state.grid[ny as usize * state.level.width as usize + nx as usize]
+                .map(|(_, p)| (nx, ny, p))
+        })
+        .collect()
+}
+
+/// Pinyin the buffer can aim for: the adjacent tiles' and that of every
+/// two-tile word starting next to the cat.
+fn adjacent_pinyins(state: &BoardState) -> Vec<&'static str> {
+    adjacent_tiles(state)
+        .into_iter()
+        .map(|(_, _, p)| p)
+        .chain(word_paths(state).into_iter().map(|w| w.pinyin))
+        .collect()
+}
+
+/// Two tiles in a row from the cat (`first` adjacent to it, `second` adjacent
+/// to `first`) whose hanzi spell a known word, with that word's pinyin.
+struct WordPath {
+    first: (u8, u8, usize),
+    second: (u8, u8, usize),
+    pinyin: &'static str,
+}
+
+/// Pinyin of the word `first` + `second` if one of `pools` lists it.
+fn word_pinyin(
+    pools: &[&[(&'static str, &'static str)]],
+    first: &str,
+    second: &str,
+) -> Option<&'static str> {
+    pools.iter().flat_map(|p| p.iter()).find_map(|&(h, p)| {
+        (h.len() == first.len() + second.len() && h.starts_with(first) && h.ends_with(second))
+            .then_some(p)
+    })
+}
+
+/// Every two-tile word reachable from the cat. Words come from the level's
+/// pool (so a dataset or practice set applies) and `MULTI_HANZI`; boss tiles
+/// only take single hits and are left out.
+fn word_paths(state: &BoardState) -> Vec<WordPath> {
+    const DIRS: [(i8, i8); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+    let pool = level_hanzi_pool(state.level);
+    let pools = [&pool[..], crate::MULTI_HANZI];
+    let w = state.level.width as usize;
+    let boss = state.boss.as_ref().map(|b| b.idx);
+    let tile = |x: u8, y: u8| {
+        let idx = y as usize * w + x as usize;
+        (Some(idx) != boss)
+            .then(|| state.grid[idx].map(|(h, _)| (idx, h)))
+            .flatten()
+    };
+    let step = |x, y, (dx, dy): (i8, i8)| {
+        step_target(
+            state.level,
+            x,
+            y,
+            dx,
+            dy,
+            &state.collected_keys,
+            state.beat.last_beat_idx,
+        )
+    };
+    let mut paths = Vec::new();
+    for d1 in DIRS {
+        let Some((x1, y1)) = step(state.cat_x, state.cat_y, d1) else {
+            continue;
+        };
+        let Some((i1, h1)) = tile(x1, y1) else {
+            continue;
+        };
+        for d2 in DIRS {
+            let Some((x2, y2)) = step(x1, y1, d2) else {
+                continue;
+            };
+            if (x2, y2) == (state.cat_x, state.cat_y) {
+                continue;
+            }
+            if let Some((i2, h2)) = tile(x2, y2)
+                && let Some(pinyin) = word_pinyin(&pools, h1, h2)
+            {
+                paths.push(WordPath {
+                    first: (x1, y1, i1),
+                    second: (x2, y2, i2),
+                    pinyin,
+                });
+            }
+        }
+    }
+    paths
+}
+
+/// Clear both tiles of a matched word: the cat hops onto the first and walks
+/// on to the second when it lands; the second tile earns `WORD_BONUS`.
+fn match_word(state: &mut BoardState, word: &WordPath, now: f64) {
+    if state.cat_teleport.is_some() {
+        return;
+    }
+    let (x1, y1, i1) = word.first;
+    let (x2, y2, i2) = word.second;
+    consume_and_hop(state, x1, y1, i1, now);
+    state.hits += 1;
+    record_outcome(state, true, now);
+    clear_matched_tile(state, x2, y2, i2, now, WORD_BONUS);
+    state.queued_hop = Some((x2, y2));
+}
+
+/// Whether `typed` can no longer become any of `candidates` (never true when
+/// there are no candidates to aim for).
+fn breaks_prefix(typed: &str, candidates: &[&str]) -> bool {
+    !candidates.is_empty() && !candidates.iter().any(|c| c.starts_with(typed))
+}
+
+/// `breaks_prefix` against the adjacent tiles' pinyin, comparing letters only
+/// when tones are ignored.
+fn breaks_adjacent_prefix(state: &BoardState, typed: &str) -> bool {
+    let candidates = adjacent_pinyins(state);
+    if !state.ignore_tones {
+        return breaks_prefix(typed, &candidates);
+    }
+    let toneless: Vec<String> = candidates.iter().map(|p| strip_tones(p)).collect();
+    let toneless: Vec<&str> = toneless.iter().map(String::as_str).collect();
+    breaks_prefix(&strip_tones(typed), &toneless)
+}
+
+/// The adjacent tile's pinyin `typed` is on its way to, if any.
+fn typing_target(state: &BoardState, typed: &str) -> Option<&'static str> {
+    adjacent_pinyins(state).into_iter().find(|p| {
+        if state.ignore_tones {
+            strip_tones(p).starts_with(&strip_tones(typed))
+        } else {
+            p.starts_with(typed)
+        }
+    })
+}
+
+/// `pinyin` without its tone digits.
+fn strip_tones(pinyin: &str) -> String {
+    pinyin.chars().filter(|c| !c.is_ascii_digit()).collect()
+}
+
+/// Whether a submitted `typed` names `pinyin`.
+fn pinyin_matches(typed: &str, pinyin: &str, ignore_tones: bool) -> bool {
+    typed == pinyin || (ignore_tones && strip_tones(typed) == strip_tones(pinyin))
+}
+
+/// Whether a non-empty buffer is still on its way to `pinyin`.
+fn is_typing_target(typed: &str, pinyin: &str) -> bool {
+    !typed.is_empty() && pinyin.starts_with(typed)
+}
+
+/// Length of the longest prefix of `typed` that starts any of `candidates`.
+fn matched_prefix_len(typed: &str, candidates: &[&str]) -> usize {
+    candidates
+        .iter()
+        .map(|c| {
+            typed
+                .bytes()
+                .zip(c.bytes())
+                .take_while(|(a, b)| a == b)
+                .count()
+        })
+        .max()
+        .unwrap_or(0)
+}
+
+/// Index of the candidate a missed submission `typed` was aimed at: the one
+/// sharing the longest prefix with it. `None` if nothing shares a letter or
+/// candidates with different pinyin tie.
+fn missed_target(typed: &str, candidates: &[&str]) -> Option<usize> {
+    let best = matched_prefix_len(typed, candidates);
+    if best == 0 {
+        return None;
+    }
+    let mut aimed = candidates
+        .iter()
+        .enumerate()
+        .filter(|(_, c)| matched_prefix_len(typed, &[c]) == best);
+    let (i, first) = aimed.next()?;
+    aimed.all(|(_, c)| c == first).then_some(i)
+}
+
+/// Typing overlay markup: the part of the buffer still matching an adjacent
+/// tile's pinyin, then any diverging tail in red. The buffer is ASCII
+/// letters / digits only, so it needs no escaping.
+fn typing_html(state: &BoardState) -> String {
+    let split = matched_prefix_len(&state.typing, &adjacent_pinyins(state));
+    let (ok, bad) = state.typing.split_at(split);
+    format!("<span>{ok}</span><span style='color:#ff5c5c;'>{bad}</span>")
+}
+
+/// Tone-contour mark per syllable of a numbered pinyin string (`"ni3hao3"` ->
+/// `["ˇ", "ˇ"]`); the neutral tone 5 keeps its slot but draws nothing.
+fn tone_marks(pinyin: &str) -> Vec<&'static str> {
+    tones(pinyin)
+        .map(|tone| ["ˉ", "ˊ", "ˇ", "ˋ", ""][tone as usize - 1])
+        .collect()
+}
+
+/// Tone (1-5) of each syllable of a numbered pinyin string, in order.
+fn tones(pinyin: &str) -> impl Iterator<Item = u8> + '_ {
+    pinyin
+        .bytes()
+        .filter(|b| (b'1'..=b'5').contains(b))
+        .map(|b| b - b'0')
+}
+
+/// Tone digit of the first syllable of a numbered pinyin string.
+fn first_tone(pinyin: &str) -> Option<u8> {
+    tones(pinyin).next()
+}
+
+/// Horizontal extent of the on-beat window on the beat strip, as fractions of
+/// the canvas width: the strip spans one beat with the beat itself centred.
+/// Uses the same `early` / `late` fractions as `on_beat`.
+fn judge_band(early: f64, late: f64) -> (f64, f64) {
+    ((0.5 - early).max(0.0), (0.5 + late).min(1.0))
+}
+
+/// Whether a signed beat offset (negative = before the beat) lies inside the
+/// on-beat window of `early` / `late` beat fractions.
+fn on_beat(offset: f64, early: f64, late: f64) -> bool {
+    (-early..=late).contains(&offset)
+}
+
+/// Hop-speed / score factor of the combo boost at `now`: `COMBO_BOOST_FACTOR`
+/// right after a qualifying match, decaying linearly to 1 at `until_ms`.
+fn speed_boost(now: f64, until_ms: f64) -> f64 {
+    let remaining = ((until_ms - now) / COMBO_BOOST_MS).clamp(0.0, 1.0);
+    1.0 + (COMBO_BOOST_FACTOR - 1.0) * remaining
+}
+
+/// Score multiplier for a match made with `combo` consecutive matches behind it.
+fn combo_multiplier(combo: u32) -> f64 {
+    1.0 + combo as f64 / 10.0
+}
+
+/// Level bpm scaled by the adaptive factor (when on) and the "slow" powerup
+/// (while active).
+fn target_bpm(state: &BoardState, now: f64) -> f64 {
+    let mut bpm = state.level.bpm;
+    if state.adaptive {
+        bpm *= tempo_factor(state.difficulty_progress);
+    }
+    if state.slow_until_ms > now {
+        bpm *= powerups::SLOW_FACTOR;
+    }
+    bpm
+}
+
+/// Retune the beat clock to `target_bpm` without a beat jump.
+fn apply_tempo(state: &mut BoardState, now: f64) {
+    let bpm = target_bpm(state, now);
+    if (bpm - state.beat.bpm).abs() > f64::EPSILON {
+        state.beat.set_bpm(bpm, now);
+    }
+}
+
+fn tempo_factor(progress: f64) -> f64 {
+    let (lo, hi) = ADAPTIVE_TEMPO_RANGE;
+    lo + (hi - lo) * progress
+}
+
+/// `progress` moved by at most `ADAPTIVE_STEP` towards faster when the recent
+/// accuracy beats `ADAPTIVE_TARGET_ACCURACY` and slower when it falls short.
+/// Holds still until the window is full.
+fn nudge_difficulty(progress: f64, outcomes: &std::collections::VecDeque<bool>) -> f64 {
+    if outcomes.len() < ADAPTIVE_WINDOW {
+        return progress;
+    }
+    let acc = outcomes.iter().filter(|&&hit| hit).count() as f64 / outcomes.len() as f64;
+    let delta = ((acc - ADAPTIVE_TARGET_ACCURACY) / (1.0 - ADAPTIVE_TARGET_ACCURACY))
+        .clamp(-1.0, 1.0)
+        * ADAPTIVE_STEP;
+    (progress + delta).clamp(0.0, 1.0)
+}
+
+/// Feed one submission outcome to the adaptive tempo (no-op when it is off).
+fn record_outcome(state: &mut BoardState, hit: bool, now: f64) {
+    if !state.adaptive {
+        return;
+    }
+    state.recent_outcomes.push_back(hit);
+    if state.recent_outcomes.len() > ADAPTIVE_WINDOW {
+        state.recent_outcomes.pop_front();
+    }
+    state.difficulty_progress = nudge_difficulty(state.difficulty_progress, &state.recent_outcomes);
+    apply_tempo(state, now);
+}
+
+/// Lives earned by score gained since the `last_award` watermark, one per
+/// `interval` points (`interval <= 0` disables); advances the watermark past
+/// every threshold crossed, whether or not the life can be granted.
+fn life_awards(score: i64, last_award: &mut i64, interval: i64) -> i32 {
+    if interval <= 0 {
+        *last_award = score.max(*last_award);
+        return 0;
+    }
+    let earned = (score - *last_award).max(0) / interval;
+    *last_award += earned * interval;
+    earned as i32
+}
+
+/// Extend the combo by one match, tracking the run's best.
+fn bump_combo(state: &mut BoardState, now: f64) {
+    state.board_combo += 1;
+    state.combo_last_beat = state.beat.last_beat_idx;
+    state.max_combo = state.max_combo.max(state.board_combo);
+    metronome::combo_blip(state.board_combo);
+    // A combo below the last milestone means it broke since; start over.
+    if state.board_combo < state.last_firework_combo {
+        state.last_firework_combo = 0;
+    }
+    if firework_due(state.board_combo, state.last_firework_combo) {
+        state.last_firework_combo = state.board_combo;
+        launch_fireworks(state, now);
+    }
+}
+
+fn show_banner(state: &mut BoardState, title: String, subtitle: String, now: f64) {
+    state.banner = Some(Banner {
+        title,
+        subtitle,
+        start_ms: now,
+    });
+}
+
+/// Index into `TIERS` of the tier that run progress `progress` falls in.
+fn tier_index(progress: f64) -> usize {
+    TIERS
+        .iter()
+        .rposition(|&(_, from)| progress >= from)
+        .unwrap_or(0)
+}
+
+/// Name of the current difficulty tier ("Warmup" before the board starts).
+#[wasm_bindgen]
+pub fn current_tier() -> String {
+    let tier = BOARD_STATE.with(|cell| cell.borrow().as_ref().map_or(0, |s| s.tier));
+    TIERS[tier].0.to_string()
+}
+
+/// Opacity of a banner at `t` (0..1 of its lifetime): a quick fade in,
+/// a hold, then a fade out over the last third.
+fn banner_alpha(t: f64) -> f64 {
+    (t * 8.0).min((1.0 - t) * 3.0).clamp(0.0, 1.0)
+}
+
+/// Whether reaching `combo` hits a new fireworks milestone past `last`.
+fn firework_due(combo: u32, last: u32) -> bool {
+    combo > last && combo.is_multiple_of(FIREWORK_EVERY)
+}
+
+/// Three staggered bursts above the cat, oldest dropped beyond `MAX_FIREWORKS`.
+fn launch_fireworks(state: &mut BoardState, now: f64) {
+    let cx = state.cat_x as f64 + 0.5;
+    let cy = state.cat_y as f64 + 0.5;
+    for (i, dx) in [-1.0, 0.0, 1.0].into_iter().enumerate() {
+        state.fireworks.push(Firework {
+            x: cx + dx,
+            y: cy - 0.5,
+            start_ms: now + i as f64 * 150.0,
+            hue: (state.board_combo * 37 + i as u32 * 120) % 360,
+        });
+    }
+    let excess = state.fireworks.len().saturating_sub(MAX_FIREWORKS);
+    state.fireworks.drain(..excess);
+}
+
+/// Offset (px) of spark `i` of a burst `age` ms after launch: the burst rises
+/// for the first third, then the sparks fan out and fall back under gravity.
+fn firework_spark(i: usize, age: f64) -> (f64, f64) {
+    let rise_ms = FIREWORK_MS / 3.0;
+    let rise = -0.15 * age.min(rise_ms);
+    let t = (age - rise_ms).max(0.0);
+    let angle = i as f64 * std::f64::consts::TAU / FIREWORK_SPARKS as f64;
+    let speed = 0.09 + 0.03 * (i % 3) as f64;
+    (
+        angle.cos() * speed * t,
+        rise + angle.sin() * speed * t + 0.00008 * t * t,
+    )
+}
+
+/// Combo meter fill (percent, capped at `COMBO_METER_CAP`) and colour: white,
+/// gold from 10, red from 20.
+fn combo_meter(combo: u32) -> (f64, &'static str) {
+    let pct = combo.min(COMBO_METER_CAP) as f64 * 100.0 / COMBO_METER_CAP as f64;
+    let color = match combo {
+        0..10 => "#ffffff",
+        10..20 => "#ffd166",
+        _ => "#ff4d4d",
+    };
+    (pct, color)
+}
+
+/// Start a cat hop animation from its current tile to `(to_x, to_y)`.
+fn begin_cat_hop(state: &mut BoardState, to_x: u8, to_y: u8, now: f64, duration_ms: f64) {
+    state.cat_from_x = state.cat_x;
+    state.cat_from_y = state.cat_y;
+    state.cat_target_x = to_x;
+    state.cat_target_y = to_y;
+    state.cat_hop_start_ms = now;
+    state.cat_hop_duration_ms = duration_ms;
+    state.cat_hopping = true;
+}
+
+/// Apply obstacle effects of the tile the cat just landed on. Called once per
+/// completed hop from `update_pieces`.
+fn apply_cat_tile_effects(state: &mut BoardState, now: f64) {
+    let tile = *state.level.tile(state.cat_x, state.cat_y);
+    if let Some(ModifierKind::ScoreMult { factor, beats }) = tile.modifier {
+        state.score_multiplier = factor;
+        state.score_mult_end_beat = state.beat.last_beat_idx + beats as i64;
+    }
+    if let Some(ObstacleKind::Key { id }) = tile.obstacle
+        && !state.collected_keys.contains(&id)
+    {
+        state.collected_keys.push(id);
+    }
+    if matches!(tile.obstacle, Some(ObstacleKind::Spike)) {
+        state.slash_effects.push(SlashEffect {
+            x: state.cat_x,
+            y: state.cat_y,
+            start_ms: now,
+        });
+        lose_life(state, now);
+        return;
+    }
+    // Cap chained auto-hops so facing conveyors cannot loop forever.
+    if state.cat_auto_hops >= MAX_CAT_AUTO_HOPS {
+        return;
+    }
+    match tile.obstacle {
+        Some(ObstacleKind::Conveyor { dx, dy }) => {
+            if let Some((nx, ny)) = step_target(
+                state.level,
+                state.cat_x,
+                state.cat_y,
+                dx,
+                dy,
+                &state.collected_keys,
+                state.beat.last_beat_idx,
+            ) {
+                state.cat_auto_hops += 1;
+                let dur = CAT_HOP_MS * 0.8 * state.hop_time_factor;
+                begin_cat_hop(state, nx, ny, now, dur);
+            }
+        }
+        Some(ObstacleKind::Ice) => {
+            // Momentum: keep gliding in the incoming direction until a wall or a
+            // non-ice tile stops the cat (each landing re-enters this function).
+            let dx = (state.cat_x as i8 - state.cat_from_x as i8).signum();
+            let dy = (state.cat_y as i8 - state.cat_from_y as i8).signum();
+            if dx == 0 && dy == 0 {
+                return;
+            }
+            if let Some((nx, ny)) = step_target(
+                state.level,
+                state.cat_x,
+                state.cat_y,
+                dx,
+                dy,
+                &state.collected_keys,
+                state.beat.last_beat_idx,
+            ) {
+                state.cat_auto_hops += 1;
+                let dur = CAT_HOP_MS * 0.6 * state.hop_time_factor;
+                begin_cat_hop(state, nx, ny, now, dur);
+            }
+        }
+        Some(ObstacleKind::Teleport { to: (tx, ty) }) => {
+            let lvl = state.level;
+            let valid = tx < lvl.width
+                && ty < lvl.height
+                && !tile_blocked(lvl, tx, ty, &state.collected_keys, state.beat.last_beat_idx);
+            // Self-targeting portals would only replay the animation; ignore them.
+            if valid && (tx, ty) != (state.cat_x, state.cat_y) {
+                state.cat_teleport = Some(TeleportEffect {
+                    to_x: tx,
+                    to_y: ty,
+                    start_ms: now,
+                    relocated: false,
+                });
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Moving blocks advanced to `beat`: clear any hanzi under them and cost a life
+/// if one lands on the cat. A block resting on the cat's tile for the rest of
+/// its period does not cost more.
+fn update_moving_blocks(state: &mut BoardState, beat: i64, now: f64) {
+    let lvl = state.level;
+    for t in lvl.tiles {
+        if let Some(ObstacleKind::MovingBlock { path, period_beats }) = t.obstacle
+            && let Some((bx, by)) = moving_block_tile(path, period_beats, beat)
+        {
+            state.grid[by as usize * lvl.width as usize + bx as usize] = None;
+            if moving_block_arrives(period_beats, beat)
+                && (bx, by) == (state.cat_x, state.cat_y)
+                && !state.cat_hopping
+            {
+                state.slash_effects.push(SlashEffect {
+                    x: bx,
+                    y: by,
+                    start_ms: now,
+                });
+                lose_life(state, now);
+            }
+        }
+    }
+}
+
+/// Remove one life; the game ends when none are left. An active shield absorbs
+/// the loss instead. Either way a crack marks the cat's tile. Zen runs have no
+/// lives to lose.
+fn lose_life(state: &mut BoardState, now: f64) {
+    if state.zen {
+        return;
+    }
+    if state.shield > 0 {
+        state.shield -= 1;
+        push_life_loss_effect(state, true, now);
+        return;
+    }
+    push_life_loss_effect(state, false, now);
+    state.lives -= 1;
+    if state.lives <= 0 {
+        state.lives = 0;
+        state.game_over = true;
+    }
+}
+
+fn push_life_loss_effect(state: &mut BoardState, shielded: bool, now: f64) {
+    state.life_loss_effects.push(LifeLossEffect {
+        x: state.cat_x,
+        y: state.cat_y,
+        start_ms: now,
+        shielded,
+    });
+}
+
+/// Whether `(x, y)` is impassable: a `Block`, or a `Door` whose key is not in `keys`.
+fn tile_blocked(level: &LevelDesc, x: u8, y: u8, keys: &[u8], beat: i64) -> bool {
+    match level.tile(x, y).obstacle {
+        Some(ObstacleKind::Block) => true,
+        Some(ObstacleKind::Door { id }) => !keys.contains(&id),
+        _ => moving_block_at(level, x, y, beat),
+    }
+}
+
+/// Waypoint occupied by a moving block at whole beat `beat`.
+fn moving_block_tile(path: &[(u8, u8)], period_beats: u32, beat: i64) -> Option<(u8, u8)> {
+    if path.is_empty() {
+        return None;
+    }
+    let step = beat.div_euclid(period_beats.max(1) as i64);
+    Some(path[step.rem_euclid(path.len() as i64) as usize])
+}
+
+/// Whether a moving block with `period_beats` arrives on a waypoint at `beat`
+/// (rather than resting on the one it reached earlier).
+fn moving_block_arrives(period_beats: u32, beat: i64) -> bool {
+    beat.rem_euclid(period_beats.max(1) as i64) == 0
+}
+
+/// Render position (in tile units) of a moving block at fractional beat `beat`.
+/// It rests on its waypoint and glides to the next one during the last quarter
+/// of each period, so the drawn block matches `moving_block_tile` collisions.
+fn moving_block_pos(path: &[(u8, u8)], period_beats: u32, beat: f64) -> Option<(f64, f64)> {
+    let (x0, y0) = moving_block_tile(path, period_beats, beat.floor() as i64)?;
+    let period = period_beats.max(1) as f64;
+    let frac = beat.rem_euclid(period) / period;
+    let (x1, y1) = moving_block_tile(path, period_beats, (beat + period).floor() as i64)?;
+    let t = ((frac - 0.75) / 0.25).clamp(0.0, 1.0);
+    Some((
+        x0 as f64 + (x1 as f64 - x0 as f64) * t,
+        y0 as f64 + (y1 as f64 - y0 as f64) * t,
+    ))
+}
+
+/// Whether any moving block on the level occupies `(x, y)` at `beat`.
+fn moving_block_at(level: &LevelDesc, x: u8, y: u8, beat: i64) -> bool {
+    level.tiles.iter().any(|t| match t.obstacle {
+        Some(ObstacleKind::MovingBlock { path, period_beats }) => {
+            moving_block_tile(path, period_beats, beat) == Some((x, y))
+        }
+        _ => false,
+    })
+}
+
+/// Tile one step from `(x, y)` in direction `(dx, dy)` if it is in-bounds (or wraps) and not blocked.
+fn step_target(
+    level: &LevelDesc,
+    x: u8,
+    y: u8,
+    dx: i8,
+    dy: i8,
+    keys: &[u8],
+    beat: i64,
+) -> Option<(u8, u8)> {
+    let (nxu, nyu) = level.offset(x, y, dx, dy)?;
+    if tile_blocked(level, nxu, nyu, keys, beat) {
+        return None;
+    }
+    Some((nxu, nyu))
+}
+
+fn render_board(state: &mut BoardState, now: f64) {
+    // Render background with a subtle beat pulse.
+    let beat_phase = {
+        let cb = state.beat.current_beat(now);
+        cb - cb.floor()
+    };
+    let pulse = ((beat_phase * std::f64::consts::TAU).sin() * 0.5 + 0.5) * 0.25;
+    let cell_w = state.canvas.width() as f64 / state.level.width as f64;
+    let cell_h = state.canvas.height() as f64 / state.level.height as f64;
+    let pal = palette::current();
+    state
+        .ctx
+        .set_fill_style_str(&palette::pulse_fill(&pal, (pulse * 40.0) as i32));
+    state.ctx.fill_rect(
+        0.0,
+        0.0,
+        state.canvas.width() as f64,
+        state.canvas.height() as f64,
+    );
+
+    // Top accent band (spawn row visual)
+    state.ctx.set_fill_style_str("rgba(255,220,120,0.08)");
+    state
+        .ctx
+        .fill_rect(0.0, 0.0, state.canvas.width() as f64, cell_h);
+
+    // Highlight goal region tiles
+    state.ctx.set_fill_style_str("rgba(120,200,255,0.10)");
+    for &(gx, gy) in state.level.goal_region.iter() {
+        let px = gx as f64 * cell_w;
+        let py = gy as f64 * cell_h;
+        state.ctx.fill_rect(px, py, cell_w, cell_h);
+    }
+
+    // Grid lines
+    state.ctx.set_stroke_style_str(&palette::hex(pal.grid));
+    state.ctx.set_line_width(2.0);
+    for x in 0..=state.level.width {
+        let fx = x as f64 * cell_w;
+        line(&state.ctx, fx, 0.0, fx, state.canvas.height() as f64);
+    }
+    for y in 0..=state.level.height {
+        let fy = y as f64 * cell_h;
+        line(&state.ctx, 0.0, fy, state.canvas.width() as f64, fy);
+    }
+
+    // Hover highlight
+    if let Some((hx, hy)) = state.hover_tile
+        && hx < state.level.width
+        && hy < state.level.height
+    {
+        let px = hx as f64 * cell_w;
+        let py = hy as f64 * cell_h;
+        state.ctx.set_stroke_style_str("rgba(255,240,150,0.55)");
+        state.ctx.set_line_width(3.0);
+        state
+            .ctx
+            .stroke_rect(px + 1.5, py + 1.5, cell_w - 3.0, cell_h - 3.0);
+    }
+
+    // Obstacles (draw before cell content so they sit beneath Hanzi when appropriate)
+    for y in 0..state.level.height {
+        for x in 0..state.level.width {
+            let t = state.level.tile(x, y);
+            if let Some(obs) = &t.obstacle {
+                draw_obstacle(&state.ctx, obs, x, y, cell_w, cell_h, &state.collected_keys);
+            }
+        }
+    }
+
+    // Moving blocks: drawn at their beat-interpolated position (the declaring
+    // tile itself is only an anchor).
+    let beat_now = state.beat.current_beat(now);
+    for y in 0..state.level.height {
+        for x in 0..state.level.width {
+            if let Some(ObstacleKind::MovingBlock { path, period_beats }) =
+                state.level.tile(x, y).obstacle
+                && let Some((fx, fy)) = moving_block_pos(path, period_beats, beat_now)
+            {
+                let px = fx * cell_w;
+                let py = fy * cell_h;
+                state.ctx.set_fill_style_str("#6a2a55");
+                state
+                    .ctx
+                    .fill_rect(px + 3.0, py + 3.0, cell_w - 6.0, cell_h - 6.0);
+                state.ctx.set_stroke_style_str("rgba(255,150,220,0.6)");
+                state.ctx.set_line_width(2.0);
+                state
+                    .ctx
+                    .stroke_rect(px + 6.0, py + 6.0, cell_w - 12.0, cell_h - 12.0);
+            }
+        }
+    }
+
+    // Draw cell hanzi (centered). Use a consistent layered stroke+fill like the piece renderer.
+    state.ctx.set_shadow_color("rgba(0,0,0,0.55)");
+    state.ctx.set_shadow_blur(12.0);
+    state.ctx.set_shadow_offset_x(0.0);
+    state.ctx.set_shadow_offset_y(3.0);
+
+    // Track fills / clears since the last frame; a level switch resizes the grid.
+    if state.cell_fades.len() != state.grid.len() {
+        state.cell_fades = vec![CellFade::default(); state.grid.len()];
+    }
+    for (fade, cell) in state.cell_fades.iter_mut().zip(&state.grid) {
+        fade.sync(cell.map(|(h, _)| h), now);
+    }
+
+    for y in 0..state.level.height {
+        for x in 0..state.level.width {
+            let idx = y as usize * state.level.width as usize + x as usize;
+            let fade = state.cell_fades[idx];
+            if let Some(shown) = fade.shown {
+                let is_boss = state.boss.is_some_and(|b| b.idx == idx);
+                // Reverse mode labels tiles with their pinyin (nothing while
+                // a cleared tile fades out).
+                let hanzi = if state.reverse {
+                    state.grid[idx].map_or("", |(_, p)| p)
+                } else {
+                    shown
+                };
+                if state.reverse {
+                    state.ctx.set_font("bold 22px 'Fira Code', monospace");
+                } else if is_boss {
+                    state.ctx.set_font("56px 'Noto Serif SC', 'SimSun', serif");
+                }
+                state.ctx.set_global_alpha(fade.alpha(now));
+                let sway = if state.wobble {
+                    // Per-cell phase so neighbours do not sway in lockstep.
+                    wobble_offset(now - fade.spawn_ms, idx as f64 * 2.399)
+                } else {
+                    0.0
+                };
+                let cx = x as f64 * cell_w + cell_w / 2.0 + sway;
+                let cy = y as f64 * cell_h + cell_h / 2.0 + 8.0; // small vertical offset
+                state.ctx.set_line_width(6.0);
+                state
+                    .ctx
+                    .set_stroke_style_str(&palette::rgba(pal.outline, 0.85));
+                state.ctx.stroke_text(hanzi, cx, cy).ok();
+                // crisp fill
+                state.ctx.set_shadow_blur(0.0);
+                state.ctx.set_fill_style_str(&palette::hex(pal.glyph));
+                state.ctx.fill_text(hanzi, cx, cy).ok();
+                state.ctx.set_line_width(2.0);
+                state.ctx.set_stroke_style_str("rgba(255,210,120,0.55)");
+                state.ctx.stroke_text(hanzi, cx, cy).ok();
+                if state.show_tone_marks
+                    && let Some((_, pinyin)) = state.grid[idx]
+                {
+                    // One mark per syllable, spaced horizontally above the glyph.
+                    let marks = tone_marks(pinyin);
+                    let spacing = 16.0;
+                    let x0 = cx - spacing * (marks.len() as f64 - 1.0) / 2.0;
+                    state.ctx.set_font("bold 20px sans-serif");
+                    state.ctx.set_fill_style_str(&palette::hex(pal.accent));
+                    for (i, mark) in marks.iter().enumerate() {
+                        state
+                            .ctx
+                            .fill_text(mark, x0 + i as f64 * spacing, cy - 34.0)
+                            .ok();
+                    }
+                    state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+                }
+                if state.show_contours
+                    && let Some((_, pinyin)) = state.grid[idx]
+                {
+                    // One contour per syllable, side by side below the glyph.
+                    let tones: Vec<u8> = tones(pinyin).collect();
+                    let spacing = 22.0;
+                    let x0 = cx - spacing * (tones.len() as f64 - 1.0) / 2.0;
+                    for (i, &tone) in tones.iter().enumerate() {
+                        draw_tone_contour(&state.ctx, x0 + i as f64 * spacing, cy + 16.0, tone);
+                    }
+                }
+                if is_boss || state.reverse {
+                    state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+                }
+                // restore shadow for next glyph
+                state.ctx.set_shadow_blur(12.0);
+            }
+        }
+    }
+    state.ctx.set_global_alpha(1.0);
+
+    // Boss tile: pulsing crimson frame, one pip per remaining hit, and a white
+    // flash after a non-final hit.
+    if let Some(boss) = state.boss {
+        let w = state.level.width as usize;
+        let px = (boss.idx % w) as f64 * cell_w;
+        let py = (boss.idx / w) as f64 * cell_h;
+        let glow = 0.6 + 0.4 * (now / 180.0).sin();
+        state
+            .ctx
+            .set_stroke_style_str(&format!("rgba(220,40,90,{glow})"));
+        state.ctx.set_line_width(4.0);
+        state
+            .ctx
+            .stroke_rect(px + 3.0, py + 3.0, cell_w - 6.0, cell_h - 6.0);
+        state.ctx.set_fill_style_str("#ff4d79");
+        let left = BOSS_HITS - boss.hits_done.min(BOSS_HITS);
+        for i in 0..left {
+            state
+                .ctx
+                .fill_rect(px + 8.0 + i as f64 * 10.0, py + cell_h - 14.0, 6.0, 6.0);
+        }
+        if let Some(start) = boss.hit_flash_ms {
+            let age = now - start;
+            if age < BOSS_HIT_FLASH_MS {
+                let alpha = 0.6 * (1.0 - age / BOSS_HIT_FLASH_MS);
+                state
+                    .ctx
+                    .set_fill_style_str(&format!("rgba(255,255,255,{alpha})"));
+                state.ctx.fill_rect(px, py, cell_w, cell_h);
+            }
+        }
+    }
+
+    // Target brackets: corner marks pulsing around each adjacent tile the
+    // buffer is still a prefix of, so it is clear what Enter will hit.
+    let pulse_in = 4.0 + 3.0 * (now / 120.0).sin();
+    let arm = cell_w.min(cell_h) * 0.22;
+    state.ctx.set_stroke_style_str(&palette::hex(pal.accent));
+    state.ctx.set_line_width(3.0);
+    for (tx, ty, pinyin) in adjacent_tiles(state) {
+        if !is_typing_target(&state.typing, pinyin) {
+            continue;
+        }
+        let left = tx as f64 * cell_w + pulse_in;
+        let top = ty as f64 * cell_h + pulse_in;
+        let right = (tx + 1) as f64 * cell_w - pulse_in;
+        let bottom = (ty + 1) as f64 * cell_h - pulse_in;
+        for (cx, cy, sx, sy) in [
+            (left, top, 1.0, 1.0),
+            (right, top, -1.0, 1.0),
+            (right, bottom, -1.0, -1.0),
+            (left, bottom, 1.0, -1.0),
+        ] {
+            state.ctx.begin_path();
+            state.ctx.move_to(cx + sx * arm, cy);
+            state.ctx.line_to(cx, cy);
+            state.ctx.line_to(cx, cy + sy * arm);
+            state.ctx.stroke();
+        }
+    }
+
+    // Clear shadows after drawing text
+    state.ctx.set_shadow_blur(0.0);
+    state.ctx.set_shadow_offset_x(0.0);
+    state.ctx.set_shadow_offset_y(0.0);
+
+    // Compute the cat center (as before) and position the canonical DOM SVG (#hc-cat)
+    // over the canvas. We preserve the SVG's internal animation by moving the element
+    // instead of rasterizing it to the canvas.
+    let (cat_cx, cat_cy) = if state.cat_hopping {
+        let elapsed = now - state.cat_hop_start_ms;
+        let dur = if state.cat_hop_duration_ms <= 0.0 {
+            1.0
+        } else {
+            state.cat_hop_duration_ms
+        };
+        let t = (elapsed / dur).clamp(0.0, 1.0);
+        // ease-in-out-ish (simple quadratic ease)
+        let ease_t = 1.0 - (1.0 - t).powf(2.0);
+        let from_x = state.cat_from_x as f64;
+        let from_y = state.cat_from_y as f64;
+        let to_x = state.cat_target_x as f64;
+        let to_y = state.cat_target_y as f64;
+        let ix = from_x + (to_x - from_x) * ease_t;
+        let iy = from_y + (to_y - from_y) * ease_t;
+        // vertical arc for hop
+        let hop_h = (t * std::f64::consts::PI).sin() * 0.20 * cell_h;
+        (
+            ix * cell_w + cell_w / 2.0,
+            iy * cell_h + cell_h / 2.0 - hop_h,
+        )
+    } else {
+        (
+            state.cat_x as f64 * cell_w + cell_w / 2.0,
+            state.cat_y as f64 * cell_h + cell_h / 2.0,
+        )
+    };
+
+    // Position the DOM cat SVG (#hc-cat) over the canvas at the computed tile center.
+    // The canvas is positioned using fixed left/top + transform:translate(-50%,-50%).
+    // We'll place the cat with the same anchor and apply pixel offsets relative to
+    // the canvas center to avoid requiring additional web-sys features.
+    if let Some(win) = window()
+        && let Some(doc) = win.document()
+        && let Some(el) = doc.get_element_by_id("hc-cat")
+    {
+        let canvas_w = state.canvas.width() as f64;
+        let canvas_h = state.canvas.height() as f64;
+        // offset from canvas center in canvas pixels
+        let offset_x = cat_cx - (canvas_w / 2.0);
+        let offset_y = cat_cy - (canvas_h / 2.0);
+        // Use the same left/top anchor used for the canvas (50% / 38%) so the
+        // cat sits correctly above the canvas. We apply a translation that
+        // adjusts from the anchor by the computed offsets.
+        // Compute a square pixel size for the DOM cat so it fits within a
+        // single grid cell with some padding. Use the smaller of cell_w
+        // and cell_h to remain consistent across non-square boards.
+        // Teleport: shrink to nothing, then expand again on the destination tile.
+        let teleport_scale = state
+            .cat_teleport
+            .as_ref()
+            .map(|tp| {
+                let phase = ((now - tp.start_ms) / TELEPORT_MS).clamp(0.0, 1.0);
+                (1.0 - 2.0 * phase).abs()
+            })
+            .unwrap_or(1.0);
+        let cat_size = (cell_w.min(cell_h) * 0.75 * teleport_scale).round() as i32;
+        let style = format!(
+            "position:fixed; left:50%; top:38%; transform:translate(calc(-50% + {ox}px), calc(-50% + {oy}px)); pointer-events:none; z-index:40; width:{w}px; height:{h}px;",
+            ox = offset_x,
+            oy = offset_y,
+            w = cat_size,
+            h = cat_size
+        );
+        el.set_attribute("style", &style).ok();
+    }
+
+    // Slash effects (tile-space, same visual as before)
+    for eff in &state.slash_effects {
+        let age = now - eff.start_ms;
+        let alpha = 1.0 - (age / 300.0).clamp(0.0, 1.0);
+        if alpha <= 0.0 {
+            continue;
+        }
+        let px = eff.x as f64 * cell_w;
+        let py = eff.y as f64 * cell_h;
+        let inset = 6.0;
+        let left = px + inset;
+        let top = py + inset;
+        let right = px + cell_w - inset;
+        let bottom = py + cell_h - inset;
+        state.ctx.set_line_width(4.0);
+        state
+            .ctx
+            .set_stroke_style_str(&format!("rgba(255,80,80,{alpha})"));
+        for i in 0..3 {
+            let offset = i as f64 * 6.0;
+            state.ctx.begin_path();
+            state.ctx.move_to(left + offset, top);
+            state.ctx.line_to(right + offset - 18.0, bottom);
+            state.ctx.stroke();
+        }
+    }
+
+    // Life-loss cracks: jagged lines bursting from the tile centre, red for a
+    // lost life and blue when a shield took the hit.
+    for eff in &state.life_loss_effects {
+        let t = ((now - eff.start_ms) / LIFE_LOSS_FX_MS).clamp(0.0, 1.0);
+        let alpha = 1.0 - t;
+        let (r, g, b) = if eff.shielded {
+            (120, 200, 255)
+        } else {
+            (255, 60, 60)
+        };
+        let px = eff.x as f64 * cell_w;
+        let py = eff.y as f64 * cell_h;
+        state
+            .ctx
+            .set_fill_style_str(&format!("rgba({r},{g},{b},{})", alpha * 0.3));
+        state.ctx.fill_rect(px, py, cell_w, cell_h);
+        let cx = px + cell_w / 2.0;
+        let cy = py + cell_h / 2.0;
+        let reach = cell_w.min(cell_h) * (0.25 + 0.25 * t);
+        state.ctx.set_line_width(3.0);
+        state
+            .ctx
+            .set_stroke_style_str(&format!("rgba({r},{g},{b},{alpha})"));
+        for i in 0..6 {
+            let a = i as f64 * std::f64::consts::TAU / 6.0 + 0.3;
+            let kink = a + if i % 2 == 0 { 0.35 } else { -0.35 };
+            state.ctx.begin_path();
+            state.ctx.move_to(cx, cy);
+            state
+                .ctx
+                .line_to(cx + a.cos() * reach * 0.5, cy + a.sin() * reach * 0.5);
+            state
+                .ctx
+                .line_to(cx + kink.cos() * reach, cy + kink.sin() * reach);
+            state.ctx.stroke();
+        }
+    }
+
+    // Combo fireworks: a rising dot, then a ring of coloured sparks.
+    for fw in &state.fireworks {
+        let age = now - fw.start_ms;
+        if age < 0.0 {
+            continue;
+        }
+        let alpha = 1.0 - (age / FIREWORK_MS).clamp(0.0, 1.0);
+        let ox = fw.x * cell_w;
+        let oy = fw.y * cell_h;
+        let exploded = age >= FIREWORK_MS / 3.0;
+        let sparks = if exploded { FIREWORK_SPARKS } else { 1 };
+        for i in 0..sparks {
+            let (dx, dy) = firework_spark(i, age);
+            let hue = (fw.hue + i as u32 * 7) % 360;
+            state
+                .ctx
+                .set_fill_style_str(&format!("hsla({hue},95%,65%,{alpha})"));
+            state.ctx.begin_path();
+            state
+                .ctx
+                .arc(ox + dx, oy + dy, 3.0, 0.0, std::f64::consts::TAU)
+                .ok();
+            state.ctx.fill();
+        }
+    }
+
+    // "clear" powerup: a bright band sweeping left to right with sparkles
+    if let Some(start) = state.clear_sweep_ms {
+        let t = (now - start) / CLEAR_SWEEP_MS;
+        if t < 1.0 {
+            let w = state.canvas.width() as f64;
+            let h = state.canvas.height() as f64;
+            let band_x = t * (w + 120.0) - 60.0;
+            let alpha = 0.5 * (1.0 - t * 0.5);
+            state
+                .ctx
+                .set_fill_style_str(&format!("rgba(255,240,180,{alpha})"));
+            state.ctx.fill_rect(band_x - 30.0, 0.0, 60.0, h);
+            for i in 0..12 {
+                // deterministic scatter trailing the band
+                let sy = (i as f64 * 0.618).fract() * h;
+                let sx = band_x - 40.0 - (i as f64 * 0.382).fract() * 60.0;
+                state
+                    .ctx
+                    .set_fill_style_str(&format!("rgba(255,255,255,{})", alpha * 1.4));
+                state.ctx.fill_rect(sx, sy, 4.0, 4.0);
+            }
+        } else {
+            state.clear_sweep_ms = None;
+        }
+    }
+
+    // Red flash on the cat's tile after a missed submission
+    if let Some(start) = state.miss_flash_ms {
+        let age = now - start;
+        if age < MISS_FLASH_MS {
+            let alpha = 0.45 * (1.0 - age / MISS_FLASH_MS);
+            state
+                .ctx
+                .set_fill_style_str(&format!("rgba(255,60,60,{alpha})"));
+            state.ctx.fill_rect(
+                state.cat_x as f64 * cell_w,
+                state.cat_y as f64 * cell_h,
+                cell_w,
+                cell_h,
+            );
+        } else {
+            state.miss_flash_ms = None;
+        }
+    }
+
+    // Beat strip along the bottom edge: the on-beat window as a band, the
+    // exact beat as its bright core and a playhead sweeping through each beat.
+    {
+        let w = state.canvas.width() as f64;
+        let y = state.canvas.height() as f64 - JUDGE_STRIP_H;
+        let (band_l, band_r) = judge_band(state.beat_window_early, state.beat_window_late);
+        state.ctx.set_fill_style_str("rgba(0,0,0,0.35)");
+        state.ctx.fill_rect(0.0, y, w, JUDGE_STRIP_H);
+        state
+            .ctx
+            .set_fill_style_str(&palette::rgba(pal.accent, 0.35));
+        state
+            .ctx
+            .fill_rect(band_l * w, y, (band_r - band_l) * w, JUDGE_STRIP_H);
+        state
+            .ctx
+            .set_fill_style_str(&palette::rgba(pal.accent, 0.9));
+        state.ctx.fill_rect(w / 2.0 - 1.5, y, 3.0, JUDGE_STRIP_H);
+        let head = (0.5 + state.beat.beat_offset(now)) * w;
+        state.ctx.set_fill_style_str(&palette::hex(pal.glyph));
+        state.ctx.fill_rect(head - 1.0, y, 2.0, JUDGE_STRIP_H);
+    }
+
+    // "ON BEAT!" flash for rhythm-timed submissions
+    if let Some(start) = state.on_beat_flash_ms {
+        let age = now - start;
+        if age < ON_BEAT_FLASH_MS {
+            let alpha = 1.0 - age / ON_BEAT_FLASH_MS;
+            let cx = state.canvas.width() as f64 / 2.0;
+            let cy = 48.0 - age * 0.02;
+            state.ctx.set_font("bold 32px 'Fira Code', monospace");
+            state.ctx.set_text_align("center");
+            state.ctx.set_line_width(5.0);
+            state
+                .ctx
+                .set_stroke_style_str(&format!("rgba(0,0,0,{})", alpha * 0.8));
+            state.ctx.stroke_text("ON BEAT!", cx, cy).ok();
+            state
+                .ctx
+                .set_fill_style_str(&palette::rgba(pal.accent, alpha));
+            state.ctx.fill_text("ON BEAT!", cx, cy).ok();
+            state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+        } else {
+            state.on_beat_flash_ms = None;
+        }
+    }
+
+    // Level / tier banner, fading in and out
+    if let Some(banner) = &state.banner {
+        let t = (now - banner.start_ms) / BANNER_MS;
+        if t < 1.0 {
+            let alpha = banner_alpha(t);
+            let w = state.canvas.width() as f64;
+            let cy = state.canvas.height() as f64 / 2.0;
+            state
+                .ctx
+                .set_fill_style_str(&format!("rgba(0,0,0,{})", alpha * 0.6));
+            state.ctx.fill_rect(0.0, cy - 44.0, w, 80.0);
+            state.ctx.set_text_align("center");
+            state.ctx.set_font("bold 34px 'Fira Code', monospace");
+            state
+                .ctx
+                .set_fill_style_str(&palette::rgba(pal.accent, alpha));
+            state.ctx.fill_text(&banner.title, w / 2.0, cy).ok();
+            state.ctx.set_font("16px 'Fira Code', monospace");
+            state
+                .ctx
+                .set_fill_style_str(&format!("rgba(230,230,230,{alpha})"));
+            state
+                .ctx
+                .fill_text(&banner.subtitle, w / 2.0, cy + 26.0)
+                .ok();
+            state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+        } else {
+            state.banner = None;
+        }
+    }
+
+    // Combo counter (top-left) once a streak is going
+    if state.board_combo >= 2 {
+        let text = if now < state.speed_boost_until_ms {
+            format!("Combo: {}  BOOST!", state.board_combo)
+        } else {
+            format!("Combo: {}", state.board_combo)
+        };
+        state.ctx.set_font("bold 20px 'Fira Code', monospace");
+        state.ctx.set_text_align("left");
+        state.ctx.set_line_width(4.0);
+        state.ctx.set_stroke_style_str("rgba(0,0,0,0.8)");
+        state.ctx.stroke_text(&text, 14.0, 30.0).ok();
+        state.ctx.set_fill_style_str("#7ee787");
+        state.ctx.fill_text(&text, 14.0, 30.0).ok();
+        state.ctx.set_text_align("center");
+        state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+    }
+
+    // PAUSED overlay
+    if state.paused {
+        state.ctx.set_fill_style_str("rgba(0,0,0,0.45)");
+        state.ctx.fill_rect(
+            0.0,
+            0.0,
+            state.canvas.width() as f64,
+            state.canvas.height() as f64,
+        );
+        state.ctx.set_fill_style_str("#ffffff");
+        state.ctx.set_font("64px 'Fira Code', monospace");
+        state.ctx.set_text_align("center");
+        state.ctx.set_line_width(6.0);
+        state.ctx.set_stroke_style_str("#000000");
+        let cx = state.canvas.width() as f64 / 2.0;
+        let cy = state.canvas.height() as f64 / 2.0;
+        state.ctx.stroke_text("PAUSED", cx, cy).ok();
+        state.ctx.fill_text("PAUSED", cx, cy).ok();
+        state.ctx.set_font("40px 'Noto Serif SC', 'SimSun', serif");
+    }
+
+    // GAME OVER overlay (unchanged)
+    if state.game_over {
+        state.ctx.set_fill_style_str("rgba(0,0,0,0.55)");
+        state.ctx.fill_rect(
+            0.0,
+            0.0,
+            state.canvas.width() as f64,
+            state.canvas.height() as f64,
+        );
+        state.ctx.set_fill_style_str("#ffffff");
+        state.ctx.set_font("72px 'Noto Serif SC', serif");
+        state.ctx.set_text_align("center");
+        state.ctx.set_line_width(6.0);
+        state.ctx.set_stroke_style_str("#000000");
+        let cx = state.canvas.width() as f64 / 2.0;
+        let cy = state.canvas.height() as f64 / 2.0;
+        state.ctx.stroke_text("GAME OVER", cx, cy).ok();
+        state.ctx.fill_text("GAME OVER", cx, cy).ok();
+        state.ctx.set_font("20px 'Fira Code', monospace");
+        state
+            .ctx
+            .fill_text("Refresh to try again", cx, cy + 44.0)
+            .ok();
+    }
+
+    // YOU WIN overlay (mirrors GAME OVER)
+    if state.won {
+        state.ctx.set_fill_style_str("rgba(0,0,0,0.55)");
+        state.ctx.fill_rect(
+            0.0,
+            0.0,
+            state.canvas.width() as f64,
+            state.canvas.height() as f64,
+        );
+        state.ctx.set_fill_style_str("#ffd166");
+        state.ctx.set_font("72px 'Noto Serif SC', serif");
+        state.ctx.set_text_align("center");
+        state.ctx.set_line_width(6.0);
+        state.ctx.set_stroke_style_str("#000000");
+        let cx = state.canvas.width() as f64 / 2.0;
+        let cy = state.canvas.height() as f64 / 2.0;
+        state.ctx.stroke_text("YOU WIN", cx, cy).ok();
+        state.ctx.fill_text("YOU WIN", cx, cy).ok();
+        state.ctx.set_fill_style_str("#ffffff");
+        state.ctx.set_font("20px 'Fira Code', monospace");
+        state
+            .ctx
+            .fill_text(
+                &format!("Final score: {}", format_score(state.score, state.locale)),
+                cx,
+                cy + 44.0,
+            )
+            .ok();
+    }
+}
+
+fn draw_obstacle(
+    ctx: &CanvasRenderingContext2d,
+    obs: &ObstacleKind,
+    x: u8,
+    y: u8,
+    cw: f64,
+    ch: f64,
+    keys: &[u8],
+) {
+    let px = x as f64 * cw;
+    let py = y as f64 * ch;
+    match *obs {
+        ObstacleKind::Block => {
+            // Solid block with subtle inner X pattern
+            ctx.set_fill_style_str("#552222");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_stroke_style_str("rgba(255,200,200,0.15)");
+            ctx.set_line_width(3.0);
+            ctx.begin_path();
+            ctx.move_to(px + 4.0, py + 4.0);
+            ctx.line_to(px + cw - 4.0, py + ch - 4.0);
+            ctx.move_to(px + cw - 4.0, py + 4.0);
+            ctx.line_to(px + 4.0, py + ch - 4.0);
+            ctx.stroke();
+        }
+        ObstacleKind::Teleport { .. } => {
+            // Portal: ring + inner glow square
+            ctx.set_stroke_style_str("#4aa3ff");
+            ctx.set_line_width(4.0);
+            ctx.begin_path();
+            let cx = px + cw / 2.0;
+            let cy = py + ch / 2.0;
+            let r = (cw.min(ch)) * 0.33;
+            ctx.arc(cx, cy, r, 0.0, std::f64::consts::TAU).ok();
+            ctx.stroke();
+            ctx.set_fill_style_str("rgba(70,140,255,0.25)");
+            let side = r * 1.1;
+            ctx.fill_rect(cx - side / 2.0, cy - side / 2.0, side, side);
+        }
+        ObstacleKind::Conveyor { dx, dy } => {
+            // Belt: darker base + directional chevrons
+            ctx.set_fill_style_str("#334433");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_fill_style_str("#88cc88");
+            // Draw 3 small chevrons along movement axis
+            let chevrons = 3;
+            for i in 0..chevrons {
+                let t = (i as f64 + 0.5) / chevrons as f64;
+                let (cx, cy) = (px + 2.0 + (cw - 4.0) * t, py + 2.0 + (ch - 4.0) * t);
+                ctx.begin_path();
+                let size = 6.0;
+                match (dx, dy) {
+                    (1, 0) => {
+                        // right
+                        ctx.move_to(cx - size, py + ch / 2.0 - size * 0.8);
+                        ctx.line_to(cx, py + ch / 2.0);
+                        ctx.line_to(cx - size, py + ch / 2.0 + size * 0.8);
+                    }
+                    (-1, 0) => {
+                        // left
+                        ctx.move_to(cx + size, py + ch / 2.0 - size * 0.8);
+                        ctx.line_to(cx, py + ch / 2.0);
+                        ctx.line_to(cx + size, py + ch / 2.0 + size * 0.8);
+                    }
+                    (0, 1) => {
+                        // down
+                        ctx.move_to(px + cw / 2.0 - size * 0.8, cy - size);
+                        ctx.line_to(px + cw / 2.0, cy);
+                        ctx.line_to(px + cw / 2.0 + size * 0.8, cy - size);
+                    }
+                    (0, -1) => {
+                        // up
+                        ctx.move_to(px + cw / 2.0 - size * 0.8, cy + size);
+                        ctx.line_to(px + cw / 2.0, cy);
+                        ctx.line_to(px + cw / 2.0 + size * 0.8, cy + size);
+                    }
+                    _ => {}
+                }
+                ctx.fill();
+            }
+        }
+        ObstacleKind::TempoShift { .. } => {
+            // Metronome tile: base + swinging arm representation
+            ctx.set_fill_style_str("#444455");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_stroke_style_str("#b0b0ff");
+            ctx.set_line_width(3.0);
+            let base_w = cw * 0.28;
+            let base_x = px + cw / 2.0 - base_w / 2.0;
+            let base_y = py + ch * 0.68;
+            ctx.begin_path();
+            ctx.move_to(base_x, base_y);
+            ctx.line_to(base_x + base_w, base_y);
+            ctx.stroke();
+            // Arm
+            ctx.begin_path();
+            ctx.move_to(px + cw / 2.0, base_y);
+            ctx.line_to(px + cw / 2.0 + cw * 0.18, py + ch * 0.30);
+            ctx.stroke();
+        }
+        ObstacleKind::Ice => {
+            // Ice: slippery pale tile with a snowflake-like symbol
+            ctx.set_fill_style_str("#224466");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_stroke_style_str("rgba(200,230,255,0.9)");
+            ctx.set_line_width(2.0);
+            // simple snowflake lines
+            let cx = px + cw / 2.0;
+            let cy = py + ch / 2.0;
+            let r = (cw.min(ch)) * 0.18;
+            ctx.begin_path();
+            ctx.move_to(cx - r, cy);
+            ctx.line_to(cx + r, cy);
+            ctx.move_to(cx, cy - r);
+            ctx.line_to(cx, cy + r);
+            ctx.move_to(cx - r * 0.7, cy - r * 0.7);
+            ctx.line_to(cx + r * 0.7, cy + r * 0.7);
+            ctx.move_to(cx - r * 0.7, cy + r * 0.7);
+            ctx.line_to(cx + r * 0.7, cy - r * 0.7);
+            ctx.stroke();
+        }
+        ObstacleKind::JumpPad { dx, dy, strength } => {
+            // JumpPad: bright pad with arrow showing direction and a strength number
+            ctx.set_fill_style_str("#554488");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_fill_style_str("#ffdd88");
+            // arrow center
+            let cx = px + cw / 2.0;
+            let cy = py + ch / 2.0;
+            let size = (cw.min(ch)) * 0.18;
+            ctx.begin_path();
+            match (dx, dy) {
+                (1, 0) => {
+                    ctx.move_to(cx - size, cy - size);
+                    ctx.line_to(cx + size, cy);
+                    ctx.line_to(cx - size, cy + size);
+                }
+                (-1, 0) => {
+                    ctx.move_to(cx + size, cy - size);
+                    ctx.line_to(cx - size, cy);
+                    ctx.line_to(cx + size, cy + size);
+                }
+                (0, 1) => {
+                    ctx.move_to(cx - size, cy - size);
+                    ctx.line_to(cx, cy + size);
+                    ctx.line_to(cx + size, cy - size);
+                }
+                (0, -1) => {
+                    ctx.move_to(cx - size, cy + size);
+                    ctx.line_to(cx, cy - size);
+                    ctx.line_to(cx + size, cy + size);
+                }
+                _ => {
+                    // no direction: draw a burst
+                    ctx.arc(cx, cy, size, 0.0, std::f64::consts::TAU).ok();
+                }
+            }
+            ctx.fill();
+            // strength number in corner
+            ctx.set_fill_style_str("#ffffff");
+            ctx.set_font("12px 'Fira Code', monospace");
+            ctx.fill_text(&format!("{}", strength), px + cw - 14.0, py + ch - 8.0)
+                .ok();
+        }
+        ObstacleKind::Transform => {
+            // Transform tile: gradient-like base + double arrow
+            ctx.set_fill_style_str("#333355");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_stroke_style_str("#aac");
+            ctx.set_line_width(3.0);
+            ctx.begin_path();
+            let mid_y = py + ch / 2.0;
+            ctx.move_to(px + cw * 0.25, mid_y - 6.0);
+            ctx.line_to(px + cw * 0.75, mid_y - 6.0);
+            ctx.move_to(px + cw * 0.75 - 8.0, mid_y - 12.0);
+            ctx.line_to(px + cw * 0.75, mid_y - 6.0);
+            ctx.line_to(px + cw * 0.75 - 8.0, mid_y);
+            ctx.move_to(px + cw * 0.75, mid_y + 6.0);
+            ctx.line_to(px + cw * 0.25, mid_y + 6.0);
+            ctx.move_to(px + cw * 0.25 + 8.0, mid_y);
+            ctx.line_to(px + cw * 0.25, mid_y + 6.0);
+            ctx.line_to(px + cw * 0.25 + 8.0, mid_y + 12.0);
+            ctx.stroke();
+        }
+        ObstacleKind::Key { id } => {
+            // Key: small yellow bow + shaft, faded once collected
+            let collected = keys.contains(&id);
+            ctx.set_fill_style_str(if collected {
+                "rgba(255,209,102,0.25)"
+            } else {
+                "#ffd166"
+            });
+            ctx.set_stroke_style_str(if collected {
+                "rgba(255,209,102,0.25)"
+            } else {
+                "#ffd166"
+            });
+            let cx = px + cw * 0.35;
+            let cy = py + ch * 0.35;
+            let r = cw.min(ch) * 0.10;
+            ctx.set_line_width(3.0);
+            ctx.begin_path();
+            ctx.arc(cx, cy, r, 0.0, std::f64::consts::TAU).ok();
+            ctx.stroke();
+            ctx.begin_path();
+            ctx.move_to(cx + r * 0.7, cy + r * 0.7);
+            ctx.line_to(px + cw * 0.72, py + ch * 0.72);
+            ctx.stroke();
+            ctx.fill_rect(px + cw * 0.60, py + ch * 0.62, 4.0, 6.0);
+        }
+        ObstacleKind::Door { id } => {
+            if keys.contains(&id) {
+                // Unlocked: open frame only
+                ctx.set_stroke_style_str("rgba(180,140,90,0.7)");
+                ctx.set_line_width(3.0);
+                ctx.stroke_rect(px + 4.0, py + 4.0, cw - 8.0, ch - 8.0);
+            } else {
+                // Locked: wooden panel with vertical bars
+                ctx.set_fill_style_str("#5a3d22");
+                ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+                ctx.set_stroke_style_str("#c9a36b");
+                ctx.set_line_width(3.0);
+                ctx.begin_path();
+                for i in 1..4 {
+                    let bx = px + cw * i as f64 / 4.0;
+                    ctx.move_to(bx, py + 4.0);
+                    ctx.line_to(bx, py + ch - 4.0);
+                }
+                ctx.stroke();
+            }
+        }
+        ObstacleKind::MovingBlock { .. } => { /* drawn by render_board at its current position */ }
+        ObstacleKind::Spike => {
+            // Spike: dark base with a row of red triangles
+            ctx.set_fill_style_str("#2a1414");
+            ctx.fill_rect(px + 2.0, py + 2.0, cw - 4.0, ch - 4.0);
+            ctx.set_fill_style_str("#e03c3c");
+            let spikes = 3;
+            let base_y = py + ch * 0.78;
+            let tip_y = py + ch * 0.30;
+            let span = (cw - 12.0) / spikes as f64;
+            for i in 0..spikes {
+                let left = px + 6.0 + span * i as f64;
+                ctx.begin_path();
+                ctx.move_to(left, base_y);
+                ctx.line_to(left + span / 2.0, tip_y);
+                ctx.line_to(left + span, base_y);
+                ctx.close_path();
+                ctx.fill();
+            }
+        }
+    }
+}
+
+/// Apply the effects of the tile a piece just arrived on. Returns `false` when
+/// the piece is destroyed (e.g. by a spike) and should be removed.
+#[allow(dead_code)]
+fn apply_tile_effects(
+    piece: &mut Piece,
+    state: &mut BoardState,
+    current_beat: i64,
+    _now: f64,
+) -> bool {
+    let tile = state.level.tile(piece.x, piece.y);
+    // Obstacles with post-arrival effects
+    if let Some(obs) = &tile.obstacle {
+        match obs {
+            ObstacleKind::Teleport { to: (tx, ty) } => {
+                piece.x = *tx;
+                piece.y = *ty; // instant relocate
+            }
+            ObstacleKind::Conveyor { dx, dy } => {
+                let nx = piece.x as i8 + *dx;
+                let ny = piece.y as i8 + *dy;
+                if nx >= 0
+                    && ny >= 0
+                    && (nx as u8) < state.level.width
+                    && (ny as u8) < state.level.height
+                {
+                    let nxu = nx as u8;
+                    let nyu = ny as u8;
+                    if !tile_blocked(
+                        state.level,
+                        nxu,
+                        nyu,
+                        &state.collected_keys,
+                        state.beat.last_beat_idx,
+                    ) {
+                        // Queue immediate hop (small duration)
+                        piece.begin_hop(nxu, nyu, _now, piece.hop_duration_ms * 0.8);
+                    }
+                }
+            }
+            ObstacleKind::TempoShift { mult, beats } => {
+                state.hop_time_factor *= 1.0 / mult; // faster tempo => shorter hops
+                state.hop_time_end_beat = current_beat + *beats as i64;
+            }
+            ObstacleKind::Ice => {
+                // If the piece has a known direction, enable sliding momentum.
+                if piece.dir_dx == 0 && piece.dir_dy == 0 {
+                    // choose a greedy direction toward goal so the piece will slide
+                    if let Some((nx, ny)) = choose_next_step(
+                        state.level,
+                        piece.x,
+                        piece.y,
+                        &state.collected_keys,
+                        state.beat.last_beat_idx,
+                    ) {
+                        piece.dir_dx = (nx as i8 - piece.x as i8).signum();
+                        piece.dir_dy = (ny as i8 - piece.y as i8).signum();
+                    }
+                }
+                piece.momentum = 1;
+            }
+            ObstacleKind::JumpPad { dx, dy, strength } => {
+                // Launch the piece `strength` tiles in the given direction,
+                // or toward the first goal if dx/dy == 0.
+                let mut ldx = *dx;
+                let mut ldy = *dy;
+                if ldx == 0 && ldy == 0 {
+                    // pick direction toward nearest goal
+                    if let Some(&(gx, gy)) = state.level.goal_region.first() {
+                        ldx = (gx as i8 - piece.x as i8).signum();
+                        ldy = (gy as i8 - piece.y as i8).signum();
+                    }
+                }
+                let mut tx = piece.x;
+                let mut ty = piece.y;
+                for _ in 0..*strength {
+                    let Some((nx, ny)) = step_target(
+                        state.level,
+                        tx,
+                        ty,
+                        ldx,
+                        ldy,
+                        &state.collected_keys,
+                        state.beat.last_beat_idx,
+                    ) else {
+                        break;
+                    };
+                    tx = nx;
+                    ty = ny;
+                }
+                // Queue a faster hop to the landing tile
+                piece.begin_hop(tx, ty, _now, piece.hop_duration_ms * 0.6);
+                piece.momentum = 0; // jump breaks sliding momentum
+            }
+            ObstacleKind::Block => { /* cannot stand here normally (shouldn't happen) */ }
+            ObstacleKind::Transform => { /* handled via modifier if present */ }
+            ObstacleKind::Spike => return false,
+            ObstacleKind::Key { .. } | ObstacleKind::Door { .. } => { /* keys are cat-only */ }
+            ObstacleKind::MovingBlock { .. } => { /* anchor tile only; see moving_block_at */ }
+        }
+    }
+    if let Some(modf) = &tile.modifier {
+        match modf {
+            ModifierKind::ScoreMult { factor, beats } => {
+                state.score_multiplier *= *factor;
+                state.score_mult_end_beat = current_beat + *beats as i64;
+            }
+            ModifierKind::SlowHop { factor, beats } => {
+                state.hop_time_factor *= *factor;
+                state.hop_time_end_beat = current_beat + *beats as i64;
+            }
+            ModifierKind::TransformMap { pairs } => {
+                for (from, to) in *pairs {
+                    if piece.hanzi == *from {
+                        piece.hanzi = to; // keep pinyin placeholder; future: pinyin map
+                        break;
+                    }
+                }
+            }
+        }
+    }
+    true
+}
+
+fn expire_effects(state: &mut BoardState, current_beat: i64) {
+    if state.score_mult_end_beat >= 0 && current_beat >= state.score_mult_end_beat {
+        state.score_multiplier = 1.0;
+        state.score_mult_end_beat = -1;
+    }
+    if state.hop_time_end_beat >= 0 && current_beat >= state.hop_time_end_beat {
+        state.hop_time_factor = 1.0;
+        state.hop_time_end_beat = -1;
+    }
+    if state.board_combo > 0 && current_beat - state.combo_last_beat > COMBO_TIMEOUT_BEATS {
+        state.board_combo = 0;
+        state.speed_boost_until_ms = 0.0;
+    }
+}
+
+fn check_level_progression(state: &mut BoardState, now: f64, current_beat: i64) {
+    // If next level exists and score threshold reached, advance.
+    if let Some(threshold) = next_level_threshold(state.level_index) {
+        if state.score >= threshold {
+            level_select::mark_level_cleared(state.level_index);
+            set_level(state, state.level_index + 1, now, current_beat);
+            show_banner(
+                state,
+                format!("Level {}", state.level_index + 1),
+                state.level.name.to_string(),
+                now,
+            );
+            hooks::level_up(state.level_index + 1, state.level.name);
+        }
+    } else if !state.won && state.score >= FINAL_LEVEL_CLEAR_SCORE {
+        // Final level cleared
+        level_select::mark_level_cleared(state.level_index);
+        state.won = true;
+    }
+}
+
+/// Score threshold to advance past `level_index`, or `None` when there is no
+/// further level (either no next level or no threshold configured for it).
+fn next_level_threshold(level_index: usize) -> Option<i64> {
+    let next_idx = level_index + 1;
+    if next_idx >= levels().len() {
+        return None;
+    }
+    LEVEL_SCORE_THRESHOLDS.get(next_idx).copied()
+}
+
+fn set_level(state: &mut BoardState, new_index: usize, now: f64, _current_beat: i64) {
+    // Switch to the new level descriptor and reinitialize dynamic per-level state.
+    state.level_index = new_index;
+    state.level = levels()[new_index];
+    state.collected_keys.clear();
+
+    // Rebuild the grid for the new level. Block tiles remain None; pinned tiles
+    // get their fixed content, others a random hanzi/pinyin for the level.
+    let lvl = state.level;
+    state.boss = None;
+    state.grid.clear();
+    state.grid.reserve(lvl.width as usize * lvl.height as usize);
+    for yy in 0..lvl.height {
+        for xx in 0..lvl.width {
+            if tile_blocked(lvl, xx, yy, &[], 0) {
+                state.grid.push(None);
+            } else if let Some(fixed) = lvl.tile(xx, yy).fixed_hanzi {
+                state.grid.push(Some(fixed));
+            } else {
+                let (h, p) = pick_random_hanzi(lvl);
+                state.grid.push(Some((h, p)));
+            }
+        }
+    }
+    state.tile_spawn_ms = vec![now; state.grid.len()];
+
+    // Position the cat near the center or on the first non-block tile found.
+    let mut cx = lvl.width / 2;
+    let mut cy = lvl.height / 2;
+    if matches!(lvl.tile(cx, cy).obstacle, Some(ObstacleKind::Block)) {
+        'search_free: for yy in 0..lvl.height {
+            for xx in 0..lvl.width {
+                if !matches!(lvl.tile(xx, yy).obstacle, Some(ObstacleKind::Block)) {
+                    cx = xx;
+                    cy = yy;
+                    break 'search_free;
+                }
+            }
+        }
+    }
+    state.cat_x = cx;
+    state.cat_y = cy;
+    // Reset hop animation so cat remains consistent with new level
+    state.cat_from_x = state.cat_x;
+    state.cat_from_y = state.cat_y;
+    state.cat_target_x = state.cat_x;
+    state.cat_target_y = state.cat_y;
+    state.cat_hop_start_ms = now;
+    state.cat_hop_duration_ms = 220.0;
+    state.cat_hopping = false;
+    state.cat_auto_hops = 0;
+    state.queued_hop = None;
+    state.cat_teleport = None;
+
+    // Ensure player's tile is empty and neighbors are uniquely populated for level 0.
+    refresh_cat_neighbors(state);
+
+    // Reset beat clock to the new level's BPM (this also ends a "slow" powerup;
+    // the adaptive factor carries over)
+    state.beat = BeatClock::new(state.level.bpm, now);
+    state.slow_until_ms = 0.0;
+    apply_tempo(state, now);
+
+    // Reset temporary modifiers
+    state.hop_time_factor = 1.0;
+    state.hop_time_end_beat = -1;
+    state.score_multiplier = 1.0;
+    state.score_mult_end_beat = -1;
+    // The beat clock restarted; keep the combo but restart its timer.
+    state.combo_last_beat = 0;
+}
+
+/// Keep the cat's tile empty and, on the first level, repopulate up to 8 neighbor
+/// tiles with distinct hanzi from its pool; remaining empty tiles are filled
+/// with an alternating two-character parity pattern.
+fn refresh_cat_neighbors(state: &mut BoardState) {
+    let lvl = state.level;
+    let w = lvl.width as usize;
+    let h = lvl.height as usize;
+    let cx = state.cat_x as i32;
+    let cy = state.cat_y as i32;
+
+    // Collect neighbor indices (8-connected)
+    let mut neighbors: Vec<usize> = Vec::new();
+    for dy in -1..=1 {
+        for dx in -1..=1 {
+            if dx == 0 && dy == 0 {
+                continue;
+            }
+            let Some((nxu, nyu)) = lvl.offset(state.cat_x, state.cat_y, dx, dy) else {
+                continue;
+            };
+            if tile_blocked(
+                lvl,
+                nxu,
+                nyu,
+                &state.collected_keys,
+                state.beat.last_beat_idx,
+            ) || lvl.tile(nxu, nyu).fixed_hanzi.is_some()
+            {
+                continue;
+            }
+            let nidx = nyu as usize * w + nxu as usize;
+            // tiny wrapping boards can reach the same tile twice
+            if !neighbors.contains(&nidx) {
+                neighbors.push(nidx);
+            }
+        }
+    }
+
+    // Clear player's tile
+    if cx >= 0 && cy >= 0 && (cx as u8) < lvl.width && (cy as u8) < lvl.height {
+        let cat_idx = cy as usize * w + cx as usize;
+        if cat_idx < state.grid.len() {
+            state.grid[cat_idx] = None;
+        }
+    }
+
+    if state.level_index == 0 && !neighbors.is_empty() {
+        let pool = vocab::filtered_pool(&level_hanzi_pool(lvl));
+        let pool_len = pool.len();
+        if pool_len > 0 {
+            let mut selected: Vec<(&'static str, &'static str)> = Vec::new();
+            let mut start = rand_index(pool_len);
+            while selected.len() < neighbors.len() + 2 && selected.len() < pool_len {
+                let cand = pool[start % pool_len];
+                if !selected.iter().any(|(h, _)| *h == cand.0) {
+                    selected.push(cand);
+                }
+                start = (start + 1) % pool_len;
+            }
+
+            for (i, &idx) in neighbors.iter().enumerate() {
+                if i < selected.len() {
+                    state.grid[idx] = Some(selected[i]);
+                } else {
+                    let (h, p) = pick_random_hanzi(lvl);
+                    state.grid[idx] = Some((h, p));
+                }
+            }
+
+            let (pat0, pat1) = if selected.len() >= neighbors.len() + 2 {
+                (selected[neighbors.len()], selected[neighbors.len() + 1])
+            } else {
+                (pool[0], pool[1 % pool_len])
+            };
+
+            for y in 0..h {
+                for x in 0..w {
+                    let idx = y * w + x;
+                    // Do not fill the player's tile (cat) so it remains empty.
+                    if x == state.cat_x as usize && y == state.cat_y as usize {
+                        continue;
+                    }
+                    if state.grid[idx].is_none()
+                        && lvl.tiles[idx].fixed_hanzi.is_none()
+                        && !tile_blocked(
+                            lvl,
+                            x as u8,
+                            y as u8,
+                            &state.collected_keys,
+                            state.beat.last_beat_idx,
+                        )
+                    {
+                        let parity = (x + y) % 2;
+                        state.grid[idx] = Some(if parity == 0 { pat0 } else { pat1 });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Make tile refills deterministic: from the next run start on, `rand_index`
+/// draws from a stream seeded with `seed`. Replays rely on this.
+#[wasm_bindgen]
+pub fn seed_board_rng(seed: u64) {
+    RNG_SEED.with(|s| s.set(Some(seed)));
+}
+
+/// Seed of the daily challenge for `date_seed` (e.g. 20261016), so every
+/// player gets the same stream that day without it matching a plain
+/// `seed_board_rng(date_seed)` run.
+fn daily_seed(date_seed: u64) -> u64 {
+    let mut st = date_seed ^ 0x6461_696C_795F_6863; // "daily_hc"
+    splitmix64(&mut st)
+}
+
+/// Start the daily challenge: level 1 with tile refills seeded from
+/// `date_seed`, so everyone playing with the same date gets the same board.
+/// Only this run is seeded; "Play Again" and later runs use the previous
+/// `seed_board_rng` setting.
+#[wasm_bindgen]
+pub fn start_daily(date_seed: u64) -> Result<(), JsValue> {
+    let previous = RNG_SEED.with(|s| s.replace(Some(daily_seed(date_seed))));
+    let started = start_board_mode_at(0);
+    RNG_SEED.with(|s| s.set(previous));
+    started?;
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.daily = true;
+        }
+    });
+    Ok(())
+}
+
+/// Start a zen run on level 1: misses still break the combo and count in the
+/// stats, but no life is ever lost, so play only ends by clearing the final
+/// level. The hearts are hidden meanwhile.
+#[wasm_bindgen]
+pub fn start_zen() -> Result<(), JsValue> {
+    start_board_mode_at(0)?;
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.zen = true;
+        }
+    });
+    Ok(())
+}
+
+/// Restart the seeded stream (if any) for a new run.
+fn reseed_rng() {
+    RNG_STATE.with(|st| st.set(RNG_SEED.with(|s| s.get())));
+}
+
+/// SplitMix64 step: advances `state` and returns the next output.
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+fn rand_index(len: usize) -> usize {
+    if len == 0 {
+        return 0;
+    }
+    if let Some(mut st) = RNG_STATE.with(|s| s.get()) {
+        let r = splitmix64(&mut st);
+        RNG_STATE.with(|s| s.set(Some(st)));
+        return (r % len as u64) as usize;
+    }
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    // Simple linear transform and modulus for prototype randomness (not crypto secure)
+    (now as u64 as usize)
+        .wrapping_mul(1664525)
+        .wrapping_add(1013904223)
+        % len
+}
+
+/// Uniform draw in `[0, 1)` from the same source as `rand_index`.
+fn rand_unit() -> f64 {
+    const SCALE: usize = 1 << 24;
+    rand_index(SCALE) as f64 / SCALE as f64
+}
+
+/// Index into `weights` chosen proportionally to its weight, for `r` in
+/// `[0, 1)` (cumulative-weight sampling). All-zero weights fall back to
+/// uniform.
+fn weighted_index(weights: &[u32], r: f64) -> usize {
+    let last = weights.len().saturating_sub(1);
+    let total: u64 = weights.iter().map(|&w| w as u64).sum();
+    if total == 0 {
+        return ((r * weights.len() as f64) as usize).min(last);
+    }
+    let mut target = r * total as f64;
+    for (i, &w) in weights.iter().enumerate() {
+        if target < w as f64 {
+            return i;
+        }
+        target -= w as f64;
+    }
+    last
+}
+
+/// Hanzi / pinyin pool the given level draws its tiles from (the opening board
+/// and unknown levels use `SINGLE_HANZI`). A runtime dataset (`set_dataset`)
+/// replaces the pool of every level, entries from `add_hanzi` are added to
+/// each level's pool, and a practice session (`start_practice`) replaces all
+/// of them.
+fn level_hanzi_pool(level: &LevelDesc) -> Vec<(&'static str, &'static str)> {
+    if let Some(pool) = practice::pool() {
+        return pool.to_vec();
+    }
+    let base = dataset::active().unwrap_or(match level.name {
+        "Conveyor Crossing" => &LEVEL2_HANZI,
+        "Zigzag Express" => &LEVEL4_HANZI,
+        "Maze Challenge" => &LEVEL3_HANZI,
+        "Spiral Dream" => &LEVEL5_HANZI,
+        "Crystal Isle" => LEVEL6_HANZI,
+        "Neon Bastion" => LEVEL7_HANZI,
+        _ => crate::SINGLE_HANZI,
+    });
+    dataset::with_extras(base)
+}
+
+/// Pick a random hanzi / pinyin tuple appropriate for the given level.
+/// Centralizes the per-level selection logic used in multiple places.
+/// Honors the spawn filter (`set_categories`, `set_hsk_range`) and samples the
+/// remaining pool by frequency weight. Practice sessions skip mastered hanzi.
+fn pick_random_hanzi(level: &LevelDesc) -> (&'static str, &'static str) {
+    let pool = practice::narrow(vocab::filtered_pool(&level_hanzi_pool(level)));
+    if pool.is_empty() {
+        ("你", "ni3")
+    } else {
+        let weights: Vec<u32> = pool.iter().map(|(h, _)| vocab::weight(h)).collect();
+        pool[weighted_index(&weights, rand_unit())]
+    }
+}
+
+/// Decide next step for a piece taking into account momentum (ice), jump pads, and
+/// simple heuristics. Returns the next tile to hop to if any.
+#[allow(dead_code)]
+fn choose_next_for_piece(level: &LevelDesc, p: &Piece, keys: &[u8], beat: i64) -> Option<(u8, u8)> {
+    let x = p.x;
+    let y = p.y;
+
+    // If we have momentum, attempt to continue in that direction; when blocked,
+    // drop momentum and fall through to normal logic.
+    if p.momentum > 0
+        && (p.dir_dx != 0 || p.dir_dy != 0)
+        && let Some(next) = step_target(level, x, y, p.dir_dx, p.dir_dy, keys, beat)
+    {
+        return Some(next);
+    }
+
+    // Prefer moving onto an adjacent JumpPad if present (it will launch the piece)
+    let dirs: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    for (dx, dy) in dirs {
+        let Some((nx, ny)) = level.offset(x, y, dx, dy) else {
+            continue;
+        };
+        let tile = level.tile(nx, ny);
+        if let Some(ObstacleKind::JumpPad { .. }) = tile.obstacle {
+            return Some((nx, ny));
+        }
+    }
+
+    // Fallback to greedy nearest-goal step
+    choose_next_step(level, x, y, keys, beat)
+}
+
+#[allow(dead_code)]
+fn choose_next_step(level: &LevelDesc, x: u8, y: u8, keys: &[u8], beat: i64) -> Option<(u8, u8)> {
+    // Greedy: pick neighbor (4-dir) that reduces Manhattan distance to ANY goal tile and is not blocked.
+    let dirs: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+    let mut best: Option<((u8, u8), i32)> = None;
+    // Per-axis distance, measured the short way around on wrapping levels.
+    let axis = |a: u8, b: u8, len: u8| -> i32 {
+        let d = (a as i32 - b as i32).abs();
+        if level.wrap { d.min(len as i32 - d) } else { d }
+    };
+    let goal_dist = |px: u8, py: u8| -> Option<i32> {
+        level
+            .goal_region
+            .iter()
+            .map(|&(gx, gy)| axis(gx, px, level.width) + axis(gy, py, level.height))
+            .min()
+    };
+    let cur_best_dist = goal_dist(x, y).unwrap_or(0);
+    for (dx, dy) in dirs {
+        let Some((nxu, nyu)) = level.offset(x, y, dx, dy) else {
+            continue;
+        };
+        // skip blocked (incl. locked doors)
+        if tile_blocked(level, nxu, nyu, keys, beat) {
+            continue;
+        }
+        let nd = goal_dist(nxu, nyu).unwrap_or(i32::MAX);
+        if nd <= cur_best_dist {
+            // allow equal to avoid deadlock
+            if let Some((_, bestd)) = &best
+                && nd > *bestd
+            {
+                continue;
+            }
+            best = Some(((nxu, nyu), nd));
+        }
+    }
+    best.map(|(pos, _)| pos)
+}
+
+/// Supported formatting language for a BCP 47-ish tag (`"fr-CA"` -> `"fr"`).
+fn normalize_lang(lang: &str) -> &'static str {
+    match lang.split(['-', '_']).next().unwrap_or("") {
+        "de" => "de",
+        "fr" => "fr",
+        _ => "en",
+    }
+}
+
+/// `n` with thousands separators for `lang`: `12,000` (en), `12.000` (de) or
+/// `12 000` (fr, no-break space).
+fn format_score(n: i64, lang: &str) -> String {
+    let sep = match normalize_lang(lang) {
+        "de" => '.',
+        "fr" => '\u{a0}',
+        _ => ',',
+    };
+    let digits = n.unsigned_abs().to_string();
+    let mut out = String::with_capacity(digits.len() + digits.len() / 3 + 1);
+    if n < 0 {
+        out.push('-');
+    }
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(sep);
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Horizontal glyph offset `age_ms` after a tile filled, for wobble `phase`.
+fn wobble_offset(age_ms: f64, phase: f64) -> f64 {
+    (age_ms * WOBBLE_FREQ + phase).sin() * WOBBLE_AMPLITUDE
+}
+
+/// Unit-box polyline of a tone's pitch contour: x in 0..=1 left to right, y in
+/// 0..=1 from low to high pitch. Tone 5 (neutral) is a single point.
+fn contour_points(tone: u8) -> &'static [(f64, f64)] {
+    match tone {
+        1 => &[(0.0, 1.0), (1.0, 1.0)],
+        2 => &[(0.0, 0.3), (1.0, 1.0)],
+        3 => &[(0.0, 0.4), (0.45, 0.0), (1.0, 0.8)],
+        4 => &[(0.0, 1.0), (1.0, 0.0)],
+        _ => &[(0.5, 0.5)],
+    }
+}
+
+/// Draw `tone`'s contour in an 18x10 px box centred on `(x, y)`.
+fn draw_tone_contour(ctx: &CanvasRenderingContext2d, x: f64, y: f64, tone: u8) {
+    let (w, h) = (18.0, 10.0);
+    let to_px = |(px, py): (f64, f64)| (x - w / 2.0 + px * w, y + h / 2.0 - py * h);
+    let points = contour_points(tone);
+    ctx.set_stroke_style_str("#7ad7ff");
+    ctx.set_fill_style_str("#7ad7ff");
+    if let [only] = points {
+        let (px, py) = to_px(*only);
+        ctx.fill_rect(px - 2.0, py - 2.0, 4.0, 4.0);
+        return;
+    }
+    ctx.set_line_width(2.5);
+    ctx.begin_path();
+    for (i, &p) in points.iter().enumerate() {
+        let (px, py) = to_px(p);
+        if i == 0 {
+            ctx.move_to(px, py);
+        } else {
+            ctx.line_to(px, py);
+        }
+    }
+    ctx.stroke();
+}
+
+fn line(ctx: &CanvasRenderingContext2d, x1: f64, y1: f64, x2: f64, y2: f64) {
+    ctx.begin_path();
+    ctx.move_to(x1, y1);
+    ctx.line_to(x2, y2);
+    ctx.stroke();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Helper to build a simple LevelDesc with optional blocked tiles and leaked static slices
+    fn make_level_with_tiles(
+        width: u8,
+        height: u8,
+        obstacle_positions: &[(u8, u8)],
+        goals: &[(u8, u8)],
+    ) -> LevelDesc {
+        let mut tiles_vec = vec![
+            TileDesc {
+                obstacle: None,
+                modifier: None,
+                fixed_hanzi: None,
+            };
+            width as usize * height as usize
+        ];
+        for &(ox, oy) in obstacle_positions.iter() {
+            let idx = oy as usize * width as usize + ox as usize;
+            tiles_vec[idx] = TileDesc {
+                obstacle: Some(ObstacleKind::Block),
+                modifier: None,
+                fixed_hanzi: None,
+            };
+        }
+        let tiles_static: &'static [TileDesc] = Box::leak(tiles_vec.into_boxed_slice());
+        let spawn_static: &'static [(u8, u8)] = Box::leak(vec![(0u8, 0u8)].into_boxed_slice());
+        let goal_static: &'static [(u8, u8)] = Box::leak(goals.to_vec().into_boxed_slice());
+        LevelDesc {
+            name: "test-level",
+            width,
+            height,
+            bpm: 120.0,
+            subdivision: 1,
+            wrap: false,
+            chain_clear: false,
+            tiles: tiles_static,
+            spawn_points: spawn_static,
+            goal_region: goal_static,
+        }
+    }
+
+    #[test]
+    fn test_beatclock() {
+        let start = 1_000.0;
+        let clock = BeatClock::new(120.0, start);
+        assert!((clock.beat_duration_ms() - 500.0).abs() < 1e-6);
+        assert!((clock.current_beat(start) - 0.0).abs() < 1e-9);
+        assert!((clock.current_beat(start + 500.0) - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_beat_offset_is_signed_distance_to_nearest_beat() {
+        let clock = BeatClock::new(120.0, 0.0);
+        assert!(clock.beat_offset(0.0).abs() < 1e-9);
+        assert!((clock.beat_offset(50.0) - 0.1).abs() < 1e-9); // just after beat 0
+        assert!((clock.beat_offset(450.0) + 0.1).abs() < 1e-9); // just before beat 1
+        assert!(clock.beat_offset(250.0).abs() > ON_BEAT_WINDOW); // off-beat
+    }
+
+    #[test]
+    fn test_beatclock_set_bpm_keeps_beat_position() {
+        let mut clock = BeatClock::new(120.0, 0.0);
+        let now = 1_250.0; // beat 2.5
+        clock.set_bpm(60.0, now);
+        assert!((clock.current_beat(now) - 2.5).abs() < 1e-9);
+        // Half tempo: the next half beat now takes 500ms instead of 250ms.
+        assert!((clock.current_beat(now + 500.0) - 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_slow_powerup_halves_beat_rate() {
+        let level_bpm = 120.0;
+        let mut clock = BeatClock::new(level_bpm, 0.0);
+        let start = 2_000.0; // beat 4
+        clock.set_bpm(level_bpm * powerups::SLOW_FACTOR, start);
+        // One second normally covers 2 beats; slowed it covers 1.
+        assert!((clock.current_beat(start + 1_000.0) - 5.0).abs() < 1e-9);
+        // Expiry restores the level tempo from the current position.
+        let end = start + powerups::SLOW_MS;
+        let at_end = clock.current_beat(end);
+        clock.set_bpm(level_bpm, end);
+        assert!((clock.current_beat(end + 1_000.0) - (at_end + 2.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_coins_accrue_monotonically_from_score() {
+        let (mut last, mut coins) = (0, 0);
+        powerups::accrue_coins(250, &mut last, &mut coins);
+        assert_eq!((last, coins), (200, 2));
+        powerups::accrue_coins(299, &mut last, &mut coins);
+        assert_eq!(coins, 2); // leftover points wait for the next full coin
+        powerups::accrue_coins(300, &mut last, &mut coins);
+        assert_eq!((last, coins), (300, 3));
+        // Spending does not re-earn: the watermark only moves with score.
+        coins -= 3;
+        powerups::accrue_coins(300, &mut last, &mut coins);
+        assert_eq!(coins, 0);
+    }
+
+    #[test]
+    fn test_powerup_costs_and_spending() {
+        assert_eq!(powerups::powerup_cost("shield"), Some(6));
+        assert_eq!(powerups::powerup_cost("slow"), Some(8));
+        assert_eq!(powerups::powerup_cost("clear"), Some(10));
+        assert_eq!(powerups::powerup_cost("x2"), None);
+        let mut coins = 7;
+        assert!(!powerups::try_spend(&mut coins, 8)); // insufficient coins
+        assert_eq!(coins, 7);
+        assert!(powerups::try_spend(&mut coins, 6));
+        assert_eq!(coins, 1);
+    }
+
+    #[test]
+    fn test_on_beat_window_boundaries() {
+        let w = ON_BEAT_WINDOW;
+        assert!(on_beat(0.0, w, w));
+        assert!(on_beat(-w, w, w) && on_beat(w, w, w));
+        assert!(!on_beat(-w - 0.01, w, w) && !on_beat(w + 0.01, w, w));
+        // Asymmetric: generous late window, strict early one.
+        assert!(on_beat(0.2, 0.05, 0.25));
+        assert!(!on_beat(-0.1, 0.05, 0.25));
+        // Assist mode accepts what the default rejects.
+        assert!(on_beat(0.2, ASSIST_BEAT_WINDOW, ASSIST_BEAT_WINDOW));
+    }
+
+    #[test]
+    fn test_beatclock_shift_preserves_beat_across_pause() {
+        let mut clock = BeatClock::new(120.0, 0.0);
+        let pause_at = 1_250.0; // beat 2.5
+        let before = clock.current_beat(pause_at);
+        // Resume after a 10s pause: the beat must not skip ahead.
+        let resume_at = pause_at + 10_000.0;
+        clock.shift(resume_at - pause_at);
+        assert!((clock.current_beat(resume_at) - before).abs() < 1e-9);
+        assert_eq!(clock.current_beat(resume_at).floor() as i64, 2);
+    }
+
+    #[test]
+    fn test_beatclock_subdivision() {
+        let clock = BeatClock::new(120.0, 0.0);
+        // 120 BPM, eighths: subbeat 1.0 lands at 250ms, beat 1 at subbeat 2.0
+        assert!((clock.current_subbeat(250.0, 2) - 1.0).abs() < 1e-9);
+        assert!((clock.current_subbeat(500.0, 2) - 2.0).abs() < 1e-9);
+        assert!((clock.current_subbeat(125.0, 4) - 1.0).abs() < 1e-9);
+        // div = 0 is treated as whole beats
+        assert!((clock.current_subbeat(500.0, 0) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_levels_and_thresholds_in_sync() {
+        assert_eq!(levels().len(), LEVEL_SCORE_THRESHOLDS.len());
+        assert!(LEVEL_SCORE_THRESHOLDS.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(next_level_threshold(0), Some(LEVEL_SCORE_THRESHOLDS[1]));
+        assert_eq!(next_level_threshold(levels().len() - 1), None);
+        assert_eq!(next_level_threshold(levels().len() + 3), None);
+    }
+
+    #[test]
+    fn test_final_clear_score_exceeds_last_threshold() {
+        assert!(FINAL_LEVEL_CLEAR_SCORE > *LEVEL_SCORE_THRESHOLDS.last().unwrap());
+    }
+
+    #[test]
+    fn test_level_tile_access() {
+        let lvl = make_level_with_tiles(2, 2, &[], &[(1, 1)]);
+        let t = lvl.tile(1, 0);
+        assert!(t.obstacle.is_none());
+        let g = lvl.tile(1, 1);
+        assert!(g.obstacle.is_none());
+    }
+
+    #[test]
+    fn test_choose_next_step_prefers_unblocked_direction() {
+        // Create 3x3 level with (1,0) blocked so (0,0) should move down to (0,1)
+        let lvl = make_level_with_tiles(3, 3, &[(1, 0)], &[(2, 2)]);
+        let step = choose_next_step(&lvl, 0, 0, &[], 0);
+        assert_eq!(step, Some((0, 1)));
+    }
+
+    #[test]
+    fn test_step_target_respects_bounds_and_blocks() {
+        let lvl = make_level_with_tiles(3, 3, &[(1, 0)], &[(2, 2)]);
+        assert_eq!(step_target(&lvl, 0, 0, 1, 0, &[], 0), None); // blocked
+        assert_eq!(step_target(&lvl, 0, 0, -1, 0, &[], 0), None); // out of bounds
+        assert_eq!(step_target(&lvl, 2, 2, 0, 1, &[], 0), None); // out of bounds
+        assert_eq!(step_target(&lvl, 0, 0, 0, 1, &[], 0), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_locked_door_blocks_until_key_collected() {
+        let mut tiles = vec![TileDesc::default(); 9];
+        tiles[1].obstacle = Some(ObstacleKind::Door { id: 7 });
+        let lvl = LevelDesc {
+            name: "door-test",
+            width: 3,
+            height: 3,
+            bpm: 120.0,
+            subdivision: 1,
+            wrap: false,
+            chain_clear: false,
+            tiles: Box::leak(tiles.into_boxed_slice()),
+            spawn_points: &[(0, 0)],
+            goal_region: &[(2, 0)],
+        };
+        assert!(tile_blocked(&lvl, 1, 0, &[], 0));
+        assert!(tile_blocked(&lvl, 1, 0, &[3], 0));
+        assert!(!tile_blocked(&lvl, 1, 0, &[7], 0));
+        assert_eq!(step_target(&lvl, 0, 0, 1, 0, &[], 0), None);
+        assert_eq!(step_target(&lvl, 0, 0, 1, 0, &[7], 0), Some((1, 0)));
+        // Locked: no step gets closer to the goal; unlocked: straight through the door.
+        assert_eq!(choose_next_step(&lvl, 0, 0, &[], 0), None);
+        assert_eq!(choose_next_step(&lvl, 0, 0, &[7], 0), Some((1, 0)));
+    }
+
+    #[test]
+    fn test_fixed_hanzi_tiles_are_reachable() {
+        // A pinned tile on a wall would never be shown or typeable.
+        for lvl in levels() {
+            for y in 0..lvl.height {
+                for x in 0..lvl.width {
+                    if lvl.tile(x, y).fixed_hanzi.is_some() {
+                        assert!(!tile_blocked(lvl, x, y, &[], 0), "{} ({x},{y})", lvl.name);
+                    }
+                }
+            }
+        }
+        let pinned = levels()
+            .iter()
+            .flat_map(|l| l.tiles.iter())
+            .filter(|t| t.fixed_hanzi.is_some())
+            .count();
+        assert!(pinned > 0);
+    }
+
+    #[test]
+    fn test_cell_fade_in_and_out() {
+        let mut fade = CellFade::default();
+        fade.sync(Some("你"), 1_000.0);
+        assert!(fade.alpha(1_000.0).abs() < 1e-9);
+        assert!((fade.alpha(1_000.0 + TILE_FADE_IN_MS / 2.0) - 0.5).abs() < 1e-9);
+        assert!((fade.alpha(1_000.0 + TILE_FADE_IN_MS) - 1.0).abs() < 1e-9);
+        // A different glyph in the same cell fades in again.
+        fade.sync(Some("好"), 2_000.0);
+        assert_eq!(fade.shown, Some("好"));
+        assert!(fade.alpha(2_000.0).abs() < 1e-9);
+        // Cleared: the old glyph lingers while fading out, then disappears.
+        fade.sync(None, 3_000.0);
+        assert_eq!(fade.shown, Some("好"));
+        assert!((fade.alpha(3_000.0) - 1.0).abs() < 1e-9);
+        fade.sync(None, 3_000.0 + TILE_FADE_OUT_MS);
+        assert_eq!(fade.shown, None);
+    }
+
+    #[test]
+    fn test_tone_marks_per_syllable() {
+        assert_eq!(tone_marks("ni3"), vec!["ˇ"]);
+        assert_eq!(tone_marks("zhong1guo2"), vec!["ˉ", "ˊ"]);
+        assert_eq!(tone_marks("er3duo5"), vec!["ˇ", ""]);
+        assert!(tone_marks("ni").is_empty());
+    }
+
+    #[test]
+    fn test_review_tone_miss_and_json() {
+        assert!(review::is_tone_miss("ni2", "ni3"));
+        assert!(review::is_tone_miss("ni", "ni3"));
+        assert!(!review::is_tone_miss("ni3", "ni3"));
+        assert!(!review::is_tone_miss("hao3", "ni3"));
+        assert_eq!(review::missed_json(&[]), "[]");
+        assert_eq!(
+            review::missed_json(&[("你", "ni3", 2), ("好", "hao3", 1)]),
+            r#"[{"hanzi":"你","pinyin":"ni3","misses":2},{"hanzi":"好","pinyin":"hao3","misses":1}]"#
+        );
+    }
+
+    #[test]
+    fn test_speed_boost_decays_to_one() {
+        let until = 10_000.0;
+        assert!((speed_boost(until - COMBO_BOOST_MS, until) - COMBO_BOOST_FACTOR).abs() < 1e-9);
+        let half = speed_boost(until - COMBO_BOOST_MS / 2.0, until);
+        assert!((half - (1.0 + (COMBO_BOOST_FACTOR - 1.0) / 2.0)).abs() < 1e-9);
+        assert!((speed_boost(until, until) - 1.0).abs() < 1e-9);
+        assert!((speed_boost(until + 500.0, until) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_matched_prefix_len_picks_best_candidate() {
+        let cands = ["ni3", "hao3", "nan2"];
+        assert_eq!(matched_prefix_len("", &cands), 0);
+        assert_eq!(matched_prefix_len("na", &cands), 2);
+        assert_eq!(matched_prefix_len("nix", &cands), 2);
+        assert_eq!(matched_prefix_len("hao3", &cands), 4);
+        assert_eq!(matched_prefix_len("qu", &cands), 0);
+        assert_eq!(matched_prefix_len("ni3", &[]), 0);
+        // A miss counts against the tile it was aimed at, if there is one.
+        assert_eq!(missed_target("ni2", &cands), Some(0));
+        assert_eq!(missed_target("hao", &cands), Some(1));
+        assert_eq!(missed_target("n", &cands), None); // ni3 or nan2
+        assert_eq!(missed_target("qu", &cands), None);
+        assert_eq!(missed_target("", &cands), None);
+        assert_eq!(missed_target("ni4", &["ni3", "ni3"]), Some(0));
+    }
+
+    #[test]
+    fn test_is_typing_target_needs_prefix() {
+        assert!(is_typing_target("n", "ni3"));
+        assert!(is_typing_target("ni3", "ni3"));
+        assert!(!is_typing_target("", "ni3"));
+        assert!(!is_typing_target("nih", "ni3"));
+    }
+
+    #[test]
+    fn test_splitmix64_is_deterministic_per_seed() {
+        let draw = |seed: u64| {
+            let mut st = seed;
+            [
+                splitmix64(&mut st),
+                splitmix64(&mut st),
+                splitmix64(&mut st),
+            ]
+        };
+        assert_eq!(draw(42), draw(42));
+        assert_ne!(draw(42), draw(43));
+        let [a, b, _] = draw(0);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_boss_tile_clears_on_last_hit() {
+        let mut boss = BossTile {
+            idx: 0,
+            hanzi: "老",
+            hits_done: 0,
+            hit_flash_ms: None,
+        };
+        for i in 1..BOSS_HITS {
+            assert!(!boss.hit(i as f64));
+            assert_eq!(boss.hit_flash_ms, Some(i as f64));
+        }
+        assert!(boss.hit(99.0));
+    }
+
+    #[test]
+    fn test_combo_meter_fills_and_changes_colour() {
+        assert_eq!(combo_meter(0), (0.0, "#ffffff"));
+        assert_eq!(combo_meter(15), (50.0, "#ffd166"));
+        assert_eq!(combo_meter(20).1, "#ff4d4d");
+        assert_eq!(combo_meter(COMBO_METER_CAP * 2).0, 100.0);
+    }
+
+    #[test]
+    fn test_life_awards_once_per_threshold() {
+        let mut last = 0;
+        assert_eq!(life_awards(4999, &mut last, 5000), 0);
+        assert_eq!(life_awards(5200, &mut last, 5000), 1);
+        assert_eq!(last, 5000);
+        assert_eq!(life_awards(5400, &mut last, 5000), 0);
+        assert_eq!(life_awards(15100, &mut last, 5000), 2);
+        assert_eq!(last, 15000);
+        assert_eq!(life_awards(90000, &mut last, 0), 0);
+        assert_eq!(last, 90000);
+    }
+
+    #[test]
+    fn test_nudge_difficulty_is_gentle_and_clamped() {
+        let window = |hits: usize| {
+            (0..ADAPTIVE_WINDOW)
+                .map(|i| i < hits)
+                .collect::<std::collections::VecDeque<bool>>()
+        };
+        // Partial window: no change yet.
+        let partial: std::collections::VecDeque<bool> = [true; 3].into_iter().collect();
+        assert_eq!(nudge_difficulty(0.5, &partial), 0.5);
+        let up = nudge_difficulty(0.5, &window(ADAPTIVE_WINDOW));
+        assert!((up - (0.5 + ADAPTIVE_STEP)).abs() < 1e-9);
+        let down = nudge_difficulty(0.5, &window(0));
+        assert!((down - (0.5 - ADAPTIVE_STEP)).abs() < 1e-9);
+        assert_eq!(nudge_difficulty(1.0, &window(ADAPTIVE_WINDOW)), 1.0);
+        assert_eq!(nudge_difficulty(0.0, &window(0)), 0.0);
+        assert!((tempo_factor(0.5) - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_build_summary_lists_stats_and_misses() {
+        let run = summary::RunSummary {
+            score: 4200,
+            hits: 18,
+            misses: 2,
+            max_combo: 11,
+            play_ms: 60_000.0,
+            coins: 42,
+            won: false,
+            locale: "en",
+            missed: vec![("你", "ni3", 2)],
+            timing: vec![("好", "hao3", 3, 1, Some(1250.0))],
+        };
+        assert_eq!(run.accuracy(), 90.0);
+        assert_eq!(run.cpm(), 18.0);
+        let html = summary::build_summary(&run);
+        for part in [
+            "GAME OVER",
+            "Score: 4,200",
+            "Accuracy: 90.0% (grade A)",
+            "Max combo: 11",
+            "CPM: 18.0",
+            "Coins: 42",
+            "你",
+            "ni3",
+            "<td>3</td><td>1</td><td>1.25s</td>",
+            "Play Again",
+        ] {
+            assert!(html.contains(part), "missing {part}");
+        }
+        assert_eq!(
+            summary::escape_html("<b>猫</b> & 'x'"),
+            "&lt;b&gt;猫&lt;/b&gt; &amp; &#39;x&#39;"
+        );
+        assert_eq!(summary::grade(100.0), "S");
+        assert_eq!(summary::grade(10.0), "D");
+    }
+
+    #[test]
+    fn test_tone_cue_contours_follow_the_tone() {
+        assert_eq!(first_tone("ni3hao3"), Some(3));
+        assert_eq!(first_tone("er2duo5"), Some(2));
+        assert_eq!(first_tone("ma"), None);
+        assert_eq!(tones("er2duo5").collect::<Vec<_>>(), vec![2, 5]);
+        let shape = |tone| {
+            let c = metronome::tone_contour(tone);
+            (c[0].1, c[c.len() - 1].1)
+        };
+        let (a, b) = shape(1);
+        assert_eq!(a, b);
+        let (a, b) = shape(2);
+        assert!(b > a);
+        let (a, b) = shape(4);
+        assert!(b < a);
+        let dip = metronome::tone_contour(3);
+        assert!(dip[1].1 < dip[0].1 && dip[2].1 > dip[1].1);
+    }
+
+    #[test]
+    fn test_contour_points_shapes() {
+        let ends = |tone| {
+            let p = contour_points(tone);
+            (p[0].1, p[p.len() - 1].1)
+        };
+        assert_eq!(ends(1), (1.0, 1.0));
+        assert!(ends(2).1 > ends(2).0);
+        assert!(ends(4).1 < ends(4).0);
+        assert!(contour_points(3)[1].1 < contour_points(3)[0].1);
+        assert_eq!(contour_points(5).len(), 1);
+        for tone in 1..=5 {
+            assert!(
+                contour_points(tone)
+                    .iter()
+                    .all(|&(x, y)| (0.0..=1.0).contains(&x) && (0.0..=1.0).contains(&y))
+            );
+        }
+    }
+
+    #[test]
+    fn test_wobble_offset_stays_small() {
+        assert_eq!(wobble_offset(0.0, 0.0), 0.0);
+        for t in (0..5000).step_by(37) {
+            assert!(wobble_offset(t as f64, 1.3).abs() <= WOBBLE_AMPLITUDE);
+        }
+    }
+
+    #[test]
+    fn test_combo_pitch_climbs_then_caps() {
+        assert_eq!(metronome::combo_pitch(0), metronome::combo_pitch(1));
+        for c in 1..12 {
+            assert!(metronome::combo_pitch(c + 1) > metronome::combo_pitch(c));
+        }
+        assert_eq!(metronome::combo_pitch(12), metronome::combo_pitch(40));
+        // Octave at the 8th step of the major scale.
+        assert!((metronome::combo_pitch(8) - 2.0 * metronome::combo_pitch(1)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_format_score_groups_by_locale() {
+        assert_eq!(format_score(0, "en"), "0");
+        assert_eq!(format_score(999, "en"), "999");
+        assert_eq!(format_score(12000, "en"), "12,000");
+        assert_eq!(format_score(1234567, "en-US"), "1,234,567");
+        assert_eq!(format_score(12000, "de"), "12.000");
+        assert_eq!(format_score(1234567, "fr"), "1\u{a0}234\u{a0}567");
+        assert_eq!(format_score(-2500, "en"), "-2,500");
+        assert_eq!(format_score(50000, "xx"), "50,000");
+    }
+
+    #[test]
+    fn test_score_payload_escapes_name() {
+        let body = leaderboard::score_payload("Mei \"猫\" Li\\", 4200, 87.5, "board", 2);
+        assert_eq!(
+            body,
+            r#"{"name":"Mei \"猫\" Li\\","score":4200,"accuracy":87.5,"mode":"board","level":3}"#
+        );
+        assert!(leaderboard::score_payload("a\nb", 0, 100.0, "daily", 0).contains(r#""a\nb""#));
+    }
+
+    #[test]
+    fn test_high_score_name_and_label() {
+        assert_eq!(highscore::display_name("  Mei "), "Mei");
+        assert_eq!(highscore::display_name("   "), highscore::DEFAULT_NAME);
+        assert_eq!(
+            highscore::best_label(Some(("Mei", 12000)), "en"),
+            "Best: 12,000 (Mei)"
+        );
+        assert_eq!(highscore::best_label(None, "en"), "Best: -");
+    }
+
+    #[test]
+    fn test_format_play_time() {
+        assert_eq!(format_play_time(0.0), "0:00");
+        assert_eq!(format_play_time(59.9), "0:59");
+        assert_eq!(format_play_time(125.0), "2:05");
+        assert_eq!(format_play_time(3600.0), "60:00");
+    }
+
+    #[test]
+    fn test_daily_seed_is_stable_per_date() {
+        assert_eq!(daily_seed(20261016), daily_seed(20261016));
+        assert_ne!(daily_seed(20261016), daily_seed(20261017));
+        assert_ne!(daily_seed(20261016), 20261016);
+    }
+
+    #[test]
+    fn test_category_filter() {
+        let allowed = vocab::parse_csv(" Animal, ,nature ");
+        assert_eq!(allowed, vec!["animal", "nature"]);
+        let filter = vocab::Filter {
+            categories: allowed,
+            ..Default::default()
+        };
+        let pool = vocab::apply_filter(crate::SINGLE_HANZI, &filter);
+        assert!(pool.contains(&("猫", "mao1")) && pool.contains(&("山", "shan1")));
+        assert!(!pool.iter().any(|(h, _)| *h == "你"));
+        let animals = vocab::Filter {
+            categories: vec!["animal".into()],
+            ..Default::default()
+        };
+        assert!(vocab::apply_filter(&LEVEL4_HANZI, &animals).is_empty());
+        assert_eq!(
+            vocab::apply_filter(crate::SINGLE_HANZI, &vocab::Filter::default()).len(),
+            crate::SINGLE_HANZI.len()
+        );
+    }
+
+    #[test]
+    fn test_hsk_filter() {
+        let hsk1 = vocab::Filter {
+            hsk: Some((1, 1)),
+            ..Default::default()
+        };
+        let pool = vocab::apply_filter(crate::SINGLE_HANZI, &hsk1);
+        assert!(!pool.is_empty());
+        assert!(pool.iter().all(|(h, _)| vocab::lookup(h).unwrap().hsk == 1));
+        // Combined with a category: HSK 1 animals are just 猫.
+        let hsk1_animals = vocab::Filter {
+            categories: vec!["animal".into()],
+            ..hsk1
+        };
+        assert_eq!(
+            vocab::apply_filter(crate::SINGLE_HANZI, &hsk1_animals),
+            vec![("猫", "mao1")]
+        );
+        let levels = vocab::available_hsk_levels();
+        assert_eq!(levels.first(), Some(&1));
+        assert!(levels.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn test_every_pool_hanzi_has_info() {
+        let pools: [&[(&str, &str)]; 7] = [
+            crate::SINGLE_HANZI,
+            &LEVEL2_HANZI,
+            &LEVEL3_HANZI,
+            &LEVEL4_HANZI,
+            &LEVEL5_HANZI,
+            LEVEL6_HANZI,
+            LEVEL7_HANZI,
+        ];
+        for (h, _) in pools.iter().flat_map(|p| p.iter()) {
+            assert!(vocab::lookup(h).is_some(), "no info for {h}");
+        }
+    }
+
+    #[test]
+    fn test_word_characters_are_not_above_the_word_level() {
+        for word in vocab::HANZI_INFO
+            .iter()
+            .filter(|i| i.hanzi.chars().count() > 1)
+        {
+            for c in word.hanzi.chars() {
+                if let Some(info) = vocab::lookup(c.encode_utf8(&mut [0; 4])) {
+                    assert!(
+                        info.hsk <= word.hsk,
+                        "{c} is HSK {} but {} is HSK {}",
+                        info.hsk,
+                        word.hanzi,
+                        word.hsk
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hsk_unlocks_with_progress() {
+        let schedule = vocab::DEFAULT_HSK_UNLOCKS;
+        assert_eq!(vocab::hsk_unlocked(0.0, &schedule), 1);
+        assert_eq!(vocab::hsk_unlocked(0.2, &schedule), 2);
+        assert_eq!(vocab::hsk_unlocked(1.0, &schedule), 6);
+        // A schedule that starts late still allows HSK 1.
+        assert_eq!(vocab::hsk_unlocked(0.0, &[0.5, 0.9]), 1);
+        let capped = vocab::Filter {
+            hsk_cap: Some(1),
+            ..Default::default()
+        };
+        let pool = vocab::apply_filter(crate::SINGLE_HANZI, &capped);
+        assert!(!pool.iter().any(|(h, _)| *h == "山"));
+        assert!(pool.contains(&("猫", "mao1")));
+    }
+
+    #[test]
+    fn test_weighted_index_bounds() {
+        assert_eq!(weighted_index(&[1, 3], 0.0), 0);
+        assert_eq!(weighted_index(&[1, 3], 0.24), 0);
+        assert_eq!(weighted_index(&[1, 3], 0.25), 1);
+        assert_eq!(weighted_index(&[1, 3], 0.999), 1);
+        assert_eq!(weighted_index(&[0, 0], 0.7), 1);
+    }
+
+    #[test]
+    fn test_high_weight_entry_drawn_more_often() {
+        RNG_STATE.with(|s| s.set(Some(42)));
+        let weights = [10, 1, 1];
+        let mut counts = [0u32; 3];
+        for _ in 0..3000 {
+            counts[weighted_index(&weights, rand_unit())] += 1;
+        }
+        RNG_STATE.with(|s| s.set(None));
+        assert!(counts[0] > counts[1] * 5 && counts[0] > counts[2] * 5);
+        assert!(counts[1] > 0 && counts[2] > 0);
+    }
+
+    #[test]
+    fn test_reverse_choices() {
+        let pool: &[(&str, &str)] = &[
+            ("山", "shan1"),
+            ("水", "shui3"),
+            ("火", "huo3"),
+            ("月", "yue4"),
+        ];
+        // rand(n) = 0 keeps the order: adjacent first, then pool distractors.
+        let mut first = |_| 0;
+        let choices = reverse::build_choices(&["水", "山", "水"], pool, 2, &mut first);
+        assert_eq!(choices.len(), 4);
+        for h in ["水", "山"] {
+            assert_eq!(choices.iter().filter(|c| **c == h).count(), 1);
         }
+        // Small pools just offer what they have.
+        assert_eq!(
+            reverse::build_choices(&["山"], &pool[..2], 5, |_| 0).len(),
+            2
+        );
     }
-}
 
-fn expire_effects(state: &mut BoardState, current_beat: i64) {
-    if state.score_mult_end_beat >= 0 && current_beat >= state.score_mult_end_beat {
-        state.score_multiplier = 1.0;
-        state.score_mult_end_beat = -1;
+    #[test]
+    fn test_volume_is_clamped() {
+        assert_eq!(metronome::clamp_volume(0.4), 0.4);
+        assert_eq!(metronome::clamp_volume(1.7), 1.0);
+        assert_eq!(metronome::clamp_volume(-0.2), 0.0);
+        assert_eq!(metronome::clamp_volume(f64::NAN), 0.0);
     }
-    if state.hop_time_end_beat >= 0 && current_beat >= state.hop_time_end_beat {
-        state.hop_time_factor = 1.0;
-        state.hop_time_end_beat = -1;
+
+    #[test]
+    fn test_mute_zeroes_master_level() {
+        assert_eq!(metronome::master_level(0.6, false), 0.6);
+        assert_eq!(metronome::master_level(0.6, true), 0.0);
     }
-}
 
-fn check_level_progression(state: &mut BoardState, now: f64, current_beat: i64) {
-    // If next level exists and score threshold reached, advance.
-    if state.level_index + 1 < levels().len() {
-        let next_idx = state.level_index + 1;
-        if state.score >= LEVEL_SCORE_THRESHOLDS[next_idx] {
-            set_level(state, next_idx, now, current_beat);
-        }
+    #[test]
+    fn test_gamepad_presses_fire_once() {
+        // Button 12 held over two polls fires only on the first.
+        assert_eq!(gamepad::pressed_since(0, 1 << 12), 1 << 12);
+        assert_eq!(gamepad::pressed_since(1 << 12, 1 << 12), 0);
+        assert_eq!(gamepad::pressed_since(1 << 12, (1 << 12) | 1), 1);
+        assert_eq!(gamepad::pressed_since(1, 0), 0);
     }
-}
 
-fn set_level(state: &mut BoardState, new_index: usize, now: f64, _current_beat: i64) {
-    // Switch to the new level descriptor and reinitialize dynamic per-level state.
-    state.level_index = new_index;
-    state.level = levels()[new_index];
+    #[test]
+    fn test_key_bindings() {
+        let defaults = keys::KeyBindings::default();
+        // Shared Escape: clears a non-empty buffer, pauses otherwise.
+        assert!(defaults.is_pause("Escape", false, true));
+        assert!(!defaults.is_pause("Escape", false, false));
+        assert!(defaults.is_pause("Escape", true, false));
+        assert_eq!(defaults.canonical("Escape"), Some("Escape"));
+        assert_eq!(defaults.canonical("n"), Some("n"));
 
-    // Rebuild the grid for the new level. Block tiles remain None; other tiles
-    // are filled with a random hanzi/pinyin appropriate to the level.
-    let lvl = state.level;
-    state.grid.clear();
-    state.grid.reserve(lvl.width as usize * lvl.height as usize);
-    for yy in 0..lvl.height {
-        for xx in 0..lvl.width {
-            let tile = lvl.tile(xx, yy);
-            if matches!(tile.obstacle, Some(ObstacleKind::Block)) {
-                state.grid.push(None);
-            } else {
-                let (h, p) = pick_random_hanzi(lvl);
-                state.grid.push(Some((h, p)));
-            }
-        }
+        let custom = keys::KeyBindings {
+            clear: "Delete".into(),
+            backspace: "Backspace".into(),
+            pause: "F9".into(),
+            toggle_hints: "F2".into(),
+        };
+        assert!(custom.is_pause("F9", false, false));
+        assert_eq!(custom.canonical("Delete"), Some("Escape"));
+        assert_eq!(custom.canonical("Escape"), None);
+
+        // Letters and digits stay game input.
+        assert!(!keys::bindable("p"));
+        assert!(!keys::bindable("P"));
+        assert!(!keys::bindable("3"));
+        assert!(!keys::bindable("9"));
+        assert!(keys::bindable("-"));
+        assert!(keys::bindable("F9"));
+        assert!(keys::bindable("Delete"));
     }
 
-    // Position the cat near the center or on the first non-block tile found.
-    let mut cx = lvl.width / 2;
-    let mut cy = lvl.height / 2;
-    if matches!(lvl.tile(cx, cy).obstacle, Some(ObstacleKind::Block)) {
-        'search_free: for yy in 0..lvl.height {
-            for xx in 0..lvl.width {
-                if !matches!(lvl.tile(xx, yy).obstacle, Some(ObstacleKind::Block)) {
-                    cx = xx;
-                    cy = yy;
-                    break 'search_free;
-                }
-            }
-        }
+    #[test]
+    fn test_breaks_prefix() {
+        let candidates = ["ni3", "hao3"];
+        assert!(!breaks_prefix("n", &candidates));
+        assert!(!breaks_prefix("hao", &candidates));
+        assert!(breaks_prefix("nx", &candidates));
+        assert!(breaks_prefix("ni4", &candidates));
+        assert!(!breaks_prefix("x", &[]));
     }
-    state.cat_x = cx;
-    state.cat_y = cy;
-    // Reset hop animation so cat remains consistent with new level
-    state.cat_from_x = state.cat_x;
-    state.cat_from_y = state.cat_y;
-    state.cat_target_x = state.cat_x;
-    state.cat_target_y = state.cat_y;
-    state.cat_hop_start_ms = now;
-    state.cat_hop_duration_ms = 220.0;
-    state.cat_hopping = false;
 
-    // Ensure player's tile is empty and neighbors are uniquely populated for level 0.
-    {
-        let lvl = state.level;
-        let w = lvl.width as usize;
-        let h = lvl.height as usize;
-        let cx = state.cat_x as i32;
-        let cy = state.cat_y as i32;
-
-        // Collect neighbor indices (8-connected)
-        let mut neighbors: Vec<usize> = Vec::new();
-        for dy in -1..=1 {
-            for dx in -1..=1 {
-                if dx == 0 && dy == 0 {
-                    continue;
-                }
-                let nx = cx + dx;
-                let ny = cy + dy;
-                if nx < 0 || ny < 0 {
-                    continue;
-                }
-                let nxu = nx as u8;
-                let nyu = ny as u8;
-                if nxu >= lvl.width || nyu >= lvl.height {
-                    continue;
-                }
-                if matches!(lvl.tile(nxu, nyu).obstacle, Some(ObstacleKind::Block)) {
-                    continue;
-                }
-                neighbors.push(ny as usize * w + nx as usize);
-            }
-        }
+    #[test]
+    fn test_judge_band_matches_on_beat_window() {
+        let (l, r) = judge_band(0.12, 0.2);
+        assert!((l - 0.38).abs() < 1e-9 && (r - 0.7).abs() < 1e-9);
+        // The band's edges are exactly the offsets `on_beat` accepts.
+        assert!(on_beat(l - 0.5, 0.12, 0.2) && on_beat(r - 0.5, 0.12, 0.2));
+        assert_eq!(judge_band(0.5, 0.5), (0.0, 1.0));
+    }
 
-        // Clear player's tile
-        if cx >= 0 && cy >= 0 && (cx as u8) < lvl.width && (cy as u8) < lvl.height {
-            let cat_idx = cy as usize * w + cx as usize;
-            if cat_idx < state.grid.len() {
-                state.grid[cat_idx] = None;
-            }
-        }
+    #[test]
+    fn test_fireworks_once_per_milestone() {
+        assert!(!firework_due(24, 0));
+        assert!(firework_due(25, 0));
+        assert!(!firework_due(25, 25));
+        assert!(firework_due(50, 25));
+        // Sparks fan out in different directions after the rise.
+        let (a, _) = firework_spark(0, FIREWORK_MS * 0.9);
+        let (b, _) = firework_spark(FIREWORK_SPARKS / 2, FIREWORK_MS * 0.9);
+        assert!(a > 0.0 && b < 0.0);
+    }
 
-        if new_index == 0 && !neighbors.is_empty() {
-            let pool = crate::SINGLE_HANZI;
-            let pool_len = pool.len();
-            if pool_len > 0 {
-                let mut selected: Vec<(&'static str, &'static str)> = Vec::new();
-                let mut start = rand_index(pool_len);
-                while selected.len() < neighbors.len() + 2 && selected.len() < pool_len {
-                    let cand = pool[start % pool_len];
-                    if !selected.iter().any(|(h, _)| *h == cand.0) {
-                        selected.push(cand);
-                    }
-                    start = (start + 1) % pool_len;
-                }
+    #[test]
+    fn test_bursty_refill_cycle() {
+        assert_eq!(refill_count(SpawnPattern::Steady, 2, 10, 4), 2);
+        assert_eq!(refill_count(SpawnPattern::Steady, 0, 10, 4), 10);
+        let counts: Vec<usize> = (0..BURST_CYCLE)
+            .map(|t| refill_count(SpawnPattern::Bursty, 2, 10, t))
+            .collect();
+        assert_eq!(counts, vec![6, 6, 0, 0, 0, 0]);
+        assert_eq!(refill_count(SpawnPattern::Bursty, 4, 5, 0), 5);
+        assert_eq!(refill_count(SpawnPattern::Bursty, 0, 7, BURST_CYCLE), 7);
+    }
 
-                for (i, &idx) in neighbors.iter().enumerate() {
-                    if i < selected.len() {
-                        state.grid[idx] = Some(selected[i]);
-                    } else {
-                        let (h, p) = pick_random_hanzi(lvl);
-                        state.grid[idx] = Some((h, p));
-                    }
-                }
+    #[test]
+    fn test_dataset_entry_validation() {
+        assert_eq!(dataset::pinyin_syllables("ni3hao3"), Some(2));
+        assert_eq!(dataset::pinyin_syllables("lv4"), Some(1));
+        assert_eq!(dataset::pinyin_syllables("ni"), None);
+        assert_eq!(dataset::pinyin_syllables("ni6"), None);
+        assert_eq!(dataset::pinyin_syllables("3ni"), None);
+        assert!(dataset::validate_entry("你", "ni3", false).is_ok());
+        assert!(dataset::validate_entry("你好", "ni3hao3", true).is_ok());
+        assert!(dataset::validate_entry("你好", "ni3hao3", false).is_err());
+        assert!(dataset::validate_entry("你", "ni3hao3", false).is_err());
+        assert!(dataset::validate_entry("", "ni3", false).is_err());
+        assert!(dataset::validate_entry("你", "", false).is_err());
+    }
 
-                let (pat0, pat1) = if selected.len() >= neighbors.len() + 2 {
-                    (selected[neighbors.len()], selected[neighbors.len() + 1])
-                } else {
-                    (crate::SINGLE_HANZI[0], crate::SINGLE_HANZI[1 % pool_len])
-                };
+    #[test]
+    fn test_dataset_extras_extend_each_pool() {
+        let pool: &'static [(&str, &str)] = &[("你", "ni3"), ("好", "hao3")];
+        assert!(dataset::ensure_new(pool, "猫").is_ok());
+        assert!(dataset::ensure_new(pool, "好").is_err());
+        // Extras go after the level's own entries, without repeating them.
+        let merged = dataset::merged(pool, &[("猫", "mao1"), ("好", "hao3")]);
+        assert_eq!(merged, vec![("你", "ni3"), ("好", "hao3"), ("猫", "mao1")]);
+        let other: &'static [(&str, &str)] = &[("大", "da4")];
+        assert_eq!(dataset::merged(other, &[("猫", "mao1")]).len(), 2);
+    }
 
-                for y in 0..h {
-                    for x in 0..w {
-                        let idx = y * w + x;
-                        // Do not fill the player's tile (cat) so it remains empty.
-                        if x == state.cat_x as usize && y == state.cat_y as usize {
-                            continue;
-                        }
-                        if state.grid[idx].is_none()
-                            && !matches!(
-                                lvl.tile(x as u8, y as u8).obstacle,
-                                Some(ObstacleKind::Block)
-                            )
-                        {
-                            let parity = (x + y) % 2;
-                            state.grid[idx] = Some(if parity == 0 { pat0 } else { pat1 });
-                        }
-                    }
-                }
-            }
-        }
+    #[test]
+    fn test_difficulty_progress_is_clamped() {
+        assert_eq!(difficulty_progress(-50), 0.0);
+        assert_eq!(difficulty_progress(FINAL_LEVEL_CLEAR_SCORE / 2), 0.5);
+        assert_eq!(difficulty_progress(FINAL_LEVEL_CLEAR_SCORE * 2), 1.0);
     }
 
-    // Reset beat clock to the new level's BPM
-    state.beat = BeatClock {
-        bpm: state.level.bpm,
-        start_ms: now,
-        last_beat_idx: -1,
-    };
+    #[test]
+    fn test_pinyin_matches_can_ignore_tones() {
+        assert!(pinyin_matches("ni3", "ni3", false));
+        assert!(!pinyin_matches("ni", "ni3", false));
+        assert!(!pinyin_matches("ni2", "ni3", false));
+        assert!(pinyin_matches("ni", "ni3", true));
+        assert!(pinyin_matches("ni2hao", "ni3hao3", true));
+        assert!(!pinyin_matches("hao", "ni3", true));
+    }
 
-    // Reset temporary modifiers
-    state.hop_time_factor = 1.0;
-    state.hop_time_end_beat = -1;
-    state.score_multiplier = 1.0;
-    state.score_mult_end_beat = -1;
-}
+    #[test]
+    fn test_config_lives_are_clamped() {
+        assert_eq!(config::clamp_lives(0), 1);
+        assert_eq!(config::clamp_lives(2), 2);
+        assert_eq!(config::clamp_lives(99), MAX_LIVES);
+    }
 
-fn rand_index(len: usize) -> usize {
-    if len == 0 {
-        return 0;
+    #[test]
+    fn test_banner_alpha_fades_in_and_out() {
+        assert_eq!(banner_alpha(0.0), 0.0);
+        assert_eq!(banner_alpha(0.5), 1.0);
+        assert!(banner_alpha(0.9) < 0.5);
+        assert_eq!(banner_alpha(1.0), 0.0);
     }
-    let now = window()
-        .and_then(|w| w.performance())
-        .map(|p| p.now())
-        .unwrap_or(0.0);
-    // Simple linear transform and modulus for prototype randomness (not crypto secure)
-    (now as u64 as usize)
-        .wrapping_mul(1664525)
-        .wrapping_add(1013904223)
-        % len
-}
 
-/// Pick a random hanzi / pinyin tuple appropriate for the given level.
-/// Centralizes the per-level selection logic used in multiple places.
-fn pick_random_hanzi(level: &LevelDesc) -> (&'static str, &'static str) {
-    match level.name {
-        "Conveyor Crossing" => {
-            let hidx = rand_index(LEVEL2_HANZI.len());
-            LEVEL2_HANZI[hidx]
-        }
-        "Zigzag Express" => {
-            let hidx = rand_index(LEVEL4_HANZI.len());
-            LEVEL4_HANZI[hidx]
-        }
-        "Maze Challenge" => {
-            let hidx = rand_index(LEVEL3_HANZI.len());
-            LEVEL3_HANZI[hidx]
-        }
-        "Spiral Dream" => {
-            let hidx = rand_index(LEVEL5_HANZI.len());
-            LEVEL5_HANZI[hidx]
-        }
-        "Crystal Isle" => {
-            let hidx = rand_index(LEVEL6_HANZI.len());
-            LEVEL6_HANZI[hidx]
-        }
-        "Neon Bastion" => {
-            let hidx = rand_index(LEVEL7_HANZI.len());
-            LEVEL7_HANZI[hidx]
-        }
-        _ => {
-            let pool = crate::SINGLE_HANZI;
-            let len = pool.len();
-            if len == 0 {
-                ("你", "ni3")
-            } else {
-                let idx = rand_index(len);
-                pool[idx]
-            }
-        }
+    #[test]
+    fn test_tier_index_bands() {
+        assert_eq!(tier_index(0.0), 0);
+        assert_eq!(tier_index(0.19), 0);
+        assert_eq!(tier_index(0.2), 1);
+        assert_eq!(tier_index(0.65), 3);
+        assert_eq!(tier_index(1.0), TIERS.len() - 1);
+        assert!(TIERS.windows(2).all(|w| w[0].1 < w[1].1));
     }
-}
 
-/// Decide next step for a piece taking into account momentum (ice), jump pads, and
-/// simple heuristics. Returns the next tile to hop to if any.
-#[allow(dead_code)]
-fn choose_next_for_piece(level: &LevelDesc, p: &Piece) -> Option<(u8, u8)> {
-    let x = p.x;
-    let y = p.y;
+    #[test]
+    fn test_palette_hex_round_trip() {
+        assert_eq!(palette::parse_hex("#ffd166"), Some((255, 209, 102)));
+        assert_eq!(palette::parse_hex("#222"), Some((34, 34, 34)));
+        assert_eq!(palette::parse_hex("ffd166"), None);
+        assert_eq!(palette::parse_hex("#ffd16"), None);
+        assert_eq!(palette::parse_hex("#gggggg"), None);
+        let dark = palette::Palette::DARK;
+        assert_eq!(palette::hex(dark.accent), "#ffd166");
+        let css = palette::theme_css(&dark);
+        assert!(css.contains("--hc-accent:#ffd166;"));
+        assert!(css.contains("--hc-panel:rgba(0,0,0,0.42);"));
+    }
 
-    // If we have momentum, attempt to continue in that direction
-    if p.momentum > 0 && (p.dir_dx != 0 || p.dir_dy != 0) {
-        let nx = x as i8 + p.dir_dx;
-        let ny = y as i8 + p.dir_dy;
-        if nx >= 0 && ny >= 0 && (nx as u8) < level.width && (ny as u8) < level.height {
-            let nxu = nx as u8;
-            let nyu = ny as u8;
-            if !matches!(level.tile(nxu, nyu).obstacle, Some(ObstacleKind::Block)) {
-                return Some((nxu, nyu));
-            }
-        }
-        // blocked: drop momentum and fallthrough to normal logic
+    #[test]
+    fn test_color_schemes() {
+        use palette::{Palette, scheme_palette};
+        assert_eq!(scheme_palette("dark", true), Some(Palette::DARK));
+        assert_eq!(scheme_palette("light", false), Some(Palette::LIGHT));
+        assert_eq!(scheme_palette("auto", true), Some(Palette::LIGHT));
+        assert_eq!(scheme_palette("auto", false), Some(Palette::DARK));
+        assert_eq!(scheme_palette("sepia", false), None);
+        assert!(Palette::LIGHT.is_light() && !Palette::DARK.is_light());
+        // Dark palette keeps its original background colour at rest.
+        assert_eq!(palette::pulse_fill(&Palette::DARK, 0), "rgb(33,29,27)");
+        assert_eq!(palette::pulse_fill(&Palette::LIGHT, 20), "rgb(234,229,218)");
     }
 
-    // Prefer moving onto an adjacent JumpPad if present (it will launch the piece)
-    let dirs: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
-    for (dx, dy) in dirs {
-        let nx = x as i8 + dx;
-        let ny = y as i8 + dy;
-        if nx < 0 || ny < 0 || (nx as u8) >= level.width || (ny as u8) >= level.height {
-            continue;
+    #[test]
+    fn test_practice_mastery_needs_on_beat_streak() {
+        static POOL: [(&str, &str); 2] = [("你", "ni3"), ("好", "hao3")];
+        practice::begin(&POOL);
+        practice::on_match("你", true);
+        practice::on_match("你", false);
+        practice::on_match("你", true);
+        practice::on_miss("你");
+        assert_eq!(practice::progress(), (0, 2));
+        for _ in 0..practice::MASTERY_STREAK {
+            practice::on_match("你", true);
         }
-        let tile = level.tile(nx as u8, ny as u8);
-        if let Some(ObstacleKind::JumpPad { .. }) = tile.obstacle {
-            return Some((nx as u8, ny as u8));
+        assert_eq!(practice::progress(), (1, 2));
+        assert_eq!(practice::narrow(POOL.to_vec()), vec![("好", "hao3")]);
+        // A miss no longer undoes a mastered character.
+        practice::on_miss("你");
+        assert!(!practice::all_mastered());
+        for _ in 0..practice::MASTERY_STREAK {
+            practice::on_match("好", true);
         }
+        assert!(practice::all_mastered());
+        // With everything mastered the full set keeps spawning.
+        assert_eq!(practice::narrow(POOL.to_vec()).len(), 2);
+        practice::clear();
+        assert_eq!(practice::progress(), (0, 0));
     }
 
-    // Fallback to greedy nearest-goal step
-    choose_next_step(level, x, y)
-}
-
-#[allow(dead_code)]
-fn choose_next_step(level: &LevelDesc, x: u8, y: u8) -> Option<(u8, u8)> {
-    // Greedy: pick neighbor (4-dir) that reduces Manhattan distance to ANY goal tile and is not blocked.
-    let dirs: [(i8, i8); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
-    let mut best: Option<((u8, u8), i32)> = None;
-    let dist_to_goal =
-        |gx: u8, gy: u8| -> i32 { (gx as i32 - x as i32).abs() + (gy as i32 - y as i32).abs() };
-    let cur_best_dist = level
-        .goal_region
-        .iter()
-        .map(|&(gx, gy)| dist_to_goal(gx, gy))
-        .min()
-        .unwrap_or(0);
-    for (dx, dy) in dirs {
-        let nx = x as i8 + dx;
-        let ny = y as i8 + dy;
-        if nx < 0 || ny < 0 || nx as u8 >= level.width || ny as u8 >= level.height {
-            continue;
-        }
-        let nxu = nx as u8;
-        let nyu = ny as u8;
-        // skip blocked
-        if matches!(level.tile(nxu, nyu).obstacle, Some(ObstacleKind::Block)) {
-            continue;
-        }
-        let nd = level
-            .goal_region
-            .iter()
-            .map(|&(gx, gy)| (gx as i32 - nx as i32).abs() + (gy as i32 - ny as i32).abs())
-            .min()
-            .unwrap_or(i32::MAX);
-        if nd <= cur_best_dist {
-            // allow equal to avoid deadlock
-            if let Some((_, bestd)) = &best {
-                if nd > *bestd {
-                    continue;
-                }
-            }
-            best = Some(((nxu, nyu), nd));
-        }
+    #[test]
+    fn test_quiz_question_has_one_matching_answer() {
+        let pool = [("你", "ni3"), ("泥", "ni2"), ("尼", "ni2"), ("好", "hao3")];
+        // Always take the first candidate: prompt 你, then distractors in
+        // swap_remove order, skipping the second ni2.
+        let q = quiz::build_question(&pool, false, |_| 0).unwrap();
+        assert_eq!(q.prompt, ("你", "ni3"));
+        assert_eq!(q.choices.len(), 3);
+        assert_eq!(q.label(q.correct), "ni3");
+        let labels: Vec<_> = (0..3).map(|i| q.label(i)).collect();
+        assert_eq!(labels.iter().filter(|l| **l == "ni2").count(), 1);
+        // A pinyin prompt with no homophone in the pool gets all four.
+        let q = quiz::build_question(&pool, true, |n| n - 1).unwrap();
+        assert_eq!(q.prompt, ("好", "hao3"));
+        assert_eq!(q.choices.len(), quiz::CHOICES);
+        assert_eq!(q.label(q.correct), q.prompt.0);
+        // Prompt ni2 (泥): 尼 also reads ni2, so it is no distractor.
+        let q = quiz::build_question(&pool, true, |n| if n == 4 { 1 } else { n - 1 }).unwrap();
+        assert_eq!(q.prompt, ("泥", "ni2"));
+        assert_eq!(q.choices.len(), 3);
+        assert!(!q.choices.contains(&("尼", "ni2")));
+        assert!(quiz::build_question(&pool[1..3], true, |_| 0).is_none());
+        assert!(quiz::build_question(&pool[1..3], false, |_| 0).is_none());
     }
-    best.map(|(pos, _)| pos)
-}
 
-fn line(ctx: &CanvasRenderingContext2d, x1: f64, y1: f64, x2: f64, y2: f64) {
-    ctx.begin_path();
-    ctx.move_to(x1, y1);
-    ctx.line_to(x2, y2);
-    ctx.stroke();
-}
+    #[test]
+    fn test_quiz_hit_test_finds_answer_tiles() {
+        let (x, y, w, h) = quiz::choice_rect(2, 4, 640.0, 640.0);
+        assert_eq!(
+            quiz::hit_test(x + w / 2.0, y + h / 2.0, 4, 640.0, 640.0),
+            Some(2)
+        );
+        assert_eq!(quiz::hit_test(x - 1.0, y + 1.0, 4, 640.0, 640.0), None);
+        assert_eq!(quiz::hit_test(320.0, 100.0, 4, 640.0, 640.0), None);
+        assert!(y + h <= 640.0);
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_marked_pinyin_places_diacritics() {
+        assert_eq!(flashcards::marked_pinyin("ni3hao3"), "nǐhǎo");
+        assert_eq!(flashcards::marked_pinyin("xue2sheng5"), "xuésheng");
+        assert_eq!(flashcards::marked_pinyin("lv4"), "lǜ");
+        assert_eq!(flashcards::marked_pinyin("gou3"), "gǒu");
+        assert_eq!(flashcards::marked_pinyin("shui3"), "shuǐ");
+        assert_eq!(flashcards::marked_pinyin("liu2"), "liú");
+        assert_eq!(flashcards::card_ms(10.0, 4), 2500.0);
+        assert_eq!(flashcards::card_ms(1.0, 10), 600.0);
+    }
 
-    // Helper to build a simple LevelDesc with optional blocked tiles and leaked static slices
-    fn make_level_with_tiles(
-        width: u8,
-        height: u8,
-        obstacle_positions: &[(u8, u8)],
-        goals: &[(u8, u8)],
-    ) -> LevelDesc {
-        let mut tiles_vec = vec![
-            TileDesc {
-                obstacle: None,
-                modifier: None
-            };
-            (width as usize * height as usize)
+    #[test]
+    fn test_timing_rows_put_slowest_first() {
+        let log = [
+            ("你", "ni3", Some(400.0)),
+            ("好", "hao3", Some(1500.0)),
+            ("你", "ni3", None),
+            ("你", "ni3", Some(800.0)),
+            ("猫", "mao1", None),
         ];
-        for &(ox, oy) in obstacle_positions.iter() {
-            let idx = oy as usize * width as usize + ox as usize;
-            tiles_vec[idx] = TileDesc {
-                obstacle: Some(ObstacleKind::Block),
-                modifier: None,
-            };
-        }
-        let tiles_static: &'static [TileDesc] = Box::leak(tiles_vec.into_boxed_slice());
-        let spawn_static: &'static [(u8, u8)] = Box::leak(vec![(0u8, 0u8)].into_boxed_slice());
-        let goal_static: &'static [(u8, u8)] = Box::leak(goals.to_vec().into_boxed_slice());
-        LevelDesc {
-            name: "test-level",
-            width,
-            height,
-            bpm: 120.0,
-            tiles: tiles_static,
-            spawn_points: spawn_static,
-            goal_region: goal_static,
-        }
+        assert_eq!(
+            timing::summarize(&log),
+            vec![
+                ("猫", "mao1", 0, 1, None),
+                ("好", "hao3", 1, 0, Some(1500.0)),
+                ("你", "ni3", 2, 1, Some(600.0)),
+            ]
+        );
+        assert_eq!(
+            timing::timing_json(&log[1..3]),
+            r#"[{"hanzi":"好","pinyin":"hao3","hit":true,"ms":1500},{"hanzi":"你","pinyin":"ni3","hit":false,"ms":null}]"#
+        );
+        // Runtime dataset strings are free text.
+        assert_eq!(
+            timing::timing_json(&[("\"猫\\", "mao1", None)]),
+            r#"[{"hanzi":"\"猫\\","pinyin":"mao1","hit":false,"ms":null}]"#
+        );
     }
 
     #[test]
-    fn test_beatclock() {
-        let start = 1_000.0;
-        let clock = BeatClock::new(120.0, start);
-        assert!((clock.beat_duration_ms() - 500.0).abs() < 1e-6);
-        assert!((clock.current_beat(start) - 0.0).abs() < 1e-9);
-        assert!((clock.current_beat(start + 500.0) - 1.0).abs() < 1e-6);
+    fn test_countdown_steps_through_go() {
+        assert_eq!(countdown_label(COUNTDOWN_MS), Some("3"));
+        assert_eq!(countdown_label(COUNTDOWN_MS * 0.6), Some("2"));
+        assert_eq!(countdown_label(COUNTDOWN_MS * 0.3), Some("1"));
+        assert_eq!(countdown_label(1.0), Some("Go!"));
+        assert_eq!(countdown_label(0.0), None);
     }
 
     #[test]
-    fn test_level_tile_access() {
-        let lvl = make_level_with_tiles(2, 2, &[], &[(1, 1)]);
-        let t = lvl.tile(1, 0);
-        assert!(t.obstacle.is_none());
-        let g = lvl.tile(1, 1);
-        assert!(g.obstacle.is_none());
+    fn test_numpad_direction_follows_keypad_layout() {
+        assert_eq!(numpad_direction("7"), Some((-1, -1)));
+        assert_eq!(numpad_direction("8"), Some((0, -1)));
+        assert_eq!(numpad_direction("6"), Some((1, 0)));
+        assert_eq!(numpad_direction("5"), Some((0, 0)));
+        assert_eq!(numpad_direction("1"), Some((-1, 1)));
+        assert_eq!(numpad_direction("3"), Some((1, 1)));
+        assert_eq!(numpad_direction("0"), None);
+        assert_eq!(numpad_direction("a"), None);
+        assert_eq!(numpad_direction("10"), None);
     }
 
     #[test]
-    fn test_choose_next_step_prefers_unblocked_direction() {
-        // Create 3x3 level with (1,0) blocked so (0,0) should move down to (0,1)
-        let lvl = make_level_with_tiles(3, 3, &[(1, 0)], &[(2, 2)]);
-        let step = choose_next_step(&lvl, 0, 0);
-        assert_eq!(step, Some((0, 1)));
+    fn test_word_pinyin_joins_two_tiles() {
+        let pools: [&[(&str, &str)]; 2] = [&[("你", "ni3")], crate::MULTI_HANZI];
+        assert_eq!(word_pinyin(&pools, "你", "好"), Some("ni3hao3"));
+        assert_eq!(word_pinyin(&pools, "电", "风扇"), Some("dian4feng1shan4"));
+        assert_eq!(word_pinyin(&pools, "好", "你"), None);
+        let own: [&[(&str, &str)]; 1] = [&[("猫猫", "mao1mao1")]];
+        assert_eq!(word_pinyin(&own, "猫", "猫"), Some("mao1mao1"));
+    }
+
+    #[test]
+    fn test_flood_same_follows_orthogonal_runs() {
+        let a = Some(("水", "shui3"));
+        let b = Some(("火", "huo3"));
+        // 水 水 火
+        // 火 水 -
+        // 水 - 水
+        let grid = [a, a, b, b, a, None, a, None, a];
+        assert_eq!(flood_same(&grid, 3, 3, 0), vec![0, 1, 4]);
+        assert_eq!(flood_same(&grid, 3, 3, 2), vec![2]);
+        // Diagonal neighbours are not connected.
+        assert_eq!(flood_same(&grid, 3, 3, 8), vec![8]);
+        assert_eq!(flood_same(&grid, 3, 3, 5), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn test_combo_multiplier_grows_by_tenths() {
+        assert!((combo_multiplier(0) - 1.0).abs() < 1e-9);
+        assert!((combo_multiplier(5) - 1.5).abs() < 1e-9);
+        assert!((combo_multiplier(10) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_wrap_level_steps_across_edges() {
+        let mut lvl = make_level_with_tiles(4, 3, &[], &[(0, 1)]);
+        // Non-wrapping levels reject out-of-bounds steps.
+        assert_eq!(step_target(&lvl, 3, 1, 1, 0, &[], 0), None);
+        lvl.wrap = true;
+        // Off the right edge onto the left, and off the top onto the bottom.
+        assert_eq!(step_target(&lvl, 3, 1, 1, 0, &[], 0), Some((0, 1)));
+        assert_eq!(step_target(&lvl, 2, 0, 0, -1, &[], 0), Some((2, 2)));
+        // The goal at the left edge is one step away across the seam.
+        assert_eq!(choose_next_step(&lvl, 3, 1, &[], 0), Some((0, 1)));
+    }
+
+    #[test]
+    fn test_moving_block_follows_path_by_beat() {
+        static PATH: [(u8, u8); 3] = [(0, 1), (1, 1), (2, 1)];
+        assert_eq!(moving_block_tile(&PATH, 2, 0), Some((0, 1)));
+        assert_eq!(moving_block_tile(&PATH, 2, 1), Some((0, 1)));
+        assert_eq!(moving_block_tile(&PATH, 2, 2), Some((1, 1)));
+        assert_eq!(moving_block_tile(&PATH, 2, 6), Some((0, 1))); // wraps
+        assert_eq!(moving_block_tile(&[], 2, 6), None);
+        // Only the first beat on a waypoint is an arrival; the block then rests.
+        assert!(moving_block_arrives(2, 2));
+        assert!(!moving_block_arrives(2, 3));
+        assert!(moving_block_arrives(1, 3));
+        // Resting during most of the period, gliding at the end.
+        assert_eq!(moving_block_pos(&PATH, 2, 0.5), Some((0.0, 1.0)));
+        let (gx, _) = moving_block_pos(&PATH, 2, 1.75).unwrap();
+        assert!((gx - 0.5).abs() < 1e-9);
+
+        let mut tiles = vec![TileDesc::default(); 9];
+        tiles[3].obstacle = Some(ObstacleKind::MovingBlock {
+            path: &PATH,
+            period_beats: 2,
+        });
+        let lvl = LevelDesc {
+            name: "moving-test",
+            width: 3,
+            height: 3,
+            bpm: 120.0,
+            subdivision: 1,
+            wrap: false,
+            chain_clear: false,
+            tiles: Box::leak(tiles.into_boxed_slice()),
+            spawn_points: &[(0, 0)],
+            goal_region: &[(2, 2)],
+        };
+        assert!(tile_blocked(&lvl, 0, 1, &[], 0));
+        assert!(!tile_blocked(&lvl, 1, 1, &[], 0));
+        assert!(tile_blocked(&lvl, 1, 1, &[], 2));
+        assert!(!tile_blocked(&lvl, 0, 1, &[], 2));
     }
 
     #[test]
@@ -1821,7 +5455,7 @@ mod tests {
         p.dir_dx = 1;
         p.dir_dy = 0;
         p.momentum = 1;
-        let next = choose_next_for_piece(&lvl, &p);
+        let next = choose_next_for_piece(&lvl, &p, &[], 0);
         assert_eq!(next, Some((2, 1)));
     }
 }
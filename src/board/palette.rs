@@ -0,0 +1,240 @@
+// Colour palette shared by the canvas and the DOM overlays, so the game can be
+// reskinned without recompiling. The canvas reads it every frame; overlays and
+// the page use CSS custom properties (`--hc-bg`, `--hc-accent`, ...) from a
+// `<style id="hc-theme">` element that is rewritten whenever it changes.
+//   set_palette('{"accent": "#7ee787", "background": "#101820"}')
+// overrides any subset of the colours below with `#rrggbb` values.
+// `set_color_scheme("dark" | "light" | "auto")` switches to the built-in dark
+// or light palette ("auto" follows `prefers-color-scheme`).
+use js_sys::{JSON, Object, Reflect};
+use std::cell::Cell;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+pub(super) type Rgb = (u8, u8, u8);
+
+const STYLE_ID: &str = "hc-theme";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub(super) struct Palette {
+    pub(super) page: Rgb,       // page background behind the board
+    pub(super) background: Rgb, // canvas background (the beat pulse shifts it)
+    pub(super) grid: Rgb,       // board grid lines
+    pub(super) glyph: Rgb,      // hanzi fill
+    pub(super) outline: Rgb,    // hanzi outline, HUD shadows
+    pub(super) accent: Rgb,     // score, tone marks, target brackets, banners
+    pub(super) danger: Rgb,     // hearts
+    pub(super) text: Rgb,       // secondary HUD text
+    pub(super) panel: Rgb,      // HUD panel fill (drawn translucent)
+    pub(super) border: Rgb,     // HUD panel border
+}
+
+impl Palette {
+    pub(super) const DARK: Palette = Palette {
+        page: (0x11, 0x11, 0x11),
+        background: (33, 29, 27),
+        grid: (0x22, 0x22, 0x22),
+        glyph: (0xff, 0xff, 0xff),
+        outline: (0, 0, 0),
+        accent: (0xff, 0xd1, 0x66),
+        danger: (0xff, 0x4d, 0x4d),
+        text: (0xcf, 0xcf, 0xcf),
+        panel: (0, 0, 0),
+        border: (0x33, 0x33, 0x33),
+    };
+
+    /// Light variant: dark glyphs with a light outline, deeper accent and
+    /// danger tones so they keep their contrast on a pale board.
+    pub(super) const LIGHT: Palette = Palette {
+        page: (0xef, 0xe9, 0xdd),
+        background: (0xf4, 0xef, 0xe4),
+        grid: (0xcf, 0xc6, 0xb4),
+        glyph: (0x1d, 0x1a, 0x16),
+        outline: (0xff, 0xff, 0xff),
+        accent: (0xb0, 0x6a, 0x00),
+        danger: (0xd0, 0x2f, 0x2f),
+        text: (0x4a, 0x45, 0x40),
+        panel: (0xff, 0xff, 0xff),
+        border: (0xbb, 0xb2, 0xa2),
+    };
+
+    /// Whether this is a light palette (pale canvas background).
+    pub(super) fn is_light(&self) -> bool {
+        let (r, g, b) = self.background;
+        (r as u32 * 299 + g as u32 * 587 + b as u32 * 114) / 1000 > 128
+    }
+
+    fn slot(&mut self, name: &str) -> Option<&mut Rgb> {
+        Some(match name {
+            "page" => &mut self.page,
+            "background" => &mut self.background,
+            "grid" => &mut self.grid,
+            "glyph" => &mut self.glyph,
+            "outline" => &mut self.outline,
+            "accent" => &mut self.accent,
+            "danger" => &mut self.danger,
+            "text" => &mut self.text,
+            "panel" => &mut self.panel,
+            "border" => &mut self.border,
+            _ => return None,
+        })
+    }
+}
+
+thread_local! {
+    static PALETTE: Cell<Palette> = const { Cell::new(Palette::DARK) };
+}
+
+/// The active palette.
+pub(super) fn current() -> Palette {
+    PALETTE.get()
+}
+
+/// Canvas background for a beat pulse of `lift` (0..40): lighter on a dark
+/// palette, darker on a light one.
+pub(super) fn pulse_fill(p: &Palette, lift: i32) -> String {
+    let lift = if p.is_light() { -lift / 2 } else { lift };
+    let (r, g, b) = p.background;
+    let shade = |c: u8| (c as i32 + lift).clamp(0, 255);
+    format!("rgb({},{},{})", shade(r), shade(g), shade(b))
+}
+
+pub(super) fn hex((r, g, b): Rgb) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(super) fn rgba((r, g, b): Rgb, alpha: f64) -> String {
+    format!("rgba({r},{g},{b},{alpha})")
+}
+
+/// Parse `#rrggbb` (or the `#rgb` shorthand).
+pub(super) fn parse_hex(s: &str) -> Option<Rgb> {
+    let digits = s.strip_prefix('#')?;
+    if !digits.is_ascii() {
+        return None;
+    }
+    let channel = |h: &str| u8::from_str_radix(h, 16).ok();
+    match digits.len() {
+        6 => Some((
+            channel(&digits[0..2])?,
+            channel(&digits[2..4])?,
+            channel(&digits[4..6])?,
+        )),
+        3 => {
+            let short = |i: usize| channel(&digits[i..i + 1]).map(|v| v * 17);
+            Some((short(0)?, short(1)?, short(2)?))
+        }
+        _ => None,
+    }
+}
+
+/// Stylesheet exposing `p` as custom properties (also usable by the host
+/// page), plus the page background and the mascot's drop shadow.
+pub(super) fn theme_css(p: &Palette) -> String {
+    format!(
+        ":root{{--hc-page:{};--hc-bg:{};--hc-grid:{};--hc-glyph:{};--hc-outline:{};\
+         --hc-accent:{};--hc-danger:{};--hc-text:{};--hc-panel:{};--hc-border:{};}}\
+         body{{background:var(--hc-page);color:{};}}\
+         .hc-cat-container{{filter:drop-shadow(0 2px 6px {});}}",
+        hex(p.page),
+        hex(p.background),
+        hex(p.grid),
+        hex(p.glyph),
+        hex(p.outline),
+        hex(p.accent),
+        hex(p.danger),
+        hex(p.text),
+        rgba(p.panel, 0.42),
+        hex(p.border),
+        if p.is_light() { "#222" } else { "#eee" },
+        rgba(p.outline, 0.7),
+    )
+}
+
+/// Create or refresh the theme stylesheet for the active palette.
+pub(super) fn inject_css() {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    let style = match doc.get_element_by_id(STYLE_ID) {
+        Some(el) => el,
+        None => {
+            let Ok(el) = doc.create_element("style") else {
+                return;
+            };
+            el.set_id(STYLE_ID);
+            let Some(body) = doc.body() else {
+                return;
+            };
+            body.append_child(&el).ok();
+            el
+        }
+    };
+    style.set_text_content(Some(&theme_css(&current())));
+}
+
+/// Replace the active palette and refresh the stylesheet.
+pub(super) fn set(p: Palette) {
+    PALETTE.set(p);
+    inject_css();
+}
+
+/// Override palette colours from a JSON object of `#rrggbb` strings keyed by
+/// page, background, grid, glyph, outline, accent, danger, text, panel or border.
+/// Unknown keys or malformed colours reject the whole object.
+#[wasm_bindgen]
+pub fn set_palette(json: &str) -> Result<(), JsValue> {
+    let err = |m: &str| JsValue::from_str(m);
+    let v = JSON::parse(json)?;
+    if !v.is_object() {
+        return Err(err("expected a JSON object"));
+    }
+    let mut p = current();
+    for key in Object::keys(&v.clone().into()).iter() {
+        let name = key.as_string().unwrap_or_default();
+        let value = Reflect::get(&v, &key)?.as_string().unwrap_or_default();
+        let slot = p
+            .slot(&name)
+            .ok_or_else(|| err(&format!("unknown palette colour '{name}'")))?;
+        *slot = parse_hex(&value)
+            .ok_or_else(|| err(&format!("{name}: expected #rrggbb, got '{value}'")))?;
+    }
+    set(p);
+    Ok(())
+}
+
+/// Built-in palette for `scheme`; "auto" asks the browser whether the user
+/// prefers a light scheme. `None` for unknown names.
+pub(super) fn scheme_palette(scheme: &str, prefers_light: bool) -> Option<Palette> {
+    match scheme {
+        "dark" => Some(Palette::DARK),
+        "light" => Some(Palette::LIGHT),
+        "auto" if prefers_light => Some(Palette::LIGHT),
+        "auto" => Some(Palette::DARK),
+        _ => None,
+    }
+}
+
+fn prefers_light() -> bool {
+    window()
+        .and_then(|w| {
+            w.match_media("(prefers-color-scheme: light)")
+                .ok()
+                .flatten()
+        })
+        .is_some_and(|m| m.matches())
+}
+
+/// Switch to the built-in "dark" or "light" palette, or pick one from the
+/// browser's `prefers-color-scheme` with "auto". Replaces any colours set
+/// through `set_palette`. Returns `false` for unknown schemes.
+#[wasm_bindgen]
+pub fn set_color_scheme(scheme: &str) -> bool {
+    match scheme_palette(scheme, prefers_light()) {
+        Some(p) => {
+            set(p);
+            true
+        }
+        None => false,
+    }
+}
@@ -0,0 +1,89 @@
+// In-game pause menu, opened with Escape. A full-screen overlay (so clicks
+// never reach the board behind it) with Resume, Restart and Toggle Hints.
+// Keys other than Escape are already ignored while the board is paused.
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::{BOARD_STATE, resume_board, start_board_mode_at, toggle_hints};
+
+const MENU_ID: &str = "hc-pause-menu";
+
+type Handler = Closure<dyn FnMut(web_sys::MouseEvent)>;
+
+thread_local! {
+    // Button handlers of the last menu shown. Not dropped by `hide`, which
+    // runs inside Resume and Restart; the next `show` replaces them.
+    static HANDLERS: RefCell<Vec<Handler>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Show the menu over the (already paused) board.
+pub(super) fn show() {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    hide();
+    HANDLERS.with(|h| h.borrow_mut().clear());
+    let Ok(overlay) = doc.create_element("div") else {
+        return;
+    };
+    overlay.set_id(MENU_ID);
+    overlay.set_attribute("style", "position:fixed; inset:0; background:rgba(0,0,0,0.45); display:flex; align-items:center; justify-content:center; z-index:70;").ok();
+    let Ok(panel) = doc.create_element("div") else {
+        return;
+    };
+    panel.set_attribute("style", "min-width:220px; padding:18px 22px; background:rgba(12,12,12,0.94); border:2px solid #333; border-radius:14px; font-family:'Fira Code', monospace; color:#ffd166; display:flex; flex-direction:column; gap:8px;").ok();
+    panel.set_inner_html(
+        "<div style='font-size:20px; text-align:center; margin-bottom:6px;'>Paused</div>",
+    );
+    let buttons: [(&str, fn()); 3] = [
+        ("Resume", resume_board),
+        ("Restart", restart),
+        ("Toggle Hints", toggle_hints),
+    ];
+    for (label, action) in buttons {
+        let Ok(btn) = doc.create_element("button") else {
+            continue;
+        };
+        btn.set_text_content(Some(label));
+        btn.set_attribute("style", "font:inherit; font-size:15px; padding:6px 10px; background:#222; color:#ffd166; border:1px solid #555; border-radius:6px; cursor:pointer;").ok();
+        let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+            action();
+        }) as Box<dyn FnMut(_)>);
+        btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+            .ok();
+        HANDLERS.with(|h| h.borrow_mut().push(closure));
+        panel.append_child(&btn).ok();
+    }
+    overlay.append_child(&panel).ok();
+    if let Some(body) = doc.body() {
+        body.append_child(&overlay).ok();
+    }
+}
+
+/// Whether the menu is showing.
+pub(super) fn is_open() -> bool {
+    window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(MENU_ID))
+        .is_some()
+}
+
+/// Remove the menu if it is open.
+pub(super) fn hide() {
+    if let Some(m) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(MENU_ID))
+    {
+        m.remove();
+    }
+}
+
+/// Start the current level over (this also unpauses and closes the menu).
+fn restart() {
+    let level_index = BOARD_STATE.with(|cell| cell.borrow().as_ref().map(|s| s.level_index));
+    if let Some(i) = level_index {
+        start_board_mode_at(i).ok();
+    }
+}
@@ -0,0 +1,105 @@
+// Powerup shop: coins are earned from score (1 per 100 points) and spent on
+// temporary effects via `purchase_powerup(kind)`.
+//   "shield" - absorbs the next life loss
+//   "slow"   - halves the tempo for SLOW_MS
+//   "clear"  - empties every (non-pinned) tile so the board refills fresh,
+//              without breaking the combo
+use web_sys::window;
+
+use super::{BOARD_STATE, BoardState, apply_tempo};
+
+/// Points of score per coin earned.
+pub(super) const POINTS_PER_COIN: i64 = 100;
+/// Duration of the "slow" powerup (ms).
+pub(super) const SLOW_MS: f64 = 10_000.0;
+/// Tempo factor while "slow" is active.
+pub(super) const SLOW_FACTOR: f64 = 0.5;
+
+/// Coin price of a powerup kind, or `None` for unknown kinds.
+pub(super) fn powerup_cost(kind: &str) -> Option<i64> {
+    match kind {
+        "shield" => Some(6),
+        "slow" => Some(8),
+        "clear" => Some(10),
+        _ => None,
+    }
+}
+
+/// Deduct `cost` from `coins` if affordable.
+pub(super) fn try_spend(coins: &mut i64, cost: i64) -> bool {
+    if *coins < cost {
+        return false;
+    }
+    *coins -= cost;
+    true
+}
+
+/// Convert score gained since the `last_score` watermark into coins, one per
+/// `POINTS_PER_COIN`; leftover points stay below the watermark for later.
+pub(super) fn accrue_coins(score: i64, last_score: &mut i64, coins: &mut i64) {
+    let earned = (score - *last_score).max(0) / POINTS_PER_COIN;
+    if earned > 0 {
+        *coins += earned;
+        *last_score += earned * POINTS_PER_COIN;
+    }
+}
+
+/// Buy and activate a powerup on the running board. Returns `false` for unknown
+/// kinds, when no board is running, when coins are short, or when the effect
+/// would do nothing (no coins are spent then).
+pub(crate) fn purchase_powerup(kind: &str) -> bool {
+    let Some(cost) = powerup_cost(kind) else {
+        return false;
+    };
+    BOARD_STATE.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let Some(state) = cell.as_mut() else {
+            return false;
+        };
+        if state.game_over || state.won {
+            return false;
+        }
+        let mut coins = state.coins;
+        if !try_spend(&mut coins, cost) {
+            return false;
+        }
+        let now = window()
+            .and_then(|w| w.performance())
+            .map(|p| p.now())
+            .unwrap_or(0.0);
+        if !activate(state, kind, now) {
+            return false;
+        }
+        state.coins = coins;
+        true
+    })
+}
+
+fn activate(state: &mut BoardState, kind: &str, now: f64) -> bool {
+    match kind {
+        "shield" => {
+            state.shield += 1;
+            true
+        }
+        "slow" => {
+            state.slow_until_ms = now + SLOW_MS;
+            apply_tempo(state, now);
+            true
+        }
+        "clear" => {
+            let lvl = state.level;
+            let mut cleared = false;
+            for (cell, tile) in state.grid.iter_mut().zip(lvl.tiles) {
+                if cell.is_some() && tile.fixed_hanzi.is_none() {
+                    *cell = None;
+                    cleared = true;
+                }
+            }
+            if cleared {
+                state.clear_sweep_ms = Some(now);
+            }
+            cleared
+        }
+        _ => false,
+    }
+}
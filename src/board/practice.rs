@@ -0,0 +1,131 @@
+// Practice mode: drill a fixed set of characters until each is mastered.
+// `start_practice(json)` takes the same `{hanzi, pinyin, multi}` array as
+// `set_dataset` and restarts level 1 with tiles drawn only from that set. A
+// character is mastered after MASTERY_STREAK on-beat matches in a row; an
+// off-beat match keeps the streak, while a miss aimed at it resets it.
+// Mastered characters stop spawning (unless nothing else is left), and the
+// session ends with a "Mastered!" banner once every character is mastered.
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::dataset::{self, Pool};
+use super::{BOARD_STATE, set_level, start_board_mode_at};
+
+/// On-beat matches in a row that master a character.
+pub(super) const MASTERY_STREAK: u32 = 3;
+
+struct Drill {
+    hanzi: &'static str,
+    streak: u32,
+}
+
+thread_local! {
+    static POOL: Cell<Option<Pool>> = const { Cell::new(None) };
+    static DRILLS: RefCell<Vec<Drill>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Spawn pool of the running practice session, if any.
+pub(super) fn pool() -> Option<Pool> {
+    POOL.get()
+}
+
+/// Start drilling `pool`; every streak starts at zero.
+pub(super) fn begin(pool: Pool) {
+    POOL.set(Some(pool));
+    DRILLS.with(|d| {
+        *d.borrow_mut() = pool
+            .iter()
+            .map(|&(hanzi, _)| Drill { hanzi, streak: 0 })
+            .collect()
+    });
+}
+
+/// Leave practice mode (a new run starts).
+pub(super) fn clear() {
+    POOL.set(None);
+    DRILLS.with(|d| d.borrow_mut().clear());
+}
+
+fn is_mastered(hanzi: &str) -> bool {
+    DRILLS.with(|d| {
+        d.borrow()
+            .iter()
+            .any(|e| e.hanzi == hanzi && e.streak >= MASTERY_STREAK)
+    })
+}
+
+/// `pool` without mastered characters, or unchanged if that would leave
+/// nothing to spawn.
+pub(super) fn narrow(pool: Vec<(&'static str, &'static str)>) -> Vec<(&'static str, &'static str)> {
+    if POOL.get().is_none() {
+        return pool;
+    }
+    let open: Vec<_> = pool
+        .iter()
+        .copied()
+        .filter(|(h, _)| !is_mastered(h))
+        .collect();
+    if open.is_empty() { pool } else { open }
+}
+
+/// A tile showing `hanzi` was matched, on the beat or not.
+pub(super) fn on_match(hanzi: &str, on_beat: bool) {
+    if !on_beat {
+        return;
+    }
+    DRILLS.with(|d| {
+        if let Some(e) = d.borrow_mut().iter_mut().find(|e| e.hanzi == hanzi) {
+            e.streak = (e.streak + 1).min(MASTERY_STREAK);
+        }
+    });
+}
+
+/// A submission aimed at `hanzi` missed; an unmastered streak on it
+/// starts over.
+pub(super) fn on_miss(hanzi: &str) {
+    DRILLS.with(|d| {
+        if let Some(e) = d
+            .borrow_mut()
+            .iter_mut()
+            .find(|e| e.hanzi == hanzi && e.streak < MASTERY_STREAK)
+        {
+            e.streak = 0;
+        }
+    });
+}
+
+/// `(mastered, total)` characters of the running session.
+pub(super) fn progress() -> (usize, usize) {
+    DRILLS.with(|d| {
+        let d = d.borrow();
+        let done = d.iter().filter(|e| e.streak >= MASTERY_STREAK).count();
+        (done, d.len())
+    })
+}
+
+/// Whether a practice session is running and every character is mastered.
+pub(super) fn all_mastered() -> bool {
+    let (done, total) = progress();
+    total > 0 && done == total
+}
+
+/// Restart level 1 drilling only the given characters (a JSON array of
+/// `{hanzi, pinyin, multi}` as for `set_dataset`) until all are mastered.
+#[wasm_bindgen]
+pub fn start_practice(chars_json: &str) -> Result<(), JsValue> {
+    let entries = dataset::parse_entries(chars_json)?;
+    start_board_mode_at(0)?;
+    begin(dataset::leak(entries));
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    // Rebuild the opening grid from the practice set.
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            set_level(state, 0, now, 0);
+        }
+    });
+    Ok(())
+}
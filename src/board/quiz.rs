@@ -0,0 +1,254 @@
+// Multiple-choice quiz for absolute beginners, separate from the board: one
+// large prompt at the top of the canvas and four answer tiles along the bottom,
+// exactly one of them correct. Prompts alternate between a hanzi (answer with
+// its pinyin) and a pinyin (answer with its hanzi). A correct click scores and
+// moves on to the next question; a wrong one flashes that tile red. Entries
+// come from the same pool as the opening board, so `set_dataset` applies.
+// `start_quiz()` replaces a running board; `stop_game()` ends the quiz.
+use std::cell::RefCell;
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, window};
+
+use super::{
+    FrameCallback, board_canvas, level_hanzi_pool, levels, lifecycle, palette, rand_index,
+};
+
+/// Maximum number of answer tiles.
+pub(super) const CHOICES: usize = 4;
+/// How long a wrong answer keeps flashing red.
+const WRONG_FLASH_MS: f64 = 400.0;
+const CANVAS_SIZE: f64 = 640.0;
+const GAP: f64 = 16.0;
+
+type Entry = (&'static str, &'static str);
+
+/// One question: `prompt` is shown, `choices[correct]` answers it.
+#[derive(Debug)]
+pub(super) struct Question {
+    pub(super) prompt: Entry,
+    /// Prompt shows the pinyin and the answers are hanzi (else the reverse).
+    pub(super) pinyin_prompt: bool,
+    pub(super) choices: Vec<Entry>,
+    pub(super) correct: usize,
+}
+
+impl Question {
+    /// Text shown on answer tile `i`.
+    pub(super) fn label(&self, i: usize) -> &'static str {
+        let (hanzi, pinyin) = self.choices[i];
+        if self.pinyin_prompt { hanzi } else { pinyin }
+    }
+}
+
+struct Quiz {
+    canvas: HtmlCanvasElement,
+    ctx: CanvasRenderingContext2d,
+    pool: Vec<Entry>,
+    question: Question,
+    asked: u32,
+    score: u32,
+    wrong: Option<(usize, f64)>,
+}
+
+thread_local! {
+    static QUIZ: RefCell<Option<Quiz>> = const { RefCell::new(None) };
+}
+
+/// Drop the running quiz, if any (the board or `stop_game()` took over).
+pub(super) fn clear() {
+    QUIZ.with(|q| q.borrow_mut().take());
+}
+
+/// Build a question around `pool[pick(len)]` with up to CHOICES - 1
+/// distractors whose answer text differs from the correct one and from each
+/// other, and whose prompt text differs from the prompt's (泥 is no wrong
+/// answer to "ni2" when 尼 is asked). `None` if no distractor is left.
+pub(super) fn build_question(
+    pool: &[Entry],
+    pinyin_prompt: bool,
+    mut pick: impl FnMut(usize) -> usize,
+) -> Option<Question> {
+    if pool.is_empty() {
+        return None;
+    }
+    let answer = |e: &Entry| if pinyin_prompt { e.0 } else { e.1 };
+    let prompt_text = |e: &Entry| if pinyin_prompt { e.1 } else { e.0 };
+    let prompt = pool[pick(pool.len())];
+    let mut choices = vec![prompt];
+    let mut rest: Vec<Entry> = pool.to_vec();
+    while choices.len() < CHOICES && !rest.is_empty() {
+        let e = rest.swap_remove(pick(rest.len()));
+        if prompt_text(&e) != prompt_text(&prompt)
+            && choices.iter().all(|c| answer(c) != answer(&e))
+        {
+            choices.push(e);
+        }
+    }
+    if choices.len() < 2 {
+        return None;
+    }
+    let correct = pick(choices.len());
+    choices.swap(0, correct);
+    Some(Question {
+        prompt,
+        pinyin_prompt,
+        choices,
+        correct,
+    })
+}
+
+/// Rectangle `(x, y, w, h)` of answer tile `i` of `count`, in a row along the
+/// bottom of a `width` x `height` canvas.
+pub(super) fn choice_rect(i: usize, count: usize, width: f64, height: f64) -> (f64, f64, f64, f64) {
+    let n = count.max(1) as f64;
+    let w = (width - GAP * (n + 1.0)) / n;
+    let h = w.min(height * 0.3);
+    (GAP + i as f64 * (w + GAP), height - GAP - h, w, h)
+}
+
+/// Answer tile under canvas point `(x, y)`, if any.
+pub(super) fn hit_test(x: f64, y: f64, count: usize, width: f64, height: f64) -> Option<usize> {
+    (0..count).find(|&i| {
+        let (rx, ry, rw, rh) = choice_rect(i, count, width, height);
+        x >= rx && x < rx + rw && y >= ry && y < ry + rh
+    })
+}
+
+fn next_question(quiz: &mut Quiz) {
+    quiz.asked += 1;
+    // Checked to have two distinct answers either way in `start_quiz`.
+    if let Some(q) = build_question(&quiz.pool, quiz.asked % 2 == 1, rand_index) {
+        quiz.question = q;
+    }
+    quiz.wrong = None;
+}
+
+fn on_click(quiz: &mut Quiz, x: f64, y: f64, now: f64) {
+    let (w, h) = (quiz.canvas.width() as f64, quiz.canvas.height() as f64);
+    let Some(i) = hit_test(x, y, quiz.question.choices.len(), w, h) else {
+        return;
+    };
+    if i == quiz.question.correct {
+        quiz.score += 1;
+        next_question(quiz);
+    } else {
+        quiz.wrong = Some((i, now));
+    }
+}
+
+fn render(quiz: &Quiz, now: f64) {
+    let pal = palette::current();
+    let ctx = &quiz.ctx;
+    let (w, h) = (quiz.canvas.width() as f64, quiz.canvas.height() as f64);
+    ctx.set_fill_style_str(&palette::hex(pal.background));
+    ctx.fill_rect(0.0, 0.0, w, h);
+
+    ctx.set_text_align("left");
+    ctx.set_font("bold 18px 'Fira Code', monospace");
+    ctx.set_fill_style_str(&palette::hex(pal.accent));
+    ctx.fill_text(&format!("Score {}", quiz.score), GAP, 30.0)
+        .ok();
+    ctx.set_fill_style_str(&palette::hex(pal.text));
+    ctx.fill_text(&format!("Question {}", quiz.asked + 1), GAP, 54.0)
+        .ok();
+
+    let q = &quiz.question;
+    let (hanzi, pinyin) = q.prompt;
+    ctx.set_text_align("center");
+    ctx.set_fill_style_str(&palette::hex(pal.glyph));
+    if q.pinyin_prompt {
+        ctx.set_font("bold 72px 'Fira Code', monospace");
+        ctx.fill_text(pinyin, w / 2.0, h * 0.38).ok();
+    } else {
+        ctx.set_font("140px 'Noto Serif SC', 'SimSun', serif");
+        ctx.fill_text(hanzi, w / 2.0, h * 0.42).ok();
+    }
+
+    let count = q.choices.len();
+    for i in 0..count {
+        let (x, y, cw, ch) = choice_rect(i, count, w, h);
+        ctx.set_fill_style_str(&palette::rgba(pal.panel, 0.42));
+        ctx.fill_rect(x, y, cw, ch);
+        if let Some((wrong, at)) = quiz.wrong
+            && wrong == i
+            && now - at < WRONG_FLASH_MS
+        {
+            let alpha = 0.6 * (1.0 - (now - at) / WRONG_FLASH_MS);
+            ctx.set_fill_style_str(&palette::rgba(pal.danger, alpha));
+            ctx.fill_rect(x, y, cw, ch);
+        }
+        ctx.set_stroke_style_str(&palette::hex(pal.border));
+        ctx.set_line_width(2.0);
+        ctx.stroke_rect(x, y, cw, ch);
+        ctx.set_fill_style_str(&palette::hex(pal.glyph));
+        if q.pinyin_prompt {
+            ctx.set_font("56px 'Noto Serif SC', 'SimSun', serif");
+        } else {
+            ctx.set_font("bold 24px 'Fira Code', monospace");
+        }
+        ctx.fill_text(q.label(i), x + cw / 2.0, y + ch / 2.0 + 14.0)
+            .ok();
+    }
+}
+
+fn start_quiz_loop() {
+    let f: FrameCallback = std::rc::Rc::new(std::cell::RefCell::new(None));
+    let g = f.clone();
+    *g.borrow_mut() = Some(Closure::wrap(Box::new(move |ts: f64| {
+        QUIZ.with(|cell| {
+            if let Some(quiz) = cell.borrow().as_ref() {
+                render(quiz, ts);
+            }
+        });
+        if lifecycle::is_running() {
+            lifecycle::request_frame(&f);
+        }
+    }) as Box<dyn FnMut(f64)>));
+    lifecycle::run_loop(g);
+}
+
+/// Stop the board (if running) and start a multiple-choice quiz on the canvas.
+/// Fails if the active pool has fewer than two distinct entries.
+#[wasm_bindgen]
+pub fn start_quiz() -> Result<(), JsValue> {
+    let pool = level_hanzi_pool(levels()[0]);
+    let err = || JsValue::from_str("quiz needs at least two distinct entries");
+    let question = build_question(&pool, false, rand_index).ok_or_else(err)?;
+    build_question(&pool, true, rand_index).ok_or_else(err)?;
+
+    lifecycle::stop_board_mode();
+    let doc = window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let canvas = board_canvas(&doc)?;
+    canvas.set_width(CANVAS_SIZE as u32);
+    canvas.set_height(CANVAS_SIZE as u32);
+    palette::inject_css();
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d")?.unwrap().dyn_into()?;
+
+    QUIZ.with(|q| {
+        *q.borrow_mut() = Some(Quiz {
+            canvas: canvas.clone(),
+            ctx,
+            pool,
+            question,
+            asked: 0,
+            score: 0,
+            wrong: None,
+        })
+    });
+    lifecycle::listen(&canvas, "click", |evt: web_sys::MouseEvent| {
+        let now = window()
+            .and_then(|w| w.performance())
+            .map(|p| p.now())
+            .unwrap_or(0.0);
+        QUIZ.with(|cell| {
+            if let Some(quiz) = cell.borrow_mut().as_mut() {
+                on_click(quiz, evt.offset_x() as f64, evt.offset_y() as f64, now);
+            }
+        });
+    })?;
+    start_quiz_loop();
+    Ok(())
+}
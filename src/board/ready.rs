@@ -0,0 +1,55 @@
+// Start screen for embedders that mount the game before the player is ready:
+// `mount_game()` puts up the (empty) board canvas with a "Ready? Click to
+// start" overlay and starts nothing. The click is a user gesture, so audio is
+// unlocked right there before the board and its metronome start.
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::{CanvasRenderingContext2d, window};
+
+use super::{DEFAULT_BOARD_SIZE, board_canvas, lifecycle, metronome, palette, start_board_mode};
+
+pub(super) const OVERLAY_ID: &str = "hc-ready";
+
+/// Show the canvas and the Ready overlay; a click on it starts the board.
+/// Replaces a running game.
+pub(crate) fn mount_board_mode() -> Result<(), JsValue> {
+    lifecycle::stop_board_mode();
+    let doc = window()
+        .and_then(|w| w.document())
+        .ok_or_else(|| JsValue::from_str("no document"))?;
+    let canvas = board_canvas(&doc)?;
+    canvas.set_width(DEFAULT_BOARD_SIZE as u32);
+    canvas.set_height(DEFAULT_BOARD_SIZE as u32);
+    palette::inject_css();
+    let ctx: CanvasRenderingContext2d = canvas.get_context("2d")?.unwrap().dyn_into()?;
+    ctx.set_fill_style_str(&palette::hex(palette::current().background));
+    ctx.fill_rect(0.0, 0.0, DEFAULT_BOARD_SIZE, DEFAULT_BOARD_SIZE);
+
+    let overlay = doc.create_element("button")?;
+    overlay.set_id(OVERLAY_ID);
+    overlay.set_attribute("style", "position:fixed; left:50%; top:38%; transform:translate(-50%,-50%); padding:18px 30px; background:var(--hc-panel); color:var(--hc-accent); border:2px solid var(--hc-border); border-radius:14px; font-family:'Fira Code', monospace; font-size:24px; cursor:pointer; z-index:30;").ok();
+    overlay.set_text_content(Some("Ready? Click to start"));
+    doc.body()
+        .ok_or_else(|| JsValue::from_str("no body"))?
+        .append_child(&overlay)?;
+    let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+        metronome::unlock();
+        hide();
+        start_board_mode().ok();
+    }) as Box<dyn FnMut(_)>);
+    overlay.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())?;
+    // Not a `lifecycle::listen` listener: starting detaches those, which would
+    // drop this closure while it runs. It goes away with the overlay.
+    closure.forget();
+    Ok(())
+}
+
+/// Remove the Ready overlay if it is up.
+pub(super) fn hide() {
+    if let Some(el) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(OVERLAY_ID))
+    {
+        el.remove();
+    }
+}
@@ -0,0 +1,193 @@
+// Keystroke recording and replay (feature = "serde_json").
+// While recording is on, every key the board accepts is logged with its offset
+// from the run start (pauses excluded), along with the input modes the run
+// started with. `play_replay(seed, json)` seeds the tile RNG for that one run,
+// restarts the recorded level with the recorded modes and feeds each key back
+// from the frame loop at its recorded offset. Recording with the same seed
+// (set via `seed_board_rng`) reproduces the run.
+//
+// Format: `{"level": <index>, "strict": <bool>, "forgiving": <bool>,
+// "ignore_tones": <bool>, "numpad_nav": <bool>,
+// "keys": [[<offset ms>, "<key>"], ...]}`. Missing mode flags read as off.
+use std::cell::RefCell;
+use std::collections::VecDeque;
+
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+
+use super::{BOARD_STATE, BoardState, RNG_SEED, handle_board_key, start_board_mode_at};
+
+/// Input modes that change how keys are handled, recorded with the log.
+#[derive(Clone, Copy, Default)]
+struct Modes {
+    strict: bool,
+    forgiving: bool,
+    ignore_tones: bool,
+    numpad_nav: bool,
+}
+
+impl Modes {
+    fn of(state: &BoardState) -> Self {
+        Modes {
+            strict: state.strict_mode,
+            forgiving: state.forgiving,
+            ignore_tones: state.ignore_tones,
+            numpad_nav: state.numpad_nav,
+        }
+    }
+
+    fn apply(self, state: &mut BoardState) {
+        state.strict_mode = self.strict;
+        state.forgiving = self.forgiving;
+        state.ignore_tones = self.ignore_tones;
+        state.numpad_nav = self.numpad_nav;
+    }
+}
+
+#[derive(Default)]
+struct Replay {
+    recording: bool,
+    level: usize,
+    modes: Modes,
+    /// Run start used for recorded offsets.
+    record_start_ms: f64,
+    log: Vec<(f64, String)>,
+    /// Run start and pending keys of a replay being played back.
+    play_start_ms: f64,
+    pending: VecDeque<(f64, String)>,
+    /// Set while `play_replay` restarts the board so its run start does not
+    /// count as a fresh recording.
+    starting_playback: bool,
+}
+
+thread_local! {
+    static REPLAY: RefCell<Replay> = RefCell::new(Replay::default());
+}
+
+/// Start or stop logging keystrokes. A log starts with the next run (level
+/// pick); stopping keeps the log for `export_replay()`.
+#[wasm_bindgen]
+pub fn set_replay_recording(on: bool) {
+    REPLAY.with(|r| r.borrow_mut().recording = on);
+}
+
+/// The current keystroke log as JSON (see the module docs for the format).
+#[wasm_bindgen]
+pub fn export_replay() -> String {
+    REPLAY.with(|r| {
+        let r = r.borrow();
+        let keys: Vec<Value> = r.log.iter().map(|(t, k)| json!([t, k])).collect();
+        json!({
+            "level": r.level,
+            "strict": r.modes.strict,
+            "forgiving": r.modes.forgiving,
+            "ignore_tones": r.modes.ignore_tones,
+            "numpad_nav": r.modes.numpad_nav,
+            "keys": keys,
+        })
+        .to_string()
+    })
+}
+
+/// Restart the board on the recorded level and modes with tile RNG seeded by
+/// `seed` and play the recorded keys back at their offsets. Only this run is
+/// seeded; later runs use the previous `seed_board_rng` setting.
+#[wasm_bindgen]
+pub fn play_replay(seed: u64, json: &str) -> Result<(), JsValue> {
+    let err = |m: &str| JsValue::from_str(m);
+    let v: Value = serde_json::from_str(json).map_err(|e| err(&e.to_string()))?;
+    let level = v["level"].as_u64().ok_or_else(|| err("missing level"))? as usize;
+    let flag = |name: &str| v[name].as_bool().unwrap_or(false);
+    let modes = Modes {
+        strict: flag("strict"),
+        forgiving: flag("forgiving"),
+        ignore_tones: flag("ignore_tones"),
+        numpad_nav: flag("numpad_nav"),
+    };
+    let mut pending = VecDeque::new();
+    for k in v["keys"].as_array().ok_or_else(|| err("missing keys"))? {
+        let (Some(t), Some(key)) = (k[0].as_f64(), k[1].as_str()) else {
+            return Err(err("malformed key entry"));
+        };
+        pending.push_back((t, key.to_string()));
+    }
+    let previous = RNG_SEED.with(|s| s.replace(Some(seed)));
+    REPLAY.with(|r| r.borrow_mut().starting_playback = true);
+    let started = start_board_mode_at(level);
+    RNG_SEED.with(|s| s.set(previous));
+    REPLAY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.starting_playback = false;
+        r.pending = pending;
+    });
+    started?;
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            modes.apply(state);
+        }
+    });
+    Ok(())
+}
+
+/// A run of `state` started at `now` on `level`: anchor playback, or begin a
+/// fresh log.
+pub(super) fn begin_run(state: &BoardState, level: usize, now: f64) {
+    REPLAY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.play_start_ms = now;
+        r.pending.clear();
+        if r.recording && !r.starting_playback {
+            r.level = level;
+            r.modes = Modes::of(state);
+            r.record_start_ms = now;
+            r.log.clear();
+        }
+    });
+}
+
+/// Log an accepted key typed at `now` (no-op unless recording).
+pub(super) fn record_key(key: &str, now: f64) {
+    REPLAY.with(|r| {
+        let mut r = r.borrow_mut();
+        if r.recording {
+            let offset = now - r.record_start_ms;
+            r.log.push((offset, key.to_string()));
+        }
+    });
+}
+
+/// Move both run anchors past a pause of `delta` ms.
+pub(super) fn shift(delta: f64) {
+    REPLAY.with(|r| {
+        let mut r = r.borrow_mut();
+        r.record_start_ms += delta;
+        r.play_start_ms += delta;
+    });
+}
+
+/// Feed every pending replay key whose offset has passed by `now`, each with
+/// the time it was recorded at rather than the frame time.
+pub(super) fn drive(state: &mut BoardState, now: f64) {
+    loop {
+        let next = REPLAY.with(|r| {
+            let mut r = r.borrow_mut();
+            let due = r
+                .pending
+                .front()
+                .is_some_and(|(t, _)| r.play_start_ms + t <= now);
+            let start = r.play_start_ms;
+            if due {
+                r.pending.pop_front().map(|(t, key)| (start + t, key))
+            } else {
+                None
+            }
+        });
+        let Some((at, key)) = next else {
+            break;
+        };
+        if state.won || state.game_over {
+            break;
+        }
+        handle_board_key(state, &key, at);
+    }
+}
@@ -0,0 +1,179 @@
+// Reverse mode: tiles show pinyin and the player recalls the hanzi. A row of
+// hanzi buttons below the board holds the hanzi of every reachable neighbour
+// plus a few distractors; clicking the right one hops the cat onto the tile
+// with that hanzi, a distractor counts as a miss. Typing and tile clicks are
+// disabled so the only way forward is through the buttons.
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::{
+    BOARD_STATE, BoardState, adjacent_tiles, consume_and_hop, level_hanzi_pool, rand_index,
+    register_miss, start_board_mode_at,
+};
+
+const BAR_ID: &str = "hc-reverse-choices";
+/// Wrong answers mixed into the buttons.
+const DISTRACTORS: usize = 2;
+
+/// Start a run in reverse mode on the first level.
+#[wasm_bindgen]
+pub fn start_reverse_mode() -> Result<(), JsValue> {
+    start_board_mode_at(0)?;
+    BOARD_STATE.with(|cell| {
+        if let Some(state) = cell.borrow_mut().as_mut() {
+            state.reverse = true;
+            state.reverse_adjacent.clear();
+        }
+    });
+    Ok(())
+}
+
+/// Hanzi of the tiles the cat can hop to, in direction order.
+fn adjacent_hanzi(state: &BoardState) -> Vec<&'static str> {
+    adjacent_tiles(state)
+        .into_iter()
+        .filter_map(|(x, y, _)| {
+            state.grid[y as usize * state.level.width as usize + x as usize].map(|(h, _)| h)
+        })
+        .collect()
+}
+
+/// Buttons for `adjacent`: each distinct adjacent hanzi plus up to
+/// `distractors` other hanzi from `pool`, shuffled with `rand(len)`.
+pub(super) fn build_choices(
+    adjacent: &[&'static str],
+    pool: &[(&'static str, &'static str)],
+    distractors: usize,
+    mut rand: impl FnMut(usize) -> usize,
+) -> Vec<&'static str> {
+    let mut choices: Vec<&'static str> = Vec::new();
+    for &h in adjacent {
+        if !choices.contains(&h) {
+            choices.push(h);
+        }
+    }
+    let wanted = choices.len() + distractors;
+    let start = rand(pool.len().max(1));
+    for i in 0..pool.len() {
+        if choices.len() >= wanted {
+            break;
+        }
+        let (h, _) = pool[(start + i) % pool.len()];
+        if !choices.contains(&h) {
+            choices.push(h);
+        }
+    }
+    for i in (1..choices.len()).rev() {
+        choices.swap(i, rand(i + 1));
+    }
+    choices
+}
+
+/// Keep the buttons in step with the cat's neighbours; rebuilt only when the
+/// neighbouring hanzi change. Removes the bar once the run is over.
+pub(super) fn sync(state: &mut BoardState) {
+    if state.game_over || state.won {
+        hide();
+        return;
+    }
+    let adjacent = adjacent_hanzi(state);
+    if adjacent == state.reverse_adjacent {
+        return;
+    }
+    let choices = build_choices(
+        &adjacent,
+        &level_hanzi_pool(state.level),
+        DISTRACTORS,
+        rand_index,
+    );
+    state.reverse_adjacent = adjacent;
+    show(&choices);
+}
+
+fn show(choices: &[&str]) {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    let bar = match doc.get_element_by_id(BAR_ID) {
+        Some(bar) => bar,
+        None => {
+            let Ok(bar) = doc.create_element("div") else {
+                return;
+            };
+            bar.set_id(BAR_ID);
+            bar.set_attribute("style", "position:fixed; bottom:150px; left:50%; transform:translateX(-50%); display:flex; gap:10px; z-index:30;").ok();
+            // One delegated listener; buttons carry their hanzi in data-hanzi.
+            let closure = Closure::wrap(Box::new(move |evt: web_sys::MouseEvent| {
+                if let Some(h) = evt
+                    .target()
+                    .and_then(|t| t.dyn_into::<web_sys::Element>().ok())
+                    .and_then(|el| el.get_attribute("data-hanzi"))
+                {
+                    choose(&h);
+                }
+            }) as Box<dyn FnMut(_)>);
+            bar.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+                .ok();
+            closure.forget();
+            if let Some(body) = doc.body() {
+                body.append_child(&bar).ok();
+            }
+            bar
+        }
+    };
+    // Built node by node: dataset hanzi are free text, not markup.
+    bar.set_inner_html("");
+    for h in choices {
+        let Ok(btn) = doc.create_element("button") else {
+            continue;
+        };
+        btn.set_attribute("data-hanzi", h).ok();
+        btn.set_attribute("style", "font:32px 'Noto Serif SC', 'SimSun', serif; min-width:64px; padding:6px 12px; background:#222; color:#fff; border:1px solid #555; border-radius:8px; cursor:pointer;").ok();
+        btn.set_text_content(Some(h));
+        bar.append_child(&btn).ok();
+    }
+}
+
+/// Remove the button bar (run over or a normal run started).
+pub(super) fn hide() {
+    if let Some(bar) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(BAR_ID))
+    {
+        bar.remove();
+    }
+}
+
+/// A button was clicked: hop onto the neighbour with `hanzi`, or miss.
+fn choose(hanzi: &str) {
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let Some(state) = cell.as_mut() else {
+            return;
+        };
+        if !state.reverse
+            || state.paused
+            || state.won
+            || state.game_over
+            || state.countdown_until_ms.is_some()
+        {
+            return;
+        }
+        let target = adjacent_tiles(state).into_iter().find_map(|(x, y, _)| {
+            let idx = y as usize * state.level.width as usize + x as usize;
+            state.grid[idx]
+                .is_some_and(|(h, _)| h == hanzi)
+                .then_some((x, y, idx))
+        });
+        match target {
+            Some((x, y, idx)) => consume_and_hop(state, x, y, idx, now),
+            // No pinyin was typed, so nothing lands on the review sheet.
+            None => register_miss(state, "", now),
+        }
+    });
+}
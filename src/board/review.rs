@@ -0,0 +1,84 @@
+// Review sheet: hanzi the player misread during the current run, for studying
+// after play. A miss is recorded when a submission has the right letters for an
+// adjacent tile but the wrong (or missing) tone, e.g. `ni2` next to 你 (ni3).
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+use super::leaderboard::json_string;
+
+struct Missed {
+    hanzi: &'static str,
+    pinyin: &'static str,
+    misses: u32,
+}
+
+thread_local! {
+    static MISSED: RefCell<Vec<Missed>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Count one miss of `(hanzi, pinyin)`.
+pub(super) fn record_miss(hanzi: &'static str, pinyin: &'static str) {
+    MISSED.with(|m| {
+        let mut m = m.borrow_mut();
+        match m
+            .iter_mut()
+            .find(|e| e.hanzi == hanzi && e.pinyin == pinyin)
+        {
+            Some(e) => e.misses += 1,
+            None => m.push(Missed {
+                hanzi,
+                pinyin,
+                misses: 1,
+            }),
+        }
+    });
+}
+
+/// Forget all misses (a new run starts).
+pub(super) fn reset() {
+    MISSED.with(|m| m.borrow_mut().clear());
+}
+
+/// Whether `typed` names `pinyin`'s syllables but with different tone digits.
+pub(super) fn is_tone_miss(typed: &str, pinyin: &str) -> bool {
+    let letters = |s: &str| {
+        s.chars()
+            .filter(|c| !c.is_ascii_digit())
+            .collect::<String>()
+    };
+    typed != pinyin && letters(typed) == letters(pinyin)
+}
+
+/// Missed `(hanzi, pinyin, misses)` of this run, most-missed first.
+pub(super) fn missed_entries() -> Vec<(&'static str, &'static str, u32)> {
+    MISSED.with(|m| {
+        let mut entries: Vec<_> = m
+            .borrow()
+            .iter()
+            .map(|e| (e.hanzi, e.pinyin, e.misses))
+            .collect();
+        entries.sort_by_key(|e| std::cmp::Reverse(e.2));
+        entries
+    })
+}
+
+/// Missed characters of this run as a JSON array of `{hanzi, pinyin, misses}`,
+/// most-missed first.
+#[wasm_bindgen]
+pub fn export_missed() -> String {
+    missed_json(&missed_entries())
+}
+
+pub(super) fn missed_json(entries: &[(&str, &str, u32)]) -> String {
+    let items: Vec<String> = entries
+        .iter()
+        .map(|(h, p, n)| {
+            format!(
+                r#"{{"hanzi":{},"pinyin":{},"misses":{n}}}"#,
+                json_string(h),
+                json_string(p)
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
@@ -0,0 +1,100 @@
+// Save / continue for board runs (feature = "serde_json").
+// Only durable progress is exported: level index, score, lives and the grid.
+// Transient effects and the beat phase are not, so an import re-seeds the beat
+// clock to now. Grid cells are `[hanzi, pinyin]` pairs or `null`.
+use serde_json::{Value, json};
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::{BOARD_STATE, LevelDesc, MAX_LIVES, level_hanzi_pool, levels, set_level};
+
+/// Serialize the running board's progress to JSON (`"null"` if not started).
+#[wasm_bindgen]
+pub fn export_board_state() -> String {
+    BOARD_STATE.with(|cell| {
+        let cell = cell.borrow();
+        let Some(state) = cell.as_ref() else {
+            return Value::Null.to_string();
+        };
+        let grid: Vec<Value> = state
+            .grid
+            .iter()
+            .map(|c| match c {
+                Some((h, p)) => json!([h, p]),
+                None => Value::Null,
+            })
+            .collect();
+        json!({
+            "level_index": state.level_index,
+            "score": state.score,
+            "lives": state.lives,
+            "grid": grid,
+        })
+        .to_string()
+    })
+}
+
+/// Restore progress produced by `export_board_state()` into the running board.
+/// Rejects unknown levels, grids that do not match the level's dimensions and
+/// hanzi that the level could not contain.
+#[wasm_bindgen]
+pub fn import_board_state(json: &str) -> Result<(), JsValue> {
+    let err = |m: &str| JsValue::from_str(m);
+    let v: Value = serde_json::from_str(json).map_err(|e| err(&e.to_string()))?;
+    let level_index = v["level_index"]
+        .as_u64()
+        .ok_or_else(|| err("missing level_index"))? as usize;
+    let lvl = *levels()
+        .get(level_index)
+        .ok_or_else(|| err("level_index out of range"))?;
+    let score = v["score"].as_i64().ok_or_else(|| err("missing score"))?;
+    let lives = v["lives"].as_i64().ok_or_else(|| err("missing lives"))?;
+    let cells = v["grid"].as_array().ok_or_else(|| err("missing grid"))?;
+    if cells.len() != lvl.width as usize * lvl.height as usize {
+        return Err(err("grid size does not match level"));
+    }
+    let mut grid = Vec::with_capacity(cells.len());
+    for c in cells {
+        if c.is_null() {
+            grid.push(None);
+            continue;
+        }
+        let (Some(h), Some(p)) = (c[0].as_str(), c[1].as_str()) else {
+            return Err(err("malformed grid cell"));
+        };
+        let pair = resolve_hanzi(lvl, h, p).ok_or_else(|| err("unknown hanzi in grid"))?;
+        grid.push(Some(pair));
+    }
+
+    let now = window()
+        .and_then(|w| w.performance())
+        .map(|p| p.now())
+        .unwrap_or(0.0);
+    BOARD_STATE.with(|cell| {
+        let mut cell = cell.borrow_mut();
+        let state = cell.as_mut().ok_or_else(|| err("board not started"))?;
+        // Resets the cat, modifiers and beat clock for the level.
+        set_level(state, level_index, now, 0);
+        state.grid = grid;
+        let cat_idx = state.cat_y as usize * lvl.width as usize + state.cat_x as usize;
+        state.grid[cat_idx] = None;
+        state.score = score;
+        state.last_coin_score = score; // imported score does not mint coins
+        state.last_life_award_score = score; // nor regain lives
+        state.lives = lives.clamp(0, MAX_LIVES as i64) as i32;
+        state.game_over = state.lives == 0;
+        state.won = false;
+        state.board_combo = 0;
+        Ok(())
+    })
+}
+
+/// Map an imported pair back to the level's static data so the grid keeps
+/// borrowing `'static` strings.
+fn resolve_hanzi(lvl: &LevelDesc, h: &str, p: &str) -> Option<(&'static str, &'static str)> {
+    level_hanzi_pool(lvl)
+        .into_iter()
+        .chain(crate::SINGLE_HANZI.iter().copied())
+        .chain(lvl.tiles.iter().filter_map(|t| t.fixed_hanzi))
+        .find(|&(fh, fp)| fh == h && fp == p)
+}
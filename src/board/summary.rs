@@ -0,0 +1,184 @@
+// End-of-run summary modal, shown once when a run ends (game over or final
+// win): score, accuracy, best combo, matches per minute, a letter grade, coins
+// the review sheet of missed characters and a per-character timing table (hits,
+// misses, mean time from spawn to match). "Play Again" restarts the level
+// the run ended on.
+use wasm_bindgen::JsCast;
+use wasm_bindgen::prelude::*;
+use web_sys::window;
+
+use super::{BoardState, format_score, review, start_board_mode_at, timing};
+
+const MODAL_ID: &str = "hc-summary";
+
+/// Figures shown in the summary, gathered from the board when the run ends.
+pub(super) struct RunSummary {
+    pub(super) score: i64,
+    pub(super) hits: u32,
+    pub(super) misses: u32,
+    pub(super) max_combo: u32,
+    pub(super) play_ms: f64,
+    pub(super) coins: i64,
+    pub(super) won: bool,
+    pub(super) locale: &'static str,
+    pub(super) missed: Vec<(&'static str, &'static str, u32)>,
+    pub(super) timing: Vec<timing::Row>,
+}
+
+impl RunSummary {
+    fn from_state(state: &BoardState, now: f64) -> Self {
+        RunSummary {
+            score: state.score,
+            hits: state.hits,
+            misses: state.misses,
+            max_combo: state.max_combo,
+            play_ms: (now - state.run_start_ms).max(0.0),
+            coins: state.coins,
+            won: state.won,
+            locale: state.locale,
+            missed: review::missed_entries(),
+            timing: timing::rows(),
+        }
+    }
+
+    pub(super) fn accuracy(&self) -> f64 {
+        accuracy_pct(self.hits, self.misses)
+    }
+
+    /// Matched characters per minute of (unpaused) play.
+    pub(super) fn cpm(&self) -> f64 {
+        if self.play_ms <= 0.0 {
+            0.0
+        } else {
+            self.hits as f64 * 60_000.0 / self.play_ms
+        }
+    }
+}
+
+/// Share of submissions that matched, in percent (100 before any).
+pub(super) fn accuracy_pct(hits: u32, misses: u32) -> f64 {
+    let total = hits + misses;
+    if total == 0 {
+        100.0
+    } else {
+        hits as f64 * 100.0 / total as f64
+    }
+}
+
+/// Letter grade for an accuracy percentage.
+pub(super) fn grade(accuracy: f64) -> &'static str {
+    match accuracy {
+        a if a >= 95.0 => "S",
+        a if a >= 85.0 => "A",
+        a if a >= 70.0 => "B",
+        a if a >= 50.0 => "C",
+        _ => "D",
+    }
+}
+
+/// `s` with HTML markup characters escaped, for dataset strings going into
+/// `innerHTML` (runtime datasets are free text).
+pub(super) fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Modal body markup.
+pub(super) fn build_summary(s: &RunSummary) -> String {
+    let acc = s.accuracy();
+    let title = if s.won { "YOU WIN" } else { "GAME OVER" };
+    let missed = if s.missed.is_empty() {
+        "<li>None, nice!</li>".to_string()
+    } else {
+        s.missed
+            .iter()
+            .map(|(h, p, n)| {
+                format!(
+                    "<li>{} <span style='color:#aaa;'>{}</span> x{n}</li>",
+                    escape_html(h),
+                    escape_html(p)
+                )
+            })
+            .collect()
+    };
+    let timing: String = s
+        .timing
+        .iter()
+        .map(|(h, p, hits, misses, ms)| {
+            let ms = ms.map_or("-".to_string(), |ms| format!("{:.2}s", ms / 1000.0));
+            format!(
+                "<tr><td>{} <span style='color:#aaa;'>{}</span></td>\
+                 <td>{hits}</td><td>{misses}</td><td>{ms}</td></tr>",
+                escape_html(h),
+                escape_html(p)
+            )
+        })
+        .collect();
+    format!(
+        "<div style='font-size:26px; text-align:center; margin-bottom:10px;'>{title}</div>\
+         <div>Score: {score}</div>\
+         <div>Accuracy: {acc:.1}% (grade {grade})</div>\
+         <div>Max combo: {combo}</div>\
+         <div>CPM: {cpm:.1}</div>\
+         <div>Coins: {coins}</div>\
+         <div style='margin-top:10px;'>Missed:</div>\
+         <ul style='margin:4px 0 12px 18px; padding:0; max-height:160px; overflow:auto;'>{missed}</ul>\
+         <div>Timing (slowest first):</div>\
+         <div style='margin:4px 0 12px 0; max-height:160px; overflow:auto;'>\
+         <table style='border-collapse:collapse; width:100%; text-align:left;'>\
+         <tr style='color:#aaa;'><th>Char</th><th>Hit</th><th>Miss</th><th>Time</th></tr>{timing}</table></div>\
+         <button id='{MODAL_ID}-again' style='font:inherit; padding:6px 14px; background:#222; color:#ffd166; border:1px solid #555; border-radius:6px; cursor:pointer;'>Play Again</button>",
+        score = format_score(s.score, s.locale),
+        grade = grade(acc),
+        combo = s.max_combo,
+        cpm = s.cpm(),
+        coins = s.coins,
+    )
+}
+
+/// Show the modal for the run that just ended on `state`.
+pub(super) fn show(state: &BoardState, now: f64) {
+    let Some(doc) = window().and_then(|w| w.document()) else {
+        return;
+    };
+    hide();
+    let Ok(modal) = doc.create_element("div") else {
+        return;
+    };
+    modal.set_id(MODAL_ID);
+    modal.set_attribute("style", "position:fixed; left:50%; top:40%; transform:translate(-50%,-50%); min-width:280px; padding:18px 22px; background:rgba(12,12,12,0.94); border:2px solid #333; border-radius:14px; font-family:'Fira Code', monospace; font-size:15px; color:#ffd166; z-index:60; line-height:1.5;").ok();
+    modal.set_inner_html(&build_summary(&RunSummary::from_state(state, now)));
+    let level_index = state.level_index;
+    if let Some(body) = doc.body() {
+        body.append_child(&modal).ok();
+    }
+    if let Some(btn) = doc.get_element_by_id(&format!("{MODAL_ID}-again")) {
+        let closure = Closure::wrap(Box::new(move |_evt: web_sys::MouseEvent| {
+            hide();
+            start_board_mode_at(level_index).ok();
+        }) as Box<dyn FnMut(_)>);
+        btn.add_event_listener_with_callback("click", closure.as_ref().unchecked_ref())
+            .ok();
+        closure.forget();
+    }
+}
+
+/// Remove the modal if it is open.
+pub(super) fn hide() {
+    if let Some(m) = window()
+        .and_then(|w| w.document())
+        .and_then(|d| d.get_element_by_id(MODAL_ID))
+    {
+        m.remove();
+    }
+}
@@ -0,0 +1,126 @@
+// Per-character timing log for the current run: every matched tile with the
+// time from its spawn to the match, and every miss aimed at a tile. The summary
+// modal shows it per character (slowest first) so learners see which
+// characters hold them up; `export_timing_log()` returns the raw log.
+use std::cell::RefCell;
+use wasm_bindgen::prelude::*;
+
+use super::leaderboard::json_string;
+
+struct Entry {
+    hanzi: &'static str,
+    pinyin: &'static str,
+    // Spawn-to-match time; `None` for a miss.
+    reaction_ms: Option<f64>,
+}
+
+/// One summary row: `(hanzi, pinyin, hits, misses, mean reaction ms)`.
+pub(super) type Row = (&'static str, &'static str, u32, u32, Option<f64>);
+
+thread_local! {
+    static LOG: RefCell<Vec<Entry>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Log a match of `(hanzi, pinyin)` `reaction_ms` after the tile spawned.
+pub(super) fn record_hit(hanzi: &'static str, pinyin: &'static str, reaction_ms: f64) {
+    LOG.with(|l| {
+        l.borrow_mut().push(Entry {
+            hanzi,
+            pinyin,
+            reaction_ms: Some(reaction_ms.max(0.0)),
+        })
+    });
+}
+
+/// Log a missed submission aimed at `(hanzi, pinyin)`.
+pub(super) fn record_miss(hanzi: &'static str, pinyin: &'static str) {
+    LOG.with(|l| {
+        l.borrow_mut().push(Entry {
+            hanzi,
+            pinyin,
+            reaction_ms: None,
+        })
+    });
+}
+
+/// Forget the log (a new run starts).
+pub(super) fn reset() {
+    LOG.with(|l| l.borrow_mut().clear());
+}
+
+/// Log entries as `(hanzi, pinyin, reaction_ms)`, oldest first.
+fn entries() -> Vec<(&'static str, &'static str, Option<f64>)> {
+    LOG.with(|l| {
+        l.borrow()
+            .iter()
+            .map(|e| (e.hanzi, e.pinyin, e.reaction_ms))
+            .collect()
+    })
+}
+
+/// Summary rows of this run.
+pub(super) fn rows() -> Vec<Row> {
+    summarize(&entries())
+}
+
+/// One row per character in `log`: hits, misses and mean reaction time of the
+/// hits, slowest first; characters that were only missed come first.
+pub(super) fn summarize(log: &[(&'static str, &'static str, Option<f64>)]) -> Vec<Row> {
+    let mut rows: Vec<(Row, f64)> = Vec::new();
+    for &(hanzi, pinyin, ms) in log {
+        let i = match rows
+            .iter()
+            .position(|((h, p, ..), _)| *h == hanzi && *p == pinyin)
+        {
+            Some(i) => i,
+            None => {
+                rows.push(((hanzi, pinyin, 0, 0, None), 0.0));
+                rows.len() - 1
+            }
+        };
+        let ((.., hits, misses, _), total) = &mut rows[i];
+        match ms {
+            Some(ms) => {
+                *hits += 1;
+                *total += ms;
+            }
+            None => *misses += 1,
+        }
+    }
+    let mut rows: Vec<Row> = rows
+        .into_iter()
+        .map(|((h, p, hits, misses, _), total)| {
+            (h, p, hits, misses, (hits > 0).then(|| total / hits as f64))
+        })
+        .collect();
+    rows.sort_by(|a, b| {
+        b.4.unwrap_or(f64::INFINITY)
+            .total_cmp(&a.4.unwrap_or(f64::INFINITY))
+    });
+    rows
+}
+
+/// This run's timing log as a JSON array of `{hanzi, pinyin, hit, ms}`, oldest
+/// first; `ms` is the spawn-to-match time of a hit and `null` for a miss.
+#[wasm_bindgen]
+pub fn export_timing_log() -> String {
+    timing_json(&entries())
+}
+
+pub(super) fn timing_json(log: &[(&str, &str, Option<f64>)]) -> String {
+    let items: Vec<String> = log
+        .iter()
+        .map(|(h, p, ms)| {
+            let (hit, ms) = match ms {
+                Some(ms) => (true, format!("{}", ms.round())),
+                None => (false, "null".to_string()),
+            };
+            format!(
+                r#"{{"hanzi":{},"pinyin":{},"hit":{hit},"ms":{ms}}}"#,
+                json_string(h),
+                json_string(p)
+            )
+        })
+        .collect();
+    format!("[{}]", items.join(","))
+}
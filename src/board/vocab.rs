@@ -0,0 +1,241 @@
+// Per-character metadata for the hanzi pools (SINGLE_HANZI and the level
+// tables) and the spawn filter built on it. Pools stay `(hanzi, pinyin)`
+// pairs, which is what the grid stores; `HanziInfo` is looked up by hanzi.
+//   set_categories("animal,nature") - only spawn characters tagged with one
+//                                     of these categories ("" = no filter)
+//   set_hsk_range(1, 2)              - only spawn HSK 1-2 characters
+//   set_hsk_progression(true)        - start on HSK 1 and unlock higher levels
+//                                      as the run's score progresses
+// HSK levels follow HSK 2.0 and use the lowest-level word the character
+// commonly appears in (e.g. 脑 as in 电脑, HSK 1). `freq` is a relative spawn
+// weight (10 = very common, 3 = rare) applied within the filtered pool.
+use std::cell::{Cell, RefCell};
+use wasm_bindgen::prelude::*;
+
+pub(super) struct HanziInfo {
+    pub(super) hanzi: &'static str,
+    pub(super) category: &'static str,
+    pub(super) hsk: u8,
+    pub(super) freq: u32,
+}
+
+const fn info(hanzi: &'static str, category: &'static str, hsk: u8, freq: u32) -> HanziInfo {
+    HanziInfo {
+        hanzi,
+        category,
+        hsk,
+        freq,
+    }
+}
+
+/// Active spawn filter.
+#[derive(Clone, Default)]
+pub(super) struct Filter {
+    pub(super) categories: Vec<String>, // empty = all
+    pub(super) hsk: Option<(u8, u8)>,   // inclusive band, None = all
+    pub(super) hsk_cap: Option<u8>,     // highest unlocked level (progression mode)
+}
+
+impl Filter {
+    fn allows(&self, hanzi: &str) -> bool {
+        if self.categories.is_empty() && self.hsk.is_none() && self.hsk_cap.is_none() {
+            return true;
+        }
+        lookup(hanzi).is_some_and(|i| {
+            (self.categories.is_empty() || self.categories.iter().any(|c| c == i.category))
+                && self.hsk.is_none_or(|(lo, hi)| (lo..=hi).contains(&i.hsk))
+                && self.hsk_cap.is_none_or(|cap| i.hsk <= cap)
+        })
+    }
+}
+
+/// Metadata for every hanzi that can spawn on the board.
+pub(super) static HANZI_INFO: &[HanziInfo] = &[
+    info("猫", "animal", 1, 6),
+    info("鱼", "animal", 2, 4),
+    info("火", "nature", 1, 6),
+    info("山", "nature", 3, 6),
+    info("水", "nature", 1, 10),
+    info("月", "nature", 1, 10),
+    info("日", "nature", 2, 10),
+    info("天", "nature", 1, 10),
+    info("雨", "nature", 1, 6),
+    info("风", "nature", 3, 6),
+    info("花", "nature", 3, 6),
+    info("林", "nature", 4, 3),
+    info("气", "nature", 1, 6),
+    info("田", "nature", 6, 3),
+    info("木", "nature", 6, 3),
+    info("石", "nature", 5, 3),
+    info("星", "nature", 1, 6),
+    info("雪", "nature", 2, 3),
+    info("光", "nature", 4, 6),
+    info("影", "nature", 1, 4),
+    info("海", "nature", 4, 6),
+    info("口", "body", 3, 10),
+    info("手", "body", 2, 10),
+    info("目", "body", 3, 6),
+    info("耳", "body", 3, 3),
+    info("足", "body", 2, 6),
+    info("脑", "body", 1, 4),
+    info("食", "food", 4, 6),
+    info("米", "food", 1, 6),
+    info("人", "people", 1, 10),
+    info("老", "people", 1, 6),
+    info("师", "people", 1, 6),
+    info("朋", "people", 1, 6),
+    info("友", "people", 1, 6),
+    info("爸爸", "people", 1, 6),
+    info("妈妈", "people", 1, 6),
+    info("老师", "people", 1, 6),
+    info("学生", "people", 1, 6),
+    info("朋友", "people", 1, 6),
+    info("同学", "people", 1, 6),
+    info("中", "direction", 1, 10),
+    info("上", "direction", 1, 10),
+    info("下", "direction", 1, 10),
+    info("左", "direction", 2, 6),
+    info("右", "direction", 2, 6),
+    info("国", "place", 1, 10),
+    info("学", "learning", 1, 10),
+    info("汉", "learning", 1, 6),
+    info("字", "learning", 1, 6),
+    info("语", "learning", 1, 6),
+    info("言", "learning", 4, 6),
+    info("校", "learning", 1, 6),
+    info("电", "tech", 1, 10),
+    info("机", "tech", 1, 6),
+    info("心", "feeling", 3, 10),
+    info("爱", "feeling", 1, 6),
+    info("梦", "feeling", 4, 3),
+    info("你", "basic", 1, 10),
+    info("好", "basic", 1, 10),
+    info("大", "basic", 1, 10),
+    info("小", "basic", 1, 10),
+    info("黑", "basic", 2, 6),
+    info("和", "basic", 1, 10),
+    info("物", "basic", 3, 6),
+];
+
+/// Default unlock schedule: entry `i` is the run progress (0..=1) at which
+/// HSK `i + 1` becomes eligible.
+pub(super) const DEFAULT_HSK_UNLOCKS: [f64; 6] = [0.0, 0.15, 0.3, 0.5, 0.7, 0.85];
+
+thread_local! {
+    static FILTER: RefCell<Filter> = RefCell::new(Filter::default());
+    static HSK_PROGRESSION: Cell<bool> = const { Cell::new(false) };
+    static HSK_UNLOCKS: RefCell<Vec<f64>> = RefCell::new(DEFAULT_HSK_UNLOCKS.to_vec());
+    // Whether the "filter matches nothing" warning was already logged for the
+    // current filter, so refills do not spam the console.
+    static WARNED: Cell<bool> = const { Cell::new(false) };
+}
+
+pub(super) fn lookup(hanzi: &str) -> Option<&'static HanziInfo> {
+    HANZI_INFO.iter().find(|i| i.hanzi == hanzi)
+}
+
+/// Spawn weight of `hanzi` (1 for characters without metadata).
+pub(super) fn weight(hanzi: &str) -> u32 {
+    lookup(hanzi).map_or(1, |i| i.freq)
+}
+
+/// Restrict spawns to characters tagged with one of the comma-separated
+/// categories (e.g. "animal,nature"); an empty string removes the filter.
+/// Applies to tiles refilled from now on.
+#[wasm_bindgen]
+pub fn set_categories(csv: &str) {
+    FILTER.with(|f| f.borrow_mut().categories = parse_csv(csv));
+    WARNED.with(|w| w.set(false));
+}
+
+/// Restrict spawns to HSK levels `min..=max` (bounds in either order);
+/// `set_hsk_range(1, 6)` allows everything again. Combines with the category
+/// filter.
+#[wasm_bindgen]
+pub fn set_hsk_range(min: u8, max: u8) {
+    FILTER.with(|f| f.borrow_mut().hsk = Some((min.min(max), min.max(max))));
+    WARNED.with(|w| w.set(false));
+}
+
+/// Progression mode: runs start with HSK 1 only and higher levels unlock as
+/// the score approaches the final clear (see `set_hsk_unlock_schedule`).
+#[wasm_bindgen]
+pub fn set_hsk_progression(enabled: bool) {
+    HSK_PROGRESSION.with(|p| p.set(enabled));
+    if !enabled {
+        FILTER.with(|f| f.borrow_mut().hsk_cap = None);
+    }
+}
+
+/// Replace the unlock schedule: `thresholds[i]` is the progress (0..=1) at
+/// which HSK `i + 1` unlocks. An empty list restores the default.
+#[wasm_bindgen]
+pub fn set_hsk_unlock_schedule(thresholds: Vec<f64>) {
+    let schedule = if thresholds.is_empty() {
+        DEFAULT_HSK_UNLOCKS.to_vec()
+    } else {
+        thresholds
+    };
+    HSK_UNLOCKS.with(|u| *u.borrow_mut() = schedule);
+}
+
+/// Highest HSK level unlocked at `progress` (always at least 1).
+pub(super) fn hsk_unlocked(progress: f64, schedule: &[f64]) -> u8 {
+    let unlocked = schedule.iter().filter(|&&t| progress >= t).count();
+    unlocked.clamp(1, u8::MAX as usize) as u8
+}
+
+/// Refresh the progression cap for the run's `progress` (no-op unless
+/// progression mode is on).
+pub(super) fn update_progression(progress: f64) {
+    if !HSK_PROGRESSION.with(|p| p.get()) {
+        return;
+    }
+    let cap = HSK_UNLOCKS.with(|u| hsk_unlocked(progress, &u.borrow()));
+    FILTER.with(|f| f.borrow_mut().hsk_cap = Some(cap));
+}
+
+/// HSK levels present in the dataset, ascending, for building a selector.
+#[wasm_bindgen]
+pub fn available_hsk_levels() -> Vec<u8> {
+    let mut levels: Vec<u8> = HANZI_INFO.iter().map(|i| i.hsk).collect();
+    levels.sort_unstable();
+    levels.dedup();
+    levels
+}
+
+pub(super) fn parse_csv(csv: &str) -> Vec<String> {
+    csv.split(',')
+        .map(|s| s.trim().to_ascii_lowercase())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+/// Entries of `pool` the filter allows. May be empty.
+pub(super) fn apply_filter(
+    pool: &[(&'static str, &'static str)],
+    filter: &Filter,
+) -> Vec<(&'static str, &'static str)> {
+    pool.iter()
+        .copied()
+        .filter(|(h, _)| filter.allows(h))
+        .collect()
+}
+
+/// `pool` narrowed by the active filter; falls back to the whole pool (with
+/// a console warning) when the filter would leave nothing to spawn.
+pub(super) fn filtered_pool(
+    pool: &[(&'static str, &'static str)],
+) -> Vec<(&'static str, &'static str)> {
+    let filtered = FILTER.with(|f| apply_filter(pool, &f.borrow()));
+    if !filtered.is_empty() {
+        return filtered;
+    }
+    if !WARNED.with(|w| w.replace(true)) {
+        web_sys::console::warn_1(
+            &"hanzi-cat: category/HSK filter matches no characters here; using the full list"
+                .into(),
+        );
+    }
+    pool.to_vec()
+}
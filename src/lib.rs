@@ -47,16 +47,42 @@ pub const MULTI_HANZI: &[(&str, &str)] = &[
 // Unified entrypoint
 // -----------------------------------------------------------------------------
 
+/// Start the game. After `stop_game()` this can be called again and sets the
+/// board up from scratch.
 #[wasm_bindgen]
 pub fn start_game() -> Result<(), JsValue> {
     // Launch board mode (default gameplay path)
     board::start_board_mode()
 }
 
+/// Mount the game without starting it: the canvas shows a "Ready? Click to
+/// start" overlay, and the click (a user gesture, so audio may play) starts the
+/// board. `start_game()` also starts it.
 #[wasm_bindgen]
-pub fn purchase_powerup(_kind: &str) -> bool {
-    // Powerups belonged to legacy falling-note system; always return false for now.
-    false
+pub fn mount_game() -> Result<(), JsValue> {
+    board::mount_board_mode()
+}
+
+/// Start the game on a canvas of `width` x `height` pixels instead of the
+/// default size, e.g. when embedding in a fixed-size widget.
+#[wasm_bindgen]
+pub fn start_game_sized(width: f64, height: f64) -> Result<(), JsValue> {
+    board::start_board_mode_sized(width, height)
+}
+
+/// Shut the game down: cancel the animation loop, detach its keyboard and mouse listeners,
+/// drop the board state and remove the canvas and overlays. For pages that
+/// mount and unmount the game; `start_game()` may be called again afterwards.
+#[wasm_bindgen]
+pub fn stop_game() {
+    board::stop_board_mode();
+}
+
+/// Buy a board powerup ("shield", "slow" or "clear") with coins earned from
+/// score. Returns `true` if it was bought and activated.
+#[wasm_bindgen]
+pub fn purchase_powerup(kind: &str) -> bool {
+    board::purchase_powerup(kind)
 }
 
 // Internal helper (currently unused) retained for potential timing utilities.
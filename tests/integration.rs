@@ -2,10 +2,14 @@
 // These tests avoid wasm-specific functionality and exercise pure Rust logic so
 // they can run under `cargo test` on the host.
 
-// Assert legacy powerups always return false (purchase_powerup currently disabled)
+// Without a running board there are no coins, so purchases must fail; unknown
+// kinds always fail.
 #[test]
-fn purchase_powerup_returns_false() {
+fn purchase_powerup_fails_without_coins() {
     assert!(!hanzi_cat::purchase_powerup("shield"));
+    assert!(!hanzi_cat::purchase_powerup("slow"));
+    assert!(!hanzi_cat::purchase_powerup("clear"));
+    assert!(!hanzi_cat::purchase_powerup("unknown"));
 }
 
 // Basic dataset sanity check: ensure the SINGLE_HANZI dataset is non-empty.